@@ -5,6 +5,7 @@ echo "checks that this builds on std+no_std + that all tests run + that all feat
 cargo build --all-targets
 
 cargo test --all-targets
+cargo test --all-targets --all-features
 
 cargo bench
 
@@ -0,0 +1,410 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for [`AdaptiveAnalyzer`], which selects the FFT length per frame
+//! based on the signal content. Long FFTs resolve low frequencies, e.g., bass
+//! tones, whereas short FFTs resolve transients, e.g., clicks, in time.
+
+use crate::error::SpectrumAnalyzerError;
+use crate::scaling::divide_by_N;
+use crate::windows::WindowType;
+use crate::{samples_fft_to_spectrum, FrequencyLimit, FrequencySpectrum, MAX_SAMPLES_LEN};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+
+/// Selects the FFT length for the next frame. It gets the spectrum of the
+/// previous frame, on the common frequency axis, and returns an index into
+/// the FFT lengths of the [`AdaptiveAnalyzer`], which are sorted ascending.
+/// Indices that are out of bounds select the longest FFT.
+pub type FftLenSelector = dyn Fn(&FrequencySpectrum) -> usize;
+
+/// A spectrum of a frame analyzed by [`AdaptiveAnalyzer::process`].
+#[derive(Debug)]
+pub struct AdaptiveFrame {
+    /// FFT length that was chosen for this frame.
+    pub fft_len: usize,
+    /// Spectrum of the frame, resampled onto the common frequency axis of
+    /// the analyzer. Hence, all frames have the same shape.
+    pub spectrum: FrequencySpectrum,
+}
+
+/// Analyzes consecutive frames with an FFT length that is selected per frame
+/// by a cheap heuristic on the previous frame, e.g., its spectral centroid.
+/// The first frame is analyzed with the longest FFT.
+///
+/// To make the frames comparable, each spectrum is
+/// - divided by the FFT length (see [`divide_by_N`]), so that the magnitude
+///   of a sine doesn't depend on the FFT length, and
+/// - resampled onto a common frequency axis with equally spaced points from
+///   `0 Hz` to the Nyquist frequency (see [`FrequencySpectrum::fit_to_length`]).
+///
+/// Optionally, the analyzer maintains a running average of all frames, e.g.,
+/// for a smoothed real-time display. See [`Self::with_running_average`].
+pub struct AdaptiveAnalyzer {
+    /// Supported FFT lengths, sorted ascending.
+    fft_lens: Vec<usize>,
+    /// Sampling rate in Hertz.
+    sampling_rate: u32,
+    /// Number of points of the common frequency axis.
+    axis_len: usize,
+    /// Window that is applied to each frame.
+    window: WindowType,
+    /// See [`FftLenSelector`].
+    selector: Box<FftLenSelector>,
+    /// Index into `fft_lens` for the next frame.
+    next_index: usize,
+    /// Decay factor of the running average, if enabled.
+    decay: Option<f32>,
+    /// Running average of all frames so far.
+    running_average: Option<FrequencySpectrum>,
+}
+
+impl AdaptiveAnalyzer {
+    /// Constructor.
+    ///
+    /// ## Parameters
+    /// * `fft_lens` Supported FFT lengths, e.g., `[1024, 4096, 16384]`. Each
+    ///              must be a power of two and not bigger than
+    ///              [`MAX_SAMPLES_LEN`].
+    /// * `sampling_rate` Sampling rate in Hertz. Must be bigger than `0`.
+    /// * `axis_len` Number of points of the common frequency axis. Must be at
+    ///              least two.
+    /// * `window` Window that is applied to each frame.
+    /// * `selector` See [`FftLenSelector`].
+    ///
+    /// ## Panics
+    /// If one of the parameters is invalid.
+    #[must_use]
+    pub fn new(
+        fft_lens: &[usize],
+        sampling_rate: u32,
+        axis_len: usize,
+        window: WindowType,
+        selector: Box<FftLenSelector>,
+    ) -> Self {
+        assert!(!fft_lens.is_empty(), "at least one FFT length is required");
+        assert!(
+            fft_lens
+                .iter()
+                .all(|len| len.is_power_of_two() && *len >= 2 && *len <= MAX_SAMPLES_LEN),
+            "each FFT length must be a power of two in interval [2; MAX_SAMPLES_LEN]"
+        );
+        assert!(axis_len >= 2, "at least two points are required");
+        assert!(
+            sampling_rate > 0,
+            "the sampling rate must be bigger than 0 Hz"
+        );
+        let mut fft_lens = fft_lens.to_vec();
+        fft_lens.sort_unstable();
+        fft_lens.dedup();
+        Self {
+            next_index: fft_lens.len() - 1,
+            fft_lens,
+            sampling_rate,
+            axis_len,
+            window,
+            selector,
+            decay: None,
+            running_average: None,
+        }
+    }
+
+    /// Enables a running average of all frames, i.e., an exponential moving
+    /// average: each frame updates it to
+    /// `decay * running_average + (1 - decay) * frame`. The first frame
+    /// initializes it. See [`Self::running_average`].
+    ///
+    /// ## Parameters
+    /// * `decay` Decay factor in interval `[0.0; 1.0)`. The bigger, the
+    ///           smoother. `0.0` keeps only the latest frame.
+    ///
+    /// ## Panics
+    /// If `decay` is not in interval `[0.0; 1.0)`.
+    #[must_use]
+    pub fn with_running_average(mut self, decay: f32) -> Self {
+        assert!(
+            (0.0..1.0).contains(&decay),
+            "decay must be in interval [0.0; 1.0)"
+        );
+        self.decay = Some(decay);
+        self
+    }
+
+    /// Like [`Self::new`] with a selector based on the spectral centroid of
+    /// the previous frame (see [`FrequencySpectrum::spectral_centroid`]): if
+    /// it is above `centroid_threshold_hz`, e.g., for transients, the
+    /// shortest FFT is chosen; otherwise the longest.
+    #[must_use]
+    pub fn with_centroid_heuristic(
+        fft_lens: &[usize],
+        sampling_rate: u32,
+        axis_len: usize,
+        window: WindowType,
+        centroid_threshold_hz: f32,
+    ) -> Self {
+        Self::new(
+            fft_lens,
+            sampling_rate,
+            axis_len,
+            window,
+            Box::new(move |previous: &FrequencySpectrum| {
+                if previous.spectral_centroid() > centroid_threshold_hz {
+                    0
+                } else {
+                    usize::MAX
+                }
+            }),
+        )
+    }
+
+    /// Returns the supported FFT lengths, sorted ascending.
+    #[inline]
+    #[must_use]
+    pub fn fft_lens(&self) -> &[usize] {
+        &self.fft_lens
+    }
+
+    /// Returns the running average of all frames on the common frequency
+    /// axis, or `None` if it is not enabled (see
+    /// [`Self::with_running_average`]) or no frame was processed yet.
+    #[inline]
+    #[must_use]
+    pub const fn running_average(&self) -> Option<&FrequencySpectrum> {
+        self.running_average.as_ref()
+    }
+
+    /// Analyzes the next frame. If enabled, it updates the running average
+    /// as well.
+    ///
+    /// ## Parameters
+    /// * `samples` The most recent samples, e.g., from a ring buffer. The
+    ///             last `fft_len` samples are analyzed. If there are fewer
+    ///             samples than the selected FFT length, the longest FFT
+    ///             length that fits is used.
+    ///
+    /// ## Return value
+    /// The spectrum on the common frequency axis and the chosen FFT length.
+    ///
+    /// ## Errors
+    /// If there are fewer samples than the shortest FFT length or if the
+    /// samples contain invalid values. See [`samples_fft_to_spectrum`].
+    pub fn process(&mut self, samples: &[f32]) -> Result<AdaptiveFrame, SpectrumAnalyzerError> {
+        let fft_len = self.fft_lens[..=self.next_index]
+            .iter()
+            .rev()
+            .find(|len| **len <= samples.len())
+            .copied()
+            .ok_or(SpectrumAnalyzerError::TooFewSamples)?;
+        let frame = &samples[samples.len() - fft_len..];
+
+        let spectrum = samples_fft_to_spectrum(
+            &self.window.apply(frame),
+            self.sampling_rate,
+            FrequencyLimit::All,
+            Some(&divide_by_N),
+        )?;
+
+        let axis_resolution = self.sampling_rate as f32 / 2.0 / (self.axis_len - 1) as f32;
+        let resampled_data = spectrum
+            .fit_to_length(self.axis_len)
+            .into_iter()
+            .enumerate()
+            .map(|(i, val)| ((i as f32 * axis_resolution).into(), val.into()));
+        let mut spectrum =
+            FrequencySpectrum::from_iter_sorted(resampled_data, axis_resolution, fft_len as u32);
+        spectrum.set_frame_level(frame);
+
+        self.next_index = (self.selector)(&spectrum).min(self.fft_lens.len() - 1);
+        if let Some(decay) = self.decay {
+            self.update_running_average(&spectrum, decay);
+        }
+        Ok(AdaptiveFrame { fft_len, spectrum })
+    }
+
+    /// Updates the running average with the spectrum of the latest frame.
+    fn update_running_average(&mut self, spectrum: &FrequencySpectrum, decay: f32) {
+        let previous = self.running_average.as_ref();
+        let averaged_data = spectrum.data().iter().enumerate().map(|(i, (fr, val))| {
+            // the first frame initializes the running average
+            let averaged_val = previous.map_or_else(
+                || val.val(),
+                |previous| decay * previous.data()[i].1.val() + (1.0 - decay) * val.val(),
+            );
+            (*fr, averaged_val.into())
+        });
+        let averaged = FrequencySpectrum::from_iter_sorted(
+            averaged_data,
+            spectrum.frequency_resolution(),
+            spectrum.samples_len(),
+        );
+        self.running_average = Some(averaged);
+    }
+}
+
+impl Debug for AdaptiveAnalyzer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AdaptiveAnalyzer")
+            .field("fft_lens", &self.fft_lens)
+            .field("sampling_rate", &self.sampling_rate)
+            .field("axis_len", &self.axis_len)
+            .field("window", &self.window)
+            .field("next_index", &self.next_index)
+            .field("decay", &self.decay)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adaptive_analyzer_switches_fft_len() {
+        let sampling_rate = 8000;
+        let tone = (0..4096)
+            .map(|i| libm::sinf(2.0 * core::f32::consts::PI * 60.0 * i as f32 / 8000.0))
+            .collect::<Vec<_>>();
+        // impulse inside the last 256 samples
+        let mut click = vec![0.0; 4096];
+        click[4096 - 128] = 1.0;
+
+        let mut analyzer = AdaptiveAnalyzer::with_centroid_heuristic(
+            &[4096, 256],
+            sampling_rate,
+            513,
+            WindowType::Hann,
+            500.0,
+        );
+        assert_eq!(analyzer.fft_lens(), [256, 4096]);
+
+        // the selection is based on the previous frame
+        let frames = [&tone, &tone, &click, &click, &tone, &tone];
+        let expected_fft_lens = [4096, 4096, 4096, 256, 256, 4096];
+        for (samples, expected_fft_len) in frames.iter().zip(expected_fft_lens) {
+            let frame = analyzer.process(samples).unwrap();
+            assert_eq!(frame.fft_len, expected_fft_len);
+            // common frequency axis
+            assert_eq!(frame.spectrum.data().len(), 513);
+            assert_eq!(frame.spectrum.max_fr().val(), 4000.0);
+
+            if *samples == &tone {
+                let peak_fr = frame.spectrum.max().0.val();
+                // the coarser of the FFT and the common frequency axis
+                let resolution = (sampling_rate as f32 / frame.fft_len as f32)
+                    .max(frame.spectrum.frequency_resolution());
+                assert!(
+                    (peak_fr - 60.0).abs() <= resolution,
+                    "{} Hz with FFT length {}",
+                    peak_fr,
+                    frame.fft_len
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_adaptive_analyzer_too_few_samples() {
+        let mut analyzer = AdaptiveAnalyzer::new(
+            &[256, 1024],
+            8000,
+            129,
+            WindowType::Hann,
+            Box::new(|_previous| usize::MAX),
+        );
+        // the longest FFT length that fits
+        assert_eq!(analyzer.process(&[0.0; 512]).unwrap().fft_len, 256);
+        assert_eq!(analyzer.process(&[0.0; 1024]).unwrap().fft_len, 1024);
+        assert!(matches!(
+            analyzer.process(&[0.0; 100]),
+            Err(SpectrumAnalyzerError::TooFewSamples)
+        ));
+    }
+
+    #[test]
+    fn test_adaptive_analyzer_running_average() {
+        let tone = (0..1024)
+            .map(|i| libm::sinf(2.0 * core::f32::consts::PI * 500.0 * i as f32 / 8000.0))
+            .collect::<Vec<_>>();
+        let silence = [0.0; 1024];
+        let mut analyzer = AdaptiveAnalyzer::new(
+            &[1024],
+            8000,
+            513,
+            WindowType::Hann,
+            Box::new(|_previous| usize::MAX),
+        );
+        analyzer.process(&tone).unwrap();
+        assert!(analyzer.running_average().is_none());
+
+        let mut analyzer = AdaptiveAnalyzer::new(
+            &[1024],
+            8000,
+            513,
+            WindowType::Hann,
+            Box::new(|_previous| usize::MAX),
+        )
+        .with_running_average(0.75);
+        assert!(analyzer.running_average().is_none());
+
+        // the first frame initializes the running average
+        let frame = analyzer.process(&tone).unwrap();
+        assert_eq!(
+            analyzer.running_average().unwrap().data(),
+            frame.spectrum.data()
+        );
+        let peak = frame.spectrum.freq_val_exact(500.0).val();
+
+        analyzer.process(&silence).unwrap();
+        let average = analyzer.running_average().unwrap();
+        float_cmp::assert_approx_eq!(
+            f32,
+            average.freq_val_exact(500.0).val(),
+            0.75 * peak,
+            epsilon = 1e-6
+        );
+        analyzer.process(&silence).unwrap();
+        let average = analyzer.running_average().unwrap();
+        float_cmp::assert_approx_eq!(
+            f32,
+            average.freq_val_exact(500.0).val(),
+            0.75 * 0.75 * peak,
+            epsilon = 1e-6
+        );
+        assert_eq!(average.data().len(), 513);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_adaptive_analyzer_invalid_decay() {
+        let _ =
+            AdaptiveAnalyzer::with_centroid_heuristic(&[1024], 8000, 513, WindowType::Hann, 500.0)
+                .with_running_average(1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_adaptive_analyzer_zero_sampling_rate() {
+        let _ = AdaptiveAnalyzer::with_centroid_heuristic(&[1024], 0, 513, WindowType::Hann, 500.0);
+    }
+}
@@ -0,0 +1,173 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the calibration of spectra with a known frequency response,
+//! e.g., of a measurement microphone. See [`CalibrationCurve`].
+
+use crate::FrequencySpectrum;
+use alloc::vec::Vec;
+use libm::{log10f, powf};
+
+/// Gain in dB over the frequency, e.g., the inverse of the frequency response
+/// of a measurement microphone. Between two points, the gain is interpolated
+/// linearly over the logarithm of the frequency, as calibration data is
+/// usually given per octave or per third-octave. Below the lowest and above
+/// the highest point, the gain of the nearest point is used.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalibrationCurve {
+    /// (log10 of the frequency in Hertz, gain in dB)-pairs, sorted by
+    /// frequency.
+    points: Vec<(f32, f32)>,
+}
+
+impl CalibrationCurve {
+    /// Constructor.
+    ///
+    /// ## Parameters
+    /// * `points` (frequency in Hertz, gain in dB)-pairs in any order. The
+    ///            frequencies must be bigger than `0.0`.
+    ///
+    /// ## Panics
+    /// If `points` is empty or a frequency is not bigger than `0.0`.
+    #[must_use]
+    pub fn new(points: &[(f32, f32)]) -> Self {
+        assert!(!points.is_empty(), "at least one point is required");
+        assert!(
+            points.iter().all(|(frequency, _gain_db)| *frequency > 0.0),
+            "the frequencies must be bigger than 0 Hz"
+        );
+        let mut points = points
+            .iter()
+            .map(|(frequency, gain_db)| (log10f(*frequency), *gain_db))
+            .collect::<Vec<_>>();
+        points.sort_by(|(l_fr, _), (r_fr, _)| l_fr.total_cmp(r_fr));
+        Self { points }
+    }
+
+    /// Returns the interpolated gain in dB at `frequency`.
+    #[must_use]
+    pub fn gain_db(&self, frequency: f32) -> f32 {
+        let first = self.points[0];
+        let last = self.points[self.points.len() - 1];
+        // also covers 0 Hz, i.e., the DC component
+        if frequency <= 0.0 {
+            return first.1;
+        }
+        let log_frequency = log10f(frequency);
+        if log_frequency <= first.0 {
+            return first.1;
+        }
+        if log_frequency >= last.0 {
+            return last.1;
+        }
+        // index of the first point above the frequency; in 1..len
+        let upper = self
+            .points
+            .partition_point(|(log_fr, _gain_db)| *log_fr <= log_frequency);
+        let (log_fr_a, gain_a) = self.points[upper - 1];
+        let (log_fr_b, gain_b) = self.points[upper];
+        let t = (log_frequency - log_fr_a) / (log_fr_b - log_fr_a);
+        gain_a + t * (gain_b - gain_a)
+    }
+
+    /// Returns the interpolated gain at `frequency` as linear factor for
+    /// magnitudes, i.e., `10^(gain_db / 20)`.
+    #[must_use]
+    pub fn gain(&self, frequency: f32) -> f32 {
+        powf(10.0, self.gain_db(frequency) / 20.0)
+    }
+}
+
+impl FrequencySpectrum {
+    /// Multiplies each frequency value by the gain of the calibration curve
+    /// at its frequency and updates the statistics afterwards. Use this for
+    /// (unscaled) magnitudes. See [`Self::apply_calibration_db`] for spectra
+    /// in dB.
+    #[inline]
+    pub fn apply_calibration(&mut self, curve: &CalibrationCurve) {
+        self.apply_to_bins(|fr, fr_val| fr_val * curve.gain(fr));
+    }
+
+    /// Adds the gain in dB of the calibration curve at its frequency to each
+    /// frequency value and updates the statistics afterwards. Use this for
+    /// spectra in dB, e.g., after [`crate::scaling::scale_20_times_log10`].
+    #[inline]
+    pub fn apply_calibration_db(&mut self, curve: &CalibrationCurve) {
+        self.apply_to_bins(|fr, fr_val| fr_val + curve.gain_db(fr));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Frequency, FrequencyValue};
+
+    #[test]
+    fn test_calibration_curve() {
+        // unsorted on purpose
+        let curve = CalibrationCurve::new(&[(10000.0, 20.0), (100.0, 0.0)]);
+        // nearest endpoint outside the range
+        assert_eq!(curve.gain_db(0.0), 0.0);
+        assert_eq!(curve.gain_db(50.0), 0.0);
+        assert_eq!(curve.gain_db(100.0), 0.0);
+        assert_eq!(curve.gain_db(10000.0), 20.0);
+        assert_eq!(curve.gain_db(20000.0), 20.0);
+        // 1000 Hz is in the middle on a logarithmic axis
+        float_cmp::assert_approx_eq!(f32, curve.gain_db(1000.0), 10.0, epsilon = 0.001);
+        float_cmp::assert_approx_eq!(f32, curve.gain(1000.0), 3.162_277_7, epsilon = 0.001);
+
+        let single = CalibrationCurve::new(&[(1000.0, -3.0)]);
+        assert_eq!(single.gain_db(10.0), -3.0);
+        assert_eq!(single.gain_db(1000.0), -3.0);
+        assert_eq!(single.gain_db(5000.0), -3.0);
+    }
+
+    #[test]
+    fn test_apply_calibration() {
+        let curve = CalibrationCurve::new(&[(100.0, 0.0), (10000.0, 20.0)]);
+        let data = [0.0, 1000.0, 10000.0]
+            .iter()
+            .map(|fr| (Frequency::from(*fr), FrequencyValue::from(2.0)));
+
+        let mut spectrum = FrequencySpectrum::from_iter_sorted(data.clone(), 1000.0, 20);
+        spectrum.apply_calibration(&curve);
+        let vals = spectrum.magnitudes();
+        assert_eq!(vals[0], 2.0);
+        float_cmp::assert_approx_eq!(f32, vals[1], 2.0 * 3.162_277_7, epsilon = 0.001);
+        float_cmp::assert_approx_eq!(f32, vals[2], 20.0, epsilon = 0.001);
+        float_cmp::assert_approx_eq!(f32, spectrum.max().1.val(), 20.0, epsilon = 0.001);
+
+        let mut spectrum = FrequencySpectrum::from_iter_sorted(data, 1000.0, 20);
+        spectrum.apply_calibration_db(&curve);
+        let vals = spectrum.magnitudes();
+        assert_eq!(vals[0], 2.0);
+        float_cmp::assert_approx_eq!(f32, vals[1], 12.0, epsilon = 0.001);
+        assert_eq!(vals[2], 22.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_calibration_curve_zero_frequency() {
+        let _ = CalibrationCurve::new(&[(0.0, 1.0), (100.0, 2.0)]);
+    }
+}
@@ -0,0 +1,141 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the struct [`SpectrumConfig`] that fine-tunes the analysis
+//! pipeline of [`crate::samples_fft_to_spectrum_with_config`].
+
+use crate::MAX_SAMPLES_LEN;
+
+/// Describes at which stage of the analysis pipeline the
+/// [`crate::FrequencyLimit`] is applied. This makes a difference for scaling
+/// functions that depend on [`crate::scaling::SpectrumDataStats`], such as
+/// [`crate::scaling::scale_to_zero_to_one`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum LimitStage {
+    /// The frequency limit is applied before the scaling function runs. Hence,
+    /// the statistics passed to the scaling function only cover the bins inside
+    /// the limit. This is the default and it is cheaper, as fewer bins are
+    /// processed.
+    #[default]
+    BeforeScaling,
+    /// The scaling function runs on the full spectrum (DC component to Nyquist
+    /// frequency), i.e., it sees the statistics of all bins. Afterwards, the
+    /// frequency limit is applied. Useful, if you want to normalize against
+    /// the full spectrum but are only interested in a sub-range.
+    AfterScaling,
+}
+
+/// Describes which value of each complex FFT bin ends up in the
+/// [`crate::FrequencySpectrum`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum BinValue {
+    /// The magnitude `sqrt(re * re + im * im)`. This is the default.
+    #[default]
+    Magnitude,
+    /// The power, i.e., the squared magnitude `re * re + im * im`. This is
+    /// cheaper than squaring the magnitude afterwards, as no square root is
+    /// calculated. Useful for power spectral densities. Use
+    /// `10 * log10(val)` instead of `20 * log10(val)` for dB.
+    Power,
+}
+
+/// Additional options for the analysis pipeline. Use
+/// [`SpectrumConfig::default`] to get the same behaviour as
+/// [`crate::samples_fft_to_spectrum`].
+#[derive(Debug, Copy, Clone)]
+pub struct SpectrumConfig {
+    /// See [`LimitStage`]. Default is [`LimitStage::BeforeScaling`].
+    pub limit_stage: LimitStage,
+    /// Maximum number of samples that are accepted. Longer inputs are rejected
+    /// with [`crate::error::SpectrumAnalyzerError::SamplesLengthTooLarge`]
+    /// before any allocation happens. This is useful on memory-constrained
+    /// targets. Default is [`crate::MAX_SAMPLES_LEN`], the biggest length that
+    /// the FFT implementation supports; bigger values have no effect.
+    pub max_samples_len: usize,
+    /// Optional lower bound for the magnitudes, as linear value (not dB). If
+    /// set, every magnitude is clamped to at least this value right after
+    /// the FFT, i.e., before the scaling function runs. Hence, silence or
+    /// zeroed bins never reach a log-based scaling function as `0.0`, which
+    /// otherwise results in `-inf`. For example, `Some(1e-5)` equals
+    /// `-100 dB` after [`crate::scaling::scale_20_times_log10`]. Must be
+    /// bigger than `0.0`, otherwise, the analysis fails with
+    /// [`crate::error::SpectrumAnalyzerError::InvalidMagnitudeFloor`].
+    /// Default is `None`.
+    pub magnitude_floor: Option<f32>,
+    /// Optional threshold, as linear value (not dB), below which bins are
+    /// dropped from the spectrum right after the FFT, e.g., to shrink sparse
+    /// spectra, such as a few tones in silence. This changes the length of
+    /// [`crate::FrequencySpectrum::data`]. Hence, the statistics, such as the
+    /// average and the median, and the scaling function only consider the
+    /// retained bins and the frequencies are not equally spaced anymore. It
+    /// applies to the value selected by [`Self::bin_value`], before
+    /// [`Self::magnitude_floor`]. Default is `None`.
+    pub min_magnitude: Option<f32>,
+    /// See [`BinValue`]. Default is [`BinValue::Magnitude`]. The
+    /// [`Self::magnitude_floor`] applies to the selected value, e.g., to the
+    /// power.
+    pub bin_value: BinValue,
+    /// Optional upper bound for the duration of the samples in seconds, i.e.,
+    /// `samples.len() / sampling_rate`. Longer inputs are rejected with
+    /// [`crate::error::SpectrumAnalyzerError::WindowDurationTooLong`]. This
+    /// catches implausible sampling rates, such as `1 Hz` for an FFT of 4096
+    /// samples. Default is `None`, as very low sampling rates are legit, e.g.,
+    /// for sensor data.
+    pub max_window_duration: Option<f32>,
+    /// Whether the spectrum contains the bin of the Nyquist frequency
+    /// `sampling_rate / 2`. If `true`, a spectrum of `N` samples (without a
+    /// frequency limit) has `N / 2 + 1` bins from the DC component up to and
+    /// including the Nyquist frequency, as most references do. If `false`, it
+    /// has `N / 2` bins and ends one frequency resolution below the Nyquist
+    /// frequency. Default is `true`.
+    pub include_nyquist: bool,
+    /// Optional coherent gain of the window that was applied to the samples,
+    /// e.g., from [`crate::windows::WindowType::coherent_gain`] or
+    /// [`crate::windows::coherent_gain`] for custom coefficients. If set,
+    /// every magnitude is divided by it (every power by its square) right
+    /// after the FFT. Hence, the amplitudes of sinusoids are comparable
+    /// across different windows. This does not hold for broadband content,
+    /// such as noise: its band energies additionally depend on the equivalent
+    /// noise bandwidth of the window, i.e., divide powers summed over bins by
+    /// [`crate::windows::WindowType::enbw`] as well to compare them. This
+    /// happens before [`Self::min_magnitude`] and [`Self::magnitude_floor`].
+    /// Must be bigger than `0.0`, otherwise, the analysis fails with
+    /// [`crate::error::SpectrumAnalyzerError::InvalidCoherentGain`]. Default
+    /// is `None`.
+    pub coherent_gain: Option<f32>,
+}
+
+impl Default for SpectrumConfig {
+    fn default() -> Self {
+        Self {
+            limit_stage: LimitStage::default(),
+            max_samples_len: MAX_SAMPLES_LEN,
+            magnitude_floor: None,
+            min_magnitude: None,
+            bin_value: BinValue::default(),
+            max_window_duration: None,
+            include_nyquist: true,
+            coherent_gain: None,
+        }
+    }
+}
@@ -0,0 +1,141 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for a decoder of [DTMF](https://en.wikipedia.org/wiki/Dual-tone_multi-frequency_signaling)
+//! tones (telephone keypad), built on the Goertzel algorithm. See
+//! [`detect_dtmf`].
+
+use crate::goertzel::goertzel;
+use libm::sqrtf;
+
+/// Frequencies in Hertz of the four rows of the DTMF keypad.
+pub const DTMF_ROW_FREQUENCIES: [f32; 4] = [697.0, 770.0, 852.0, 941.0];
+
+/// Frequencies in Hertz of the four columns of the DTMF keypad.
+pub const DTMF_COLUMN_FREQUENCIES: [f32; 4] = [1209.0, 1336.0, 1477.0, 1633.0];
+
+/// Keys of the DTMF keypad, indexed by row and column.
+pub const DTMF_KEYS: [[char; 4]; 4] = [
+    ['1', '2', '3', 'A'],
+    ['4', '5', '6', 'B'],
+    ['7', '8', '9', 'C'],
+    ['*', '0', '#', 'D'],
+];
+
+/// A tone is considered as present if its estimated amplitude is at least
+/// this fraction of the RMS of all samples.
+const RELATIVE_THRESHOLD: f32 = 0.45;
+
+/// Decodes the DTMF key in the samples. This checks the magnitudes of the
+/// eight DTMF frequencies with the Goertzel algorithm. A tone is considered
+/// as present if its amplitude is big relative to the overall signal level.
+/// The key is only returned, if exactly one row tone and exactly one column
+/// tone are present.
+///
+/// ## Parameters
+/// * `samples` Samples of the signal. At `8000 Hz`, at least around 200
+///             samples (25 ms) are necessary to distinguish the tones. The
+///             number of samples doesn't need to be a power of two.
+/// * `sampling_rate` Sampling rate in Hertz.
+///
+/// ## Return value
+/// The decoded key or `None`, if no key or multiple keys were detected.
+#[inline]
+#[must_use]
+pub fn detect_dtmf(samples: &[f32], sampling_rate: u32) -> Option<char> {
+    if samples.is_empty() {
+        return None;
+    }
+    let rms = sqrtf(samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32);
+    if rms == 0.0 {
+        return None;
+    }
+
+    // The magnitude of a sine with amplitude `a` is `a * N / 2`.
+    let threshold = RELATIVE_THRESHOLD * rms * samples.len() as f32 / 2.0;
+    let find_single_tone = |frequencies: &[f32; 4]| {
+        let mut present_tones = frequencies
+            .iter()
+            .enumerate()
+            .filter(|(_, fr)| goertzel(samples, **fr, sampling_rate) >= threshold)
+            .map(|(i, _)| i);
+        match (present_tones.next(), present_tones.next()) {
+            (Some(i), None) => Some(i),
+            _ => None,
+        }
+    };
+
+    let row = find_single_tone(&DTMF_ROW_FREQUENCIES)?;
+    let column = find_single_tone(&DTMF_COLUMN_FREQUENCIES)?;
+    Some(DTMF_KEYS[row][column])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signal_gen::sine_wave;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    /// Sum of sines with an amplitude of `1.0` each.
+    fn tones(frequencies: &[f32], sampling_rate: u32, len: usize) -> Vec<f32> {
+        // round up, so that there are at least `len` samples
+        let duration_ms = (len as u32 * 1000 + sampling_rate - 1) / sampling_rate;
+        frequencies.iter().fold(vec![0.0; len], |sum, fr| {
+            sum.iter()
+                .zip(sine_wave(*fr, sampling_rate, duration_ms))
+                .map(|(sum, sample)| sum + sample)
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_detect_dtmf_all_keys() {
+        for (sampling_rate, len) in [(8000, 205), (8000, 400), (44100, 2048)] {
+            for (row, row_fr) in DTMF_ROW_FREQUENCIES.iter().enumerate() {
+                for (column, column_fr) in DTMF_COLUMN_FREQUENCIES.iter().enumerate() {
+                    let samples = tones(&[*row_fr, *column_fr], sampling_rate, len);
+                    assert_eq!(
+                        detect_dtmf(&samples, sampling_rate),
+                        Some(DTMF_KEYS[row][column])
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_detect_dtmf_no_key() {
+        assert_eq!(detect_dtmf(&[], 8000), None);
+        assert_eq!(detect_dtmf(&[0.0; 205], 8000), None);
+        // single tone
+        assert_eq!(detect_dtmf(&tones(&[697.0], 8000, 205), 8000), None);
+        // two row tones
+        assert_eq!(
+            detect_dtmf(&tones(&[697.0, 852.0, 1209.0], 8000, 205), 8000),
+            None
+        );
+        // no DTMF tones at all
+        assert_eq!(detect_dtmf(&tones(&[440.0, 2000.0], 8000, 205), 8000), None);
+    }
+}
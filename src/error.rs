@@ -28,6 +28,7 @@ SOFTWARE.
 //! located in submodules.
 
 use crate::limit::FrequencyLimitError;
+use core::fmt::{Display, Formatter};
 
 /// Describes main errors of the library. Almost all errors
 /// are caused by wrong input.
@@ -43,8 +44,171 @@ pub enum SpectrumAnalyzerError {
     InvalidFrequencyLimit(FrequencyLimitError),
     /// The number of samples must be a power of two in order for the FFT.
     SamplesLengthNotAPowerOfTwo,
+    /// The number of samples (first value) is bigger than the allowed maximum
+    /// (second value). See [`crate::SpectrumConfig::max_samples_len`] and
+    /// [`crate::MAX_SAMPLES_LEN`].
+    SamplesLengthTooLarge(usize, usize),
     /// After applying the scaling function on a specific item, the returned value is either
     /// infinity or NaN, according to IEEE-754. This is invalid. Check
     /// your scaling function!
     ScalingError(f32, f32),
+    /// The hop length between two consecutive chunks must not be zero.
+    InvalidHopLength,
+    /// The frequency limit is so narrow that fewer than two frequency bins
+    /// remain, which is too few for a [`crate::FrequencySpectrum`]. Either
+    /// widen the limit or analyze more samples to get a finer frequency
+    /// resolution.
+    FrequencyLimitExcludesAllBins,
+    /// Interleaved stereo samples must consist of complete frames, i.e., the
+    /// number of samples must be even.
+    OddInterleavedSamplesLength,
+    /// The number of samples (first value) differs from the block length
+    /// (second value) the [`crate::goertzel::GoertzelBank`] was created for.
+    BlockLengthMismatch(usize, usize),
+    /// Window coefficients (first value) and samples or other window
+    /// coefficients (second value) must have the same length.
+    WindowLengthMismatch(usize, usize),
+    /// The overlap of two consecutive chunks, as fraction, must be in
+    /// interval `[0.0; 1.0)`. See [`crate::spectrogram::Overlap`].
+    InvalidOverlap(f32),
+    /// A [`crate::FrequencySpectrum`] requires at least two frequency bins.
+    TooFewFrequencyBins,
+    /// Two signals that are compared with each other, such as in
+    /// [`crate::difference_spectrum`], must have the same number of samples
+    /// (first and second value).
+    SamplesLengthMismatch(usize, usize),
+    /// The sampling rate must be bigger than `0 Hz`. Otherwise, all frequency
+    /// bins collapse onto `0 Hz`.
+    ZeroSamplingRate,
+    /// The duration of the samples in seconds (first value) exceeds
+    /// [`crate::SpectrumConfig::max_window_duration`] (second value). This
+    /// usually means that the sampling rate is wrong.
+    WindowDurationTooLong(f32, f32),
+    /// Spectra can only be combined, e.g., averaged, if they were produced the
+    /// same way. See [`crate::spectrogram::AnalysisMetadata::compatible_with`].
+    IncompatibleMetadata,
+    /// The scratch buffer (first value) must provide space for at least as
+    /// many values as there are samples (second value). See
+    /// [`crate::minimal::dominant_frequency_with_scratch`].
+    ScratchBufferTooSmall(usize, usize),
+    /// The end of the analyzed slice (first value) exceeds the length of the
+    /// samples (second value). See [`crate::spectrogram::analyze_slice`].
+    SliceOutOfBounds(usize, usize),
+    /// The retained bins of a deserialized [`crate::sparse::SparseSpectrum`]
+    /// don't match its frequency axis.
+    InvalidSparseSpectrum,
+    /// The coherent gain (value) must be bigger than `0.0`. See
+    /// [`crate::SpectrumConfig::coherent_gain`].
+    InvalidCoherentGain(f32),
+    /// The magnitude floor (value) must be bigger than `0.0`. See
+    /// [`crate::SpectrumConfig::magnitude_floor`].
+    InvalidMagnitudeFloor(f32),
+}
+
+impl Display for SpectrumAnalyzerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TooFewSamples => write!(f, "there must be at least two samples"),
+            Self::NaNValuesNotSupported => write!(f, "NaN values are not supported"),
+            Self::InfinityValuesNotSupported => write!(f, "infinite values are not supported"),
+            Self::InvalidFrequencyLimit(err) => write!(f, "invalid frequency limit: {}", err),
+            Self::SamplesLengthNotAPowerOfTwo => {
+                write!(f, "the number of samples must be a power of two")
+            }
+            Self::SamplesLengthTooLarge(len, max) => write!(
+                f,
+                "{} samples are more than the allowed maximum of {}",
+                len, max
+            ),
+            Self::ScalingError(val, scaled) => write!(
+                f,
+                "the scaling function mapped {} to the invalid value {}",
+                val, scaled
+            ),
+            Self::InvalidHopLength => write!(f, "the hop length must not be zero"),
+            Self::FrequencyLimitExcludesAllBins => write!(
+                f,
+                "the frequency limit leaves fewer than two frequency bins"
+            ),
+            Self::OddInterleavedSamplesLength => write!(
+                f,
+                "interleaved stereo samples must consist of complete frames"
+            ),
+            Self::BlockLengthMismatch(len, block_len) => write!(
+                f,
+                "{} samples don't match the block length of {}",
+                len, block_len
+            ),
+            Self::WindowLengthMismatch(window_len, len) => write!(
+                f,
+                "{} window coefficients don't match the length of {}",
+                window_len, len
+            ),
+            Self::InvalidOverlap(overlap) => {
+                write!(f, "the overlap {} is not in interval [0.0; 1.0)", overlap)
+            }
+            Self::TooFewFrequencyBins => {
+                write!(f, "a spectrum requires at least two frequency bins")
+            }
+            Self::SamplesLengthMismatch(a, b) => write!(
+                f,
+                "the signals have a different number of samples: {} and {}",
+                a, b
+            ),
+            Self::ZeroSamplingRate => write!(f, "the sampling rate must be bigger than 0 Hz"),
+            Self::WindowDurationTooLong(duration, max) => write!(
+                f,
+                "the samples last {} s, which is longer than the maximum of {} s",
+                duration, max
+            ),
+            Self::IncompatibleMetadata => {
+                write!(f, "the spectra were not produced the same way")
+            }
+            Self::ScratchBufferTooSmall(len, required) => write!(
+                f,
+                "the scratch buffer has space for {} values but {} are required",
+                len, required
+            ),
+            Self::SliceOutOfBounds(end, len) => write!(
+                f,
+                "the slice ends at {}, which exceeds the {} samples",
+                end, len
+            ),
+            Self::InvalidSparseSpectrum => write!(
+                f,
+                "the bins of the sparse spectrum don't match its frequency axis"
+            ),
+            Self::InvalidCoherentGain(gain) => {
+                write!(f, "the coherent gain {} must be bigger than 0.0", gain)
+            }
+            Self::InvalidMagnitudeFloor(floor) => {
+                write!(f, "the magnitude floor {} must be bigger than 0.0", floor)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            SpectrumAnalyzerError::TooFewSamples.to_string(),
+            "there must be at least two samples"
+        );
+        assert_eq!(
+            SpectrumAnalyzerError::SamplesLengthTooLarge(32768, 16384).to_string(),
+            "32768 samples are more than the allowed maximum of 16384"
+        );
+        assert_eq!(
+            SpectrumAnalyzerError::InvalidFrequencyLimit(FrequencyLimitError::InvalidBinRange(
+                5, 2
+            ))
+            .to_string(),
+            "invalid frequency limit: the first bin 5 is not smaller than the end 2"
+        );
+    }
 }
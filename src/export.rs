@@ -0,0 +1,290 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for streaming spectrograms, i.e., sequences of [`TimedSpectrum`],
+//! as CSV or JSON lines into any [`Write`], e.g., a file. Only available with
+//! the `std` feature.
+//!
+//! Rows are written one by one, thus memory usage doesn't grow with the
+//! length of the recording. Wrap files in a [`std::io::BufWriter`].
+
+use crate::spectrogram::TimedSpectrum;
+use std::io::{self, Write};
+
+/// Layout of the exported spectrogram.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SpectrogramLayout {
+    /// One row per bin of each frame: `(time, frequency, value)`.
+    Long,
+    /// One row per frame with one column per bin. All frames must have the
+    /// same number of bins, otherwise an error of kind
+    /// [`io::ErrorKind::InvalidInput`] is returned.
+    Wide,
+}
+
+/// Writes the frames as CSV with a header. The time is the beginning of each
+/// frame in seconds, see [`TimedSpectrum::start_seconds`].
+///
+/// - [`SpectrogramLayout::Long`] writes header `time,frequency,value`
+/// - [`SpectrogramLayout::Wide`] writes header `time,<frequency of bin 0>,...`
+///   with the frequencies of the first frame
+///
+/// ## Parameters
+/// * `frames` Frames in chronological order, e.g., from
+///            [`crate::spectrogram::analyze_file_samples_timed`].
+/// * `layout` See [`SpectrogramLayout`].
+/// * `writer` Destination of the CSV data.
+///
+/// ## Errors
+/// All errors of `writer` and [`io::ErrorKind::InvalidInput`] if the
+/// number of bins differs between frames in the wide layout.
+pub fn write_spectrogram_csv<W: Write>(
+    frames: &[TimedSpectrum],
+    layout: SpectrogramLayout,
+    mut writer: W,
+) -> io::Result<()> {
+    match layout {
+        SpectrogramLayout::Long => {
+            writeln!(writer, "time,frequency,value")?;
+            for frame in frames {
+                for (fr, fr_val) in frame.spectrum.data() {
+                    writeln!(
+                        writer,
+                        "{},{},{}",
+                        frame.start_seconds,
+                        fr.val(),
+                        fr_val.val()
+                    )?;
+                }
+            }
+        }
+        SpectrogramLayout::Wide => {
+            verify_same_bin_count(frames)?;
+            write!(writer, "time")?;
+            if let Some(first) = frames.first() {
+                for (fr, _fr_val) in first.spectrum.data() {
+                    write!(writer, ",{}", fr.val())?;
+                }
+            }
+            writeln!(writer)?;
+            for frame in frames {
+                write!(writer, "{}", frame.start_seconds)?;
+                for (_fr, fr_val) in frame.spectrum.data() {
+                    write!(writer, ",{}", fr_val.val())?;
+                }
+                writeln!(writer)?;
+            }
+        }
+    }
+    writer.flush()
+}
+
+/// Like [`write_spectrogram_csv`] but writes [JSON lines](https://jsonlines.org),
+/// i.e., one JSON object per line and no header. Non-finite values are
+/// written as `null`.
+///
+/// - [`SpectrogramLayout::Long`] writes `{"time":0.5,"frequency":21.5,"value":1.5}`
+/// - [`SpectrogramLayout::Wide`] writes
+///   `{"time":0.5,"frequencies":[0,21.5,...],"values":[1.5,...]}`
+///
+/// ## Parameters
+/// See [`write_spectrogram_csv`].
+///
+/// ## Errors
+/// See [`write_spectrogram_csv`].
+pub fn write_spectrogram_json_lines<W: Write>(
+    frames: &[TimedSpectrum],
+    layout: SpectrogramLayout,
+    mut writer: W,
+) -> io::Result<()> {
+    match layout {
+        SpectrogramLayout::Long => {
+            for frame in frames {
+                for (fr, fr_val) in frame.spectrum.data() {
+                    write!(writer, "{{\"time\":")?;
+                    write_json_number(&mut writer, frame.start_seconds)?;
+                    write!(writer, ",\"frequency\":")?;
+                    write_json_number(&mut writer, fr.val())?;
+                    write!(writer, ",\"value\":")?;
+                    write_json_number(&mut writer, fr_val.val())?;
+                    writeln!(writer, "}}")?;
+                }
+            }
+        }
+        SpectrogramLayout::Wide => {
+            verify_same_bin_count(frames)?;
+            for frame in frames {
+                write!(writer, "{{\"time\":")?;
+                write_json_number(&mut writer, frame.start_seconds)?;
+                write!(writer, ",\"frequencies\":")?;
+                write_json_array(
+                    &mut writer,
+                    frame.spectrum.data().iter().map(|(fr, _)| fr.val()),
+                )?;
+                write!(writer, ",\"values\":")?;
+                write_json_array(
+                    &mut writer,
+                    frame.spectrum.data().iter().map(|(_, fr_val)| fr_val.val()),
+                )?;
+                writeln!(writer, "}}")?;
+            }
+        }
+    }
+    writer.flush()
+}
+
+/// Returns an error if not all frames have the same number of bins.
+fn verify_same_bin_count(frames: &[TimedSpectrum]) -> io::Result<()> {
+    let mut bin_counts = frames.iter().map(|frame| frame.spectrum.data().len());
+    let first = bin_counts.next();
+    if bin_counts.any(|bin_count| Some(bin_count) != first) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "all frames must have the same number of bins for the wide layout",
+        ));
+    }
+    Ok(())
+}
+
+/// Writes a JSON number, or `null` for values that JSON can't represent.
+fn write_json_number<W: Write>(writer: &mut W, val: f32) -> io::Result<()> {
+    if val.is_finite() {
+        write!(writer, "{}", val)
+    } else {
+        write!(writer, "null")
+    }
+}
+
+/// Writes a JSON array of numbers. See [`write_json_number`].
+fn write_json_array<W: Write>(writer: &mut W, vals: impl Iterator<Item = f32>) -> io::Result<()> {
+    write!(writer, "[")?;
+    for (i, val) in vals.enumerate() {
+        if i > 0 {
+            write!(writer, ",")?;
+        }
+        write_json_number(writer, val)?;
+    }
+    write!(writer, "]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spectrogram::{AnalysisMetadata, ChunkConfig};
+    use crate::windows::WindowType;
+    use crate::{FrequencyLimit, FrequencySpectrum};
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    /// Two frames with three bins each; the value of bin `j` of frame `i` is
+    /// `10 * i + j`.
+    fn frames() -> Vec<TimedSpectrum> {
+        let config = ChunkConfig {
+            chunk_len: 4,
+            hop_len: 2,
+            window: WindowType::Rectangular,
+            frequency_limit: FrequencyLimit::All,
+        };
+        (0..2)
+            .map(|i| TimedSpectrum {
+                start_sample: i * 2,
+                start_seconds: i as f32 * 0.5,
+                spectrum: FrequencySpectrum::from_iter_sorted(
+                    (0..3).map(|j| ((j as f32).into(), ((10 * i + j) as f32).into())),
+                    1.0,
+                    4,
+                ),
+                metadata: AnalysisMetadata::new(4, &config),
+            })
+            .collect()
+    }
+
+    fn lines(buffer: &[u8]) -> Vec<String> {
+        String::from_utf8(buffer.to_vec())
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn test_write_spectrogram_csv() {
+        let mut buffer = Vec::new();
+        write_spectrogram_csv(&frames(), SpectrogramLayout::Long, &mut buffer).unwrap();
+        let rows = lines(&buffer);
+        // header + 2 frames * 3 bins
+        assert_eq!(rows.len(), 7);
+        assert_eq!(rows[0], "time,frequency,value");
+        assert_eq!(rows[1], "0,0,0");
+        assert_eq!(rows[6], "0.5,2,12");
+
+        let mut buffer = Vec::new();
+        write_spectrogram_csv(&frames(), SpectrogramLayout::Wide, &mut buffer).unwrap();
+        let rows = lines(&buffer);
+        assert_eq!(rows, ["time,0,1,2", "0,0,1,2", "0.5,10,11,12"]);
+
+        let mut buffer = Vec::new();
+        write_spectrogram_csv(&[], SpectrogramLayout::Wide, &mut buffer).unwrap();
+        assert_eq!(lines(&buffer), ["time"]);
+    }
+
+    #[test]
+    fn test_write_spectrogram_json_lines() {
+        let mut buffer = Vec::new();
+        write_spectrogram_json_lines(&frames(), SpectrogramLayout::Long, &mut buffer).unwrap();
+        let rows = lines(&buffer);
+        assert_eq!(rows.len(), 6);
+        assert_eq!(rows[0], r#"{"time":0,"frequency":0,"value":0}"#);
+        assert_eq!(rows[5], r#"{"time":0.5,"frequency":2,"value":12}"#);
+
+        let mut buffer = Vec::new();
+        write_spectrogram_json_lines(&frames(), SpectrogramLayout::Wide, &mut buffer).unwrap();
+        let rows = lines(&buffer);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            rows[1],
+            r#"{"time":0.5,"frequencies":[0,1,2],"values":[10,11,12]}"#
+        );
+
+        let mut buffer = Vec::new();
+        write_json_number(&mut buffer, f32::NAN).unwrap();
+        assert_eq!(buffer, b"null");
+    }
+
+    #[test]
+    fn test_wide_layout_requires_same_bin_count() {
+        let mut frames = frames();
+        frames[1].spectrum = FrequencySpectrum::from_iter_sorted(
+            (0..2).map(|j| ((j as f32).into(), 0.0.into())),
+            1.0,
+            4,
+        );
+        let err = write_spectrogram_csv(&frames, SpectrogramLayout::Wide, io::sink()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err =
+            write_spectrogram_json_lines(&frames, SpectrogramLayout::Wide, io::sink()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        // the long layout doesn't care
+        write_spectrogram_csv(&frames, SpectrogramLayout::Long, io::sink()).unwrap();
+    }
+}
@@ -0,0 +1,158 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the struct [`FeatureVector`] that bundles several spectral
+//! features into a vector, e.g., to feed them into a classifier.
+
+use crate::FrequencySpectrum;
+use alloc::vec::Vec;
+
+/// A frequency band `(low, high)` in Hertz. Both bounds are inclusive.
+pub type FrequencyBand = (f32, f32);
+
+/// Describes a set of spectral features of a [`FrequencySpectrum`] that are
+/// bundled into a vector of `f32` by [`FeatureVector::compute`]. This is
+/// useful as input for classifiers, e.g., for voice activity or hum detection.
+///
+/// The order of the entries is stable:
+/// 1. spectral centroid (if [`Self::centroid`] is set), see
+///    [`FrequencySpectrum::spectral_centroid`]
+/// 2. spectral flatness (if [`Self::flatness`] is set), see
+///    [`FrequencySpectrum::spectral_flatness`]
+/// 3. spectral rolloff (if [`Self::rolloff`] is set), see
+///    [`FrequencySpectrum::spectral_rolloff`]
+/// 4. one band energy ratio per entry in [`Self::band_ratios`], in the same
+///    order, see [`FrequencySpectrum::band_energy_ratio`]
+#[derive(Debug, Copy, Clone, Default)]
+pub struct FeatureVector<'a> {
+    /// Whether the spectral centroid is part of the vector.
+    pub centroid: bool,
+    /// Whether the spectral flatness is part of the vector.
+    pub flatness: bool,
+    /// If set, the spectral rolloff for the given fraction (e.g. `0.85`) is
+    /// part of the vector.
+    pub rolloff: Option<f32>,
+    /// `(band_a, band_b)` pairs for band energy ratios.
+    pub band_ratios: &'a [(FrequencyBand, FrequencyBand)],
+}
+
+impl FeatureVector<'_> {
+    /// Returns the number of entries in the vector returned by
+    /// [`Self::compute`].
+    #[must_use]
+    pub fn len(&self) -> usize {
+        usize::from(self.centroid)
+            + usize::from(self.flatness)
+            + usize::from(self.rolloff.is_some())
+            + self.band_ratios.len()
+    }
+
+    /// Returns whether the vector returned by [`Self::compute`] is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Computes all configured features of the given spectrum. See
+    /// [`FeatureVector`] for the order of the entries.
+    #[must_use]
+    pub fn compute(&self, spectrum: &FrequencySpectrum) -> Vec<f32> {
+        let mut features = Vec::with_capacity(self.len());
+        if self.centroid {
+            features.push(spectrum.spectral_centroid());
+        }
+        if self.flatness {
+            features.push(spectrum.spectral_flatness());
+        }
+        if let Some(fraction) = self.rolloff {
+            features.push(spectrum.spectral_rolloff(fraction).val());
+        }
+        for (band_a, band_b) in self.band_ratios {
+            features.push(spectrum.band_energy_ratio(*band_a, *band_b));
+        }
+        features
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signal_gen::white_noise;
+    use crate::windows::hann_window;
+    use crate::{samples_fft_to_spectrum, FrequencyLimit};
+    use core::f32::consts::PI;
+
+    #[test]
+    fn test_feature_vector() {
+        let sampling_rate = 8000;
+        let sine = (0..1024)
+            .map(|i| libm::sinf(2.0 * PI * 200.0 * i as f32 / sampling_rate as f32))
+            .collect::<Vec<_>>();
+        let noise = white_noise(1.0, 1024, 1000, 0x1234_5678);
+
+        let sine_spectrum = samples_fft_to_spectrum(
+            &hann_window(&sine),
+            sampling_rate,
+            FrequencyLimit::All,
+            None,
+        )
+        .unwrap();
+        let noise_spectrum = samples_fft_to_spectrum(
+            &hann_window(&noise),
+            sampling_rate,
+            FrequencyLimit::All,
+            None,
+        )
+        .unwrap();
+
+        let feature_vector = FeatureVector {
+            centroid: true,
+            flatness: true,
+            rolloff: Some(0.85),
+            band_ratios: &[((0.0, 300.0), (300.0, 4000.0))],
+        };
+        assert_eq!(feature_vector.len(), 4);
+
+        let sine_features = feature_vector.compute(&sine_spectrum);
+        let noise_features = feature_vector.compute(&noise_spectrum);
+        assert_eq!(sine_features.len(), 4);
+        assert_eq!(noise_features.len(), 4);
+
+        // centroid: sine is centered around its frequency, noise is spread
+        // over the whole spectrum
+        assert!((sine_features[0] - 200.0).abs() < 50.0);
+        assert!(noise_features[0] > 1500.0);
+        // flatness: sine is tonal, noise is flat
+        assert!(sine_features[1] < 0.01);
+        assert!(noise_features[1] > 0.3);
+        // rolloff
+        assert!(sine_features[2] < 300.0);
+        assert!(noise_features[2] > 3000.0);
+        // low/high energy ratio
+        assert!(sine_features[3] > 100.0);
+        assert!(noise_features[3] < 0.2);
+
+        assert!(FeatureVector::default().is_empty());
+        assert!(FeatureVector::default().compute(&sine_spectrum).is_empty());
+    }
+}
@@ -29,7 +29,7 @@ SOFTWARE.
 
 use alloc::vec::Vec;
 use core::convert::TryInto;
-use microfft::real;
+use microfft::{complex, real};
 
 /// The result of a FFT is always complex but because different FFT crates might
 /// use different versions of "num-complex", each implementation exports
@@ -44,9 +44,50 @@ macro_rules! real_fft_n {
             $(
                 $i => {
                     let mut buffer: [_; $i] = $buffer.try_into().unwrap();
+                    // +1: space for the Nyquist frequency, so that there
+                    // is no reallocation later
+                    let mut res = Vec::with_capacity($i / 2 + 1);
+                    res.extend_from_slice(paste::paste! (
+                        real::[<rfft_$i>]
+                    )(&mut buffer));
+                    res
+                }
+            )*
+            _ => { unimplemented!("unexpected buffer len") }
+        }
+    };
+}
+
+/// Calculates the complex FFT in-place by invoking the proper function
+/// corresponding to the buffer length.
+macro_rules! complex_fft_n {
+    ($buffer:expr, $( $i:literal ),*) => {
+        match $buffer.len() {
+            $(
+                $i => {
+                    let buffer: &mut [_; $i] = $buffer.try_into().unwrap();
                     paste::paste! (
+                        complex::[<cfft_$i>]
+                    )(buffer);
+                }
+            )*
+            _ => { unimplemented!("unexpected buffer len") }
+        }
+    };
+}
+
+/// Calculates the real FFT in-place by invoking the proper function
+/// corresponding to the buffer length.
+macro_rules! real_fft_in_place_n {
+    ($buffer:expr, $( $i:literal ),*) => {
+        match $buffer.len() {
+            $(
+                $i => {
+                    let buffer: &mut [_; $i] = $buffer.try_into().unwrap();
+                    let res: &mut [Complex32] = paste::paste! (
                         real::[<rfft_$i>]
-                    )(&mut buffer).to_vec()
+                    )(buffer);
+                    res
                 }
             )*
             _ => { unimplemented!("unexpected buffer len") }
@@ -54,6 +95,9 @@ macro_rules! real_fft_n {
     };
 }
 
+/// The biggest number of samples that the FFT implementation supports.
+pub const MAX_SAMPLES_LEN: usize = 16384;
+
 /// Real FFT using [`microfft::real`].
 pub struct FftImpl;
 
@@ -66,7 +110,8 @@ impl FftImpl {
     /// # Parameters
     /// - `samples`: Array with samples. Each value must be a regular floating
     ///              point number (no NaN or infinite) and the length must be
-    ///              a power of two. Otherwise, the function panics.
+    ///              a power of two not bigger than [`MAX_SAMPLES_LEN`].
+    ///              Otherwise, the function panics.
     #[inline]
     pub(crate) fn calc(samples: &[f32]) -> Vec<Complex32> {
         let mut fft_res: Vec<Complex32> =
@@ -80,4 +125,98 @@ impl FftImpl {
         fft_res.push(Complex32::new(nyquist_fr_pos_val, 0.0));
         fft_res
     }
+
+    /// Like [`Self::calc`] but calculates the FFT in-place without any
+    /// allocation. The returned slice reuses the memory of `buffer` and has
+    /// length `buffer.len() / 2`. As in [`microfft::real`], the real value of
+    /// the Nyquist frequency is packed inside the imaginary part of the DC
+    /// component (index 0).
+    ///
+    /// # Parameters
+    /// - `buffer`: Samples, same restrictions as for [`Self::calc`]. The
+    ///             content is destroyed.
+    #[inline]
+    pub(crate) fn calc_in_place(buffer: &mut [f32]) -> &mut [Complex32] {
+        real_fft_in_place_n!(
+            buffer, 2, 4, 8, 16, 32, 64, 128, 256, 512, 1024, 2048, 4096, 8192, 16384
+        )
+    }
+
+    /// Calculates the complex FFT of the given buffer in-place using
+    /// [`microfft::complex`]. In contrast to [`Self::calc`], the result
+    /// contains all `N` bins, i.e., also the mirrored (negative) frequencies.
+    ///
+    /// # Parameters
+    /// - `buffer`: Complex samples. The length must be a power of two not
+    ///             bigger than [`MAX_SAMPLES_LEN`]. Otherwise, the function
+    ///             panics.
+    #[inline]
+    pub(crate) fn calc_complex(buffer: &mut [Complex32]) {
+        complex_fft_n!(buffer, 2, 4, 8, 16, 32, 64, 128, 256, 512, 1024, 2048, 4096, 8192, 16384);
+    }
+
+    /// Calculates the inverse complex FFT of the given buffer in-place. The
+    /// result is divided by `N`, hence, `inverse(fft(x)) == x`. This uses the
+    /// identity `ifft(x) = conj(fft(conj(x))) / N`.
+    ///
+    /// # Parameters
+    /// - `buffer`: Complex FFT bins. The length must be a power of two.
+    ///             Otherwise, the function panics.
+    #[inline]
+    pub(crate) fn calc_complex_inverse(buffer: &mut [Complex32]) {
+        buffer.iter_mut().for_each(|x| *x = x.conj());
+        Self::calc_complex(buffer);
+        let n = buffer.len() as f32;
+        buffer.iter_mut().for_each(|x| *x = x.conj() / n);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complex_fft_matches_real_fft() {
+        let samples = [1.0, 2.0, -3.0, 4.0, 0.5, -1.5, 2.5, 0.0];
+        let real_res = FftImpl::calc(&samples);
+        let mut complex_res = samples
+            .iter()
+            .map(|x| Complex32::new(*x, 0.0))
+            .collect::<Vec<_>>();
+        FftImpl::calc_complex(&mut complex_res);
+        for (real, complex) in real_res.iter().zip(complex_res.iter()) {
+            float_cmp::assert_approx_eq!(f32, real.re, complex.re, epsilon = 1e-5);
+            float_cmp::assert_approx_eq!(f32, real.im, complex.im, epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_real_fft_in_place_matches_real_fft() {
+        let samples = [1.0, 2.0, -3.0, 4.0, 0.5, -1.5, 2.5, 0.0];
+        let expected = FftImpl::calc(&samples);
+        let mut buffer = samples;
+        let res = FftImpl::calc_in_place(&mut buffer);
+        assert_eq!(res.len(), samples.len() / 2);
+        assert_eq!(res[0].re, expected[0].re);
+        // Nyquist frequency packed into the DC component
+        assert_eq!(res[0].im, expected[samples.len() / 2].re);
+        for (actual, expected) in res.iter().zip(expected.iter()).skip(1) {
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_complex_fft_inverse() {
+        let samples = [1.0, 2.0, -3.0, 4.0, 0.5, -1.5, 2.5, 0.0];
+        let mut buffer = samples
+            .iter()
+            .map(|x| Complex32::new(*x, 0.0))
+            .collect::<Vec<_>>();
+        FftImpl::calc_complex(&mut buffer);
+        FftImpl::calc_complex_inverse(&mut buffer);
+        for (expected, actual) in samples.iter().zip(buffer.iter()) {
+            float_cmp::assert_approx_eq!(f32, *expected, actual.re, epsilon = 1e-5);
+            float_cmp::assert_approx_eq!(f32, 0.0, actual.im, epsilon = 1e-5);
+        }
+    }
 }
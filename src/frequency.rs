@@ -24,6 +24,7 @@ SOFTWARE.
 //! Module for the struct [`OrderableF32`] and the two
 //! convenient type definitions [`Frequency`] and [`FrequencyValue`].
 
+use crate::error::SpectrumAnalyzerError;
 use core::cmp::Ordering;
 use core::fmt::{Display, Formatter, Result};
 use core::ops::{Add, Div, Mul, Sub};
@@ -36,7 +37,28 @@ pub type FrequencyValue = OrderableF32;
 
 /// Wrapper around [`f32`] that guarantees a valid number, hence, the number is
 /// neither `NaN` or `infinite`. This makes the number orderable and sortable.
+///
+/// ## NaN policy
+/// The public API never creates `NaN` values: scaling functions that return
+/// `NaN` or infinity are rejected with
+/// [`crate::error::SpectrumAnalyzerError::ScalingError`]. Constructing a
+/// `NaN` value via [`From`] panics in debug builds. In release builds, the
+/// ordering is total nevertheless, so that sorting never panics: it follows
+/// [`f32::total_cmp`], i.e., `NaN` is bigger than positive infinity (or
+/// smaller than negative infinity, if its sign bit is set). In contrast to
+/// [`f32::total_cmp`], `-0.0` and `0.0` are equal.
+///
+/// ## Memory layout
+/// The type is `#[repr(transparent)]`, i.e., it has the same layout as
+/// [`f32`]. Hence, slices can be reinterpreted without copying, see
+/// [`Self::as_f32_slice`] and [`Self::try_from_f32_slice`].
+///
+/// ## Serde
+/// With the `serde` feature, deserialization goes through [`Self::try_new`],
+/// hence, `NaN` or infinite values are rejected.
 #[derive(Debug, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[repr(transparent)]
 pub struct OrderableF32(f32);
 
 impl OrderableF32 {
@@ -44,6 +66,58 @@ impl OrderableF32 {
     pub const fn val(&self) -> f32 {
         self.0
     }
+
+    /// Checked variant of [`From<f32>`].
+    ///
+    /// ## Errors
+    /// [`SpectrumAnalyzerError::NaNValuesNotSupported`] or
+    /// [`SpectrumAnalyzerError::InfinityValuesNotSupported`] if the value
+    /// violates the guarantee of this type.
+    #[inline]
+    pub fn try_new(val: f32) -> core::result::Result<Self, SpectrumAnalyzerError> {
+        if val.is_nan() {
+            Err(SpectrumAnalyzerError::NaNValuesNotSupported)
+        } else if val.is_infinite() {
+            Err(SpectrumAnalyzerError::InfinityValuesNotSupported)
+        } else {
+            Ok(Self(val))
+        }
+    }
+
+    /// Reinterprets the values as plain [`f32`] values without copying or
+    /// mapping each element with [`Self::val`], e.g., for hot conversion
+    /// loops or to pass the values to other libraries.
+    #[inline]
+    #[must_use]
+    pub fn as_f32_slice(values: &[Self]) -> &[f32] {
+        // SAFETY: `OrderableF32` is `#[repr(transparent)]` over `f32`, hence,
+        // both have the same size and alignment. Every `OrderableF32` is a
+        // valid `f32` and the lifetime is inherited from `values`.
+        unsafe { core::slice::from_raw_parts(values.as_ptr().cast::<f32>(), values.len()) }
+    }
+
+    /// Reinterprets plain [`f32`] values as [`OrderableF32`] values without
+    /// copying. This is the inverse of [`Self::as_f32_slice`].
+    ///
+    /// ## Errors
+    /// [`SpectrumAnalyzerError::NaNValuesNotSupported`] or
+    /// [`SpectrumAnalyzerError::InfinityValuesNotSupported`] if a value
+    /// violates the guarantee of this type. This needs one pass over the
+    /// values.
+    #[inline]
+    pub fn try_from_f32_slice(
+        values: &[f32],
+    ) -> core::result::Result<&[Self], SpectrumAnalyzerError> {
+        if values.iter().any(|x| x.is_nan()) {
+            return Err(SpectrumAnalyzerError::NaNValuesNotSupported);
+        }
+        if values.iter().any(|x| x.is_infinite()) {
+            return Err(SpectrumAnalyzerError::InfinityValuesNotSupported);
+        }
+        // SAFETY: See `as_f32_slice`. The values are neither `NaN` nor
+        // infinite, as checked above.
+        Ok(unsafe { core::slice::from_raw_parts(values.as_ptr().cast::<Self>(), values.len()) })
+    }
 }
 
 impl From<f32> for OrderableF32 {
@@ -55,6 +129,17 @@ impl From<f32> for OrderableF32 {
     }
 }
 
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for OrderableF32 {
+    #[inline]
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Self, D::Error> {
+        let val = f32::deserialize(deserializer)?;
+        Self::try_new(val).map_err(serde::de::Error::custom)
+    }
+}
+
 impl Display for OrderableF32 {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(f, "{}", self.0)
@@ -62,9 +147,16 @@ impl Display for OrderableF32 {
 }
 
 impl Ord for OrderableF32 {
+    #[allow(clippy::float_cmp)]
     #[inline]
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        // `-0.0` equals `0.0`; everything else, including `NaN`, is ordered
+        // by `total_cmp`
+        if self.val() == other.val() {
+            Ordering::Equal
+        } else {
+            self.val().total_cmp(&other.val())
+        }
     }
 }
 
@@ -78,17 +170,9 @@ impl PartialEq for OrderableF32 {
 }
 
 impl PartialOrd for OrderableF32 {
-    #[allow(clippy::float_cmp)]
     #[inline]
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        // self.cmp(other).is_eq()
-        Some(if self.val() < other.val() {
-            Ordering::Less
-        } else if self.val() == other.val() {
-            Ordering::Equal
-        } else {
-            Ordering::Greater
-        })
+        Some(self.cmp(other))
     }
 }
 
@@ -152,4 +236,81 @@ mod tests {
             assert_eq!(f1, f1, "Equal must work");
         }
     }
+
+    #[test]
+    fn test_orderablef32_total_order() {
+        // bypass the debug assertions of `From`, as release builds would do
+        let nan = OrderableF32(f32::NAN);
+        let negative_nan = OrderableF32(-f32::NAN);
+        let zero = OrderableF32::from(0.0);
+        let negative_zero = OrderableF32::from(-0.0);
+
+        assert_eq!(zero, negative_zero);
+        assert_eq!(nan, nan);
+        assert!(nan > OrderableF32::from(f32::MAX));
+        assert!(negative_nan < OrderableF32::from(f32::MIN));
+
+        let mut values = [nan, 1.0.into(), negative_nan, zero, (-1.0).into()];
+        values.sort();
+        assert!(values[0].val().is_nan());
+        assert_eq!(values[1].val(), -1.0);
+        assert_eq!(values[2].val(), 0.0);
+        assert_eq!(values[3].val(), 1.0);
+        assert!(values[4].val().is_nan());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "NaN-values are not supported!")]
+    fn test_orderablef32_from_nan() {
+        let _ = OrderableF32::from(f32::NAN);
+    }
+
+    #[test]
+    fn test_orderablef32_slice_conversion() {
+        let values = [1.0, -2.5, 0.0, 1e10];
+        let wrapped = OrderableF32::try_from_f32_slice(&values).unwrap();
+        assert_eq!(wrapped.len(), values.len());
+        for (wrapped, value) in wrapped.iter().zip(values) {
+            assert_eq!(wrapped.val(), value);
+        }
+        let unwrapped = OrderableF32::as_f32_slice(wrapped);
+        assert_eq!(unwrapped, values);
+        // no copy
+        assert_eq!(unwrapped.as_ptr(), values.as_ptr());
+        assert!(OrderableF32::as_f32_slice(&[]).is_empty());
+
+        assert!(matches!(
+            OrderableF32::try_from_f32_slice(&[1.0, f32::NAN]),
+            Err(SpectrumAnalyzerError::NaNValuesNotSupported)
+        ));
+        assert!(matches!(
+            OrderableF32::try_from_f32_slice(&[f32::NEG_INFINITY]),
+            Err(SpectrumAnalyzerError::InfinityValuesNotSupported)
+        ));
+    }
+
+    #[test]
+    fn test_orderablef32_try_new() {
+        assert_eq!(OrderableF32::try_new(-3.5).unwrap().val(), -3.5);
+        assert!(matches!(
+            OrderableF32::try_new(f32::NAN),
+            Err(SpectrumAnalyzerError::NaNValuesNotSupported)
+        ));
+        assert!(matches!(
+            OrderableF32::try_new(f32::INFINITY),
+            Err(SpectrumAnalyzerError::InfinityValuesNotSupported)
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_orderablef32_serde() {
+        let json = serde_json::to_string(&OrderableF32::from(1.5)).unwrap();
+        assert_eq!(json, "1.5");
+        let value = serde_json::from_str::<OrderableF32>(&json).unwrap();
+        assert_eq!(value.val(), 1.5);
+        // JSON has no infinity; values that overflow `f32` become infinite
+        assert!(serde_json::from_str::<OrderableF32>("1e39").is_err());
+    }
 }
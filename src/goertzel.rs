@@ -0,0 +1,285 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the [Goertzel algorithm](https://en.wikipedia.org/wiki/Goertzel_algorithm).
+//! It calculates the magnitude of a single frequency in `O(N)`, which is
+//! cheaper than a full FFT if only a few frequencies are of interest, e.g.,
+//! the tones of [DTMF](https://en.wikipedia.org/wiki/Dual-tone_multi-frequency_signaling).
+
+use crate::error::SpectrumAnalyzerError;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+use libm::{cosf, sqrtf};
+
+/// Calculates the magnitude of `target_freq` in the samples with the Goertzel
+/// algorithm. The result equals the magnitude of the corresponding bin of a
+/// FFT without scaling (see [`crate::samples_fft_to_spectrum`]) if
+/// `target_freq` is a multiple of the frequency resolution
+/// `sampling_rate / samples.len()`. In contrast to the FFT, the number of
+/// samples doesn't need to be a power of two and `target_freq` can be any
+/// frequency.
+///
+/// ## Parameters
+/// * `samples` Samples to analyze. Apply a window function first, if
+///             necessary.
+/// * `target_freq` Frequency in Hertz. Should be in interval
+///                 `[0; sampling_rate / 2]`.
+/// * `sampling_rate` Sampling rate in Hertz.
+///
+/// ## Return value
+/// Magnitude of `target_freq` in the samples.
+#[inline]
+#[must_use]
+pub fn goertzel(samples: &[f32], target_freq: f32, sampling_rate: u32) -> f32 {
+    goertzel_with_coefficient(samples, coefficient(target_freq, sampling_rate))
+}
+
+/// Calculates the coefficient `2 * cos(omega)` of the Goertzel filter for
+/// `target_freq`.
+#[inline]
+fn coefficient(target_freq: f32, sampling_rate: u32) -> f32 {
+    let omega = 2.0 * PI * target_freq / sampling_rate as f32;
+    2.0 * cosf(omega)
+}
+
+/// Runs the Goertzel filter with a precomputed coefficient. See [`goertzel`].
+#[inline]
+fn goertzel_with_coefficient(samples: &[f32], coefficient: f32) -> f32 {
+    let (s_prev, s_prev2) = samples
+        .iter()
+        .fold((0.0, 0.0), |(s_prev, s_prev2), sample| {
+            (sample + coefficient * s_prev - s_prev2, s_prev)
+        });
+
+    let power = s_prev * s_prev + s_prev2 * s_prev2 - coefficient * s_prev * s_prev2;
+    // rounding errors might result in tiny negative values
+    sqrtf(power.max(0.0))
+}
+
+/// Like [`goertzel`] but for multiple frequencies.
+///
+/// ## Return value
+/// Magnitude of each frequency of `target_freqs` in the same order.
+#[inline]
+#[must_use]
+pub fn goertzel_multi(samples: &[f32], target_freqs: &[f32], sampling_rate: u32) -> Vec<f32> {
+    target_freqs
+        .iter()
+        .map(|target_freq| goertzel(samples, *target_freq, sampling_rate))
+        .collect()
+}
+
+/// A bank of Goertzel filters for a fixed set of frequencies and a fixed block
+/// length. The coefficients are calculated once, in [`GoertzelBank::new`].
+/// Hence, this is cheaper than [`goertzel_multi`] if the same frequencies are
+/// scanned in every block, e.g., on a microcontroller.
+#[derive(Debug, Clone)]
+pub struct GoertzelBank {
+    /// Coefficient `2 * cos(omega)` of each frequency.
+    coefficients: Vec<f32>,
+    /// Number of samples of each block.
+    block_len: usize,
+}
+
+impl GoertzelBank {
+    /// Constructor.
+    ///
+    /// ## Parameters
+    /// * `frequencies` Frequencies in Hertz. Should be in interval
+    ///                 `[0; sampling_rate / 2]`.
+    /// * `sampling_rate` Sampling rate in Hertz. Must be bigger than `0`.
+    /// * `block_len` Number of samples of each block that is passed to
+    ///               [`Self::process`] or [`Self::process_into`].
+    ///
+    /// ## Panics
+    /// If `sampling_rate` is `0`.
+    #[must_use]
+    pub fn new(frequencies: &[f32], sampling_rate: u32, block_len: usize) -> Self {
+        assert!(
+            sampling_rate > 0,
+            "the sampling rate must be bigger than 0 Hz"
+        );
+        Self {
+            coefficients: frequencies
+                .iter()
+                .map(|frequency| coefficient(*frequency, sampling_rate))
+                .collect(),
+            block_len,
+        }
+    }
+
+    /// Getter for the number of samples of each block.
+    #[inline]
+    #[must_use]
+    pub const fn block_len(&self) -> usize {
+        self.block_len
+    }
+
+    /// Getter for the number of frequencies.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    /// Returns whether the bank has no frequencies.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    /// Calculates the magnitude of each frequency in the block. See
+    /// [`goertzel`].
+    ///
+    /// ## Return value
+    /// Magnitude of each frequency in the order of construction.
+    ///
+    /// ## Errors
+    /// If the number of samples differs from [`Self::block_len`].
+    #[inline]
+    pub fn process(&self, samples: &[f32]) -> Result<Vec<f32>, SpectrumAnalyzerError> {
+        let mut magnitudes = vec![0.0; self.len()];
+        self.process_into(samples, &mut magnitudes)?;
+        Ok(magnitudes)
+    }
+
+    /// Like [`Self::process`] but writes the magnitudes into a buffer of the
+    /// caller. This doesn't allocate.
+    ///
+    /// ## Parameters
+    /// * `samples` Block of samples.
+    /// * `magnitudes` Buffer for the magnitude of each frequency. Must have
+    ///                [`Self::len`] elements.
+    ///
+    /// ## Errors
+    /// If the number of samples differs from [`Self::block_len`].
+    ///
+    /// ## Panics
+    /// If `magnitudes` has not [`Self::len`] elements.
+    #[inline]
+    pub fn process_into(
+        &self,
+        samples: &[f32],
+        magnitudes: &mut [f32],
+    ) -> Result<(), SpectrumAnalyzerError> {
+        assert_eq!(
+            magnitudes.len(),
+            self.len(),
+            "The buffer must have one element per frequency!"
+        );
+        if samples.len() != self.block_len {
+            return Err(SpectrumAnalyzerError::BlockLengthMismatch(
+                samples.len(),
+                self.block_len,
+            ));
+        }
+        for (magnitude, coefficient) in magnitudes.iter_mut().zip(&self.coefficients) {
+            *magnitude = goertzel_with_coefficient(samples, *coefficient);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signal_gen::sine_wave;
+    use crate::{samples_fft_to_spectrum, FrequencyLimit};
+
+    #[test]
+    fn test_goertzel_equals_fft_bin() {
+        // 8000 / 256 = 31.25 Hz frequency resolution
+        let samples = sine_wave(1000.0, 8000, 32)
+            .iter()
+            .zip(sine_wave(2000.0, 8000, 32))
+            .map(|(a, b)| a + 0.5 * b)
+            .collect::<Vec<_>>();
+        let spectrum = samples_fft_to_spectrum(&samples, 8000, FrequencyLimit::All, None).unwrap();
+        for fr in [0.0, 500.0, 1000.0, 2000.0, 3000.0] {
+            float_cmp::assert_approx_eq!(
+                f32,
+                goertzel(&samples, fr, 8000),
+                spectrum.freq_val_exact(fr).val(),
+                epsilon = 1e-2
+            );
+        }
+    }
+
+    #[test]
+    fn test_goertzel_multi() {
+        // no power of two
+        let samples = &sine_wave(697.0, 8000, 26)[..205];
+        let magnitudes = goertzel_multi(samples, &[697.0, 770.0, 1209.0], 8000);
+        assert_eq!(magnitudes.len(), 3);
+        // N/2 for a sine with amplitude 1
+        float_cmp::assert_approx_eq!(f32, magnitudes[0], 102.5, epsilon = 1.0);
+        assert!(magnitudes[1] < magnitudes[0] / 4.0);
+        assert!(magnitudes[2] < magnitudes[0] / 10.0);
+    }
+
+    #[test]
+    fn test_goertzel_bank() {
+        let frequencies = [0.0, 500.0, 697.0, 1000.0, 2000.0, 3000.0];
+        let bank = GoertzelBank::new(&frequencies, 8000, 256);
+        assert_eq!(bank.len(), 6);
+        assert_eq!(bank.block_len(), 256);
+
+        let samples = sine_wave(1000.0, 8000, 32)
+            .iter()
+            .zip(sine_wave(2000.0, 8000, 32))
+            .map(|(a, b)| a + 0.5 * b)
+            .collect::<Vec<_>>();
+        let magnitudes = bank.process(&samples).unwrap();
+        let mut magnitudes_into = [0.0; 6];
+        bank.process_into(&samples, &mut magnitudes_into).unwrap();
+        assert_eq!(magnitudes, magnitudes_into);
+
+        let spectrum = samples_fft_to_spectrum(&samples, 8000, FrequencyLimit::All, None).unwrap();
+        for (frequency, magnitude) in frequencies.iter().zip(&magnitudes) {
+            assert_eq!(*magnitude, goertzel(&samples, *frequency, 8000));
+            // 697 Hz is not on a bin
+            if *frequency != 697.0 {
+                float_cmp::assert_approx_eq!(
+                    f32,
+                    *magnitude,
+                    spectrum.freq_val_exact(*frequency).val(),
+                    epsilon = 1e-2
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_goertzel_bank_block_length_mismatch() {
+        let bank = GoertzelBank::new(&[1000.0], 8000, 256);
+        assert!(matches!(
+            bank.process(&[0.0; 255]),
+            Err(SpectrumAnalyzerError::BlockLengthMismatch(255, 256))
+        ));
+        assert!(GoertzelBank::new(&[], 8000, 4)
+            .process(&[0.0; 4])
+            .unwrap()
+            .is_empty());
+    }
+}
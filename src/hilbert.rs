@@ -0,0 +1,131 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the [analytic signal](https://en.wikipedia.org/wiki/Analytic_signal)
+//! via the [Hilbert transform](https://en.wikipedia.org/wiki/Hilbert_transform)
+//! and the envelope of a signal that can be derived from it.
+
+use crate::error::SpectrumAnalyzerError;
+use crate::fft::FftImpl;
+use crate::{verify_samples, Complex32};
+use alloc::vec::Vec;
+
+/// Calculates the analytic signal of the given real samples. The real part
+/// equals the original signal and the imaginary part is its Hilbert transform.
+///
+/// This is done in the frequency domain: the complex FFT of the samples is
+/// calculated, the negative frequencies are set to zero, the positive
+/// frequencies are doubled (DC component and Nyquist frequency stay
+/// unchanged), and the inverse FFT transforms everything back.
+///
+/// ## Parameters
+/// * `samples` Real samples. The same restrictions as for
+///             [`crate::samples_fft_to_spectrum`] apply, i.e., the length must
+///             be a power of two.
+///
+/// ## Return value
+/// Complex analytic signal with the same length as `samples`.
+pub fn analytic_signal(samples: &[f32]) -> Result<Vec<Complex32>, SpectrumAnalyzerError> {
+    verify_samples(samples)?;
+
+    let mut buffer = samples
+        .iter()
+        .map(|x| Complex32::new(*x, 0.0))
+        .collect::<Vec<_>>();
+    FftImpl::calc_complex(&mut buffer);
+
+    // Index 0 (DC) and N/2 (Nyquist) stay unchanged, 1..N/2 are the positive
+    // frequencies and N/2+1..N are the negative frequencies.
+    let nyquist_index = buffer.len() / 2;
+    for (i, bin) in buffer.iter_mut().enumerate() {
+        if i == 0 || i == nyquist_index {
+            continue;
+        } else if i < nyquist_index {
+            *bin *= 2.0;
+        } else {
+            *bin = Complex32::new(0.0, 0.0);
+        }
+    }
+
+    FftImpl::calc_complex_inverse(&mut buffer);
+    Ok(buffer)
+}
+
+/// Calculates the (amplitude) envelope of the given real samples, i.e., the
+/// magnitude of its [`analytic_signal`]. Useful for envelope detection, e.g.,
+/// of amplitude modulated signals.
+///
+/// ## Parameters
+/// * `samples` Real samples. See [`analytic_signal`].
+///
+/// ## Return value
+/// Envelope with the same length as `samples`.
+pub fn envelope(samples: &[f32]) -> Result<Vec<f32>, SpectrumAnalyzerError> {
+    let analytic_signal = analytic_signal(samples)?;
+    Ok(analytic_signal
+        .iter()
+        .map(|x| libm::sqrtf(x.re * x.re + x.im * x.im))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f32::consts::PI;
+
+    #[test]
+    fn test_analytic_signal_of_cosine() {
+        // 8 full periods: the Hilbert transform of cos is sin
+        let samples = (0..256)
+            .map(|i| libm::cosf(2.0 * PI * 8.0 * i as f32 / 256.0))
+            .collect::<Vec<_>>();
+        let analytic_signal = analytic_signal(&samples).unwrap();
+        for (i, (sample, analytic)) in samples.iter().zip(analytic_signal.iter()).enumerate() {
+            let expected_im = libm::sinf(2.0 * PI * 8.0 * i as f32 / 256.0);
+            float_cmp::assert_approx_eq!(f32, *sample, analytic.re, epsilon = 1e-4);
+            float_cmp::assert_approx_eq!(f32, expected_im, analytic.im, epsilon = 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_envelope_of_am_signal() {
+        // carrier at 64 periods, modulated by 0.75 + 0.25 * cos(2 periods)
+        let modulation = |i: usize| 0.75 + 0.25 * libm::cosf(2.0 * PI * 2.0 * i as f32 / 1024.0);
+        let samples = (0..1024)
+            .map(|i| modulation(i) * libm::sinf(2.0 * PI * 64.0 * i as f32 / 1024.0))
+            .collect::<Vec<_>>();
+        let envelope = envelope(&samples).unwrap();
+        for (i, val) in envelope.iter().enumerate() {
+            float_cmp::assert_approx_eq!(f32, modulation(i), *val, epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let err = analytic_signal(&[0.0; 3]).unwrap_err();
+        assert!(matches!(
+            err,
+            SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo
+        ));
+    }
+}
@@ -0,0 +1,247 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the detection of mains hum, i.e., interference from the power
+//! grid at 50 Hz (e.g., Europe) or 60 Hz (e.g., North America) and its
+//! harmonics. See [`detect_mains_hum`].
+
+use crate::{FrequencySpectrum, FrequencyValue};
+use alloc::vec::Vec;
+
+/// Fundamental frequencies in Hertz of the power grids.
+pub const MAINS_FREQUENCIES: [f32; 2] = [50.0, 60.0];
+
+/// Number of harmonics (including the fundamental) that are checked for each
+/// mains frequency. The combs of 50 Hz and 60 Hz don't share a frequency
+/// below 300 Hz.
+const MAX_HARMONICS: usize = 5;
+
+/// A harmonic may deviate this much in Hertz from its nominal frequency, as
+/// the grid frequency varies slightly.
+const TOLERANCE_HZ: f32 = 1.0;
+
+/// A harmonic is present if its value is at least this factor bigger than
+/// the median of its surroundings, i.e., the local noise floor.
+const PEAK_FACTOR: f32 = 4.0;
+
+/// Minimum number of present harmonics for a detection.
+const MIN_HARMONICS: usize = 2;
+
+/// The energy of the detected comb must be at least this factor bigger than
+/// the energy of the other comb.
+const MIN_SCORE_RATIO: f32 = 2.0;
+
+/// Mains hum detected by [`detect_mains_hum`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MainsHum {
+    /// Fundamental frequency in Hertz, i.e., `50.0` or `60.0`.
+    pub fundamental: f32,
+    /// Number of present harmonics, including the fundamental, out of the
+    /// first five.
+    pub harmonics_found: usize,
+    /// Energy of all present harmonics relative to the energy of the whole
+    /// spectrum in dB. `0.0` means that the signal consists only of hum.
+    pub level_db_rel_total: f32,
+}
+
+/// Detects mains hum, e.g., to choose the right notch filter for a
+/// recording. For 50 Hz and 60 Hz, this checks the first five harmonics
+/// (50, 100, ..., 250 Hz and 60, 120, ..., 300 Hz) for peaks that stand out
+/// from the local noise floor. The hypothesis whose peaks have more energy
+/// wins, if it has at least two peaks and clearly more energy than the other
+/// one.
+///
+/// ## Parameters
+/// * `spectrum` Spectrum of the recording. Its frequency resolution should be
+///              about `1 Hz` or finer to separate the harmonics from other
+///              tones. The values must be linear magnitudes, i.e., no
+///              logarithmic (dB) scaling function must be applied.
+///
+/// ## Return value
+/// The detected hum or `None`, if no hum or no clear winner was found.
+#[inline]
+#[must_use]
+pub fn detect_mains_hum(spectrum: &FrequencySpectrum) -> Option<MainsHum> {
+    if spectrum.is_empty() {
+        return None;
+    }
+    let total_energy = spectrum.band_energy(spectrum.min_fr().val(), spectrum.max_fr().val());
+    if total_energy == 0.0 {
+        return None;
+    }
+
+    let [hum_50, hum_60] = MAINS_FREQUENCIES.map(|fundamental| {
+        let peaks = (1..=MAX_HARMONICS)
+            .filter_map(|k| harmonic_peak(spectrum, k as f32 * fundamental, fundamental))
+            .collect::<Vec<_>>();
+        let energy = peaks.iter().map(|val| val.val() * val.val()).sum::<f32>();
+        (fundamental, peaks.len(), energy)
+    });
+    let ((fundamental, harmonics_found, energy), (_, _, other_energy)) = if hum_50.2 >= hum_60.2 {
+        (hum_50, hum_60)
+    } else {
+        (hum_60, hum_50)
+    };
+    if harmonics_found < MIN_HARMONICS || energy < MIN_SCORE_RATIO * other_energy {
+        return None;
+    }
+
+    Some(MainsHum {
+        fundamental,
+        harmonics_found,
+        level_db_rel_total: 10.0 * libm::log10f(energy / total_energy),
+    })
+}
+
+/// Returns the value of the peak around `frequency`, if there is a local
+/// maximum within [`TOLERANCE_HZ`] that stands out from the median of the
+/// surroundings of width `spacing`. Returns `None` if the surroundings
+/// contain no bin, i.e., the frequency resolution is too coarse.
+fn harmonic_peak(
+    spectrum: &FrequencySpectrum,
+    frequency: f32,
+    spacing: f32,
+) -> Option<FrequencyValue> {
+    let data = spectrum.data();
+    let tolerance = TOLERANCE_HZ.max(spectrum.frequency_resolution());
+    let index_of = |fr: f32| data.partition_point(|(bin_fr, _)| bin_fr.val() < fr);
+
+    let begin = index_of(frequency - tolerance);
+    let end = index_of(frequency + tolerance + f32::EPSILON);
+    let (peak_index, peak_val) = (begin..end)
+        .map(|i| (i, data[i].1))
+        .max_by_key(|(_, val)| *val)?;
+    // must be a local maximum and not the flank of a neighbouring tone
+    if peak_index == 0
+        || peak_index + 1 >= data.len()
+        || peak_val <= data[peak_index - 1].1
+        || peak_val < data[peak_index + 1].1
+    {
+        return None;
+    }
+
+    let mut surroundings = data
+        [index_of(frequency - spacing / 2.0)..index_of(frequency + spacing / 2.0)]
+        .iter()
+        .map(|(_fr, val)| *val)
+        .collect::<Vec<_>>();
+    surroundings.sort_unstable();
+    let median = *surroundings.get(surroundings.len() / 2)?;
+    (peak_val.val() > PEAK_FACTOR * median.val()).then_some(peak_val)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scaling::divide_by_N;
+    use crate::signal_gen::white_noise;
+    use crate::windows::hann_window;
+    use crate::{samples_fft_to_spectrum, FrequencyLimit};
+    use core::f32::consts::PI;
+
+    const SAMPLING_RATE: u32 = 8000;
+
+    /// Spectrum of the sum of sines `(frequency, amplitude)` plus a little
+    /// white noise. The resolution is about 1 Hz.
+    fn spectrum_of(tones: &[(f32, f32)]) -> FrequencySpectrum {
+        // 8192 samples
+        let noise = white_noise(0.005, SAMPLING_RATE, 1024, 12345);
+        let samples = noise
+            .iter()
+            .enumerate()
+            .map(|(i, noise)| {
+                let t = i as f32 / SAMPLING_RATE as f32;
+                tones
+                    .iter()
+                    .map(|(fr, amplitude)| amplitude * libm::sinf(2.0 * PI * fr * t))
+                    .sum::<f32>()
+                    + noise
+            })
+            .collect::<Vec<_>>();
+        samples_fft_to_spectrum(
+            &hann_window(&samples),
+            SAMPLING_RATE,
+            FrequencyLimit::All,
+            Some(&divide_by_N),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_detect_mains_hum_50_hz() {
+        let spectrum = spectrum_of(&[
+            (50.0, 0.1),
+            (100.0, 0.05),
+            (150.0, 0.05),
+            (250.0, 0.02),
+            (1000.0, 1.0),
+        ]);
+        let hum = detect_mains_hum(&spectrum).unwrap();
+        assert_eq!(hum.fundamental, 50.0);
+        assert_eq!(hum.harmonics_found, 4);
+        // about 1/80 of the energy
+        assert!(
+            (-22.0..-16.0).contains(&hum.level_db_rel_total),
+            "{}",
+            hum.level_db_rel_total
+        );
+    }
+
+    #[test]
+    fn test_detect_mains_hum_60_hz() {
+        // slightly off the nominal frequency
+        let spectrum = spectrum_of(&[(60.3, 0.1), (120.6, 0.1), (180.9, 0.05), (440.0, 1.0)]);
+        let hum = detect_mains_hum(&spectrum).unwrap();
+        assert_eq!(hum.fundamental, 60.0);
+        assert_eq!(hum.harmonics_found, 3);
+    }
+
+    #[test]
+    fn test_detect_mains_hum_none() {
+        assert_eq!(detect_mains_hum(&spectrum_of(&[(1000.0, 1.0)])), None);
+        assert_eq!(
+            detect_mains_hum(&spectrum_of(&[(440.0, 1.0), (1000.0, 0.5)])),
+            None
+        );
+        // a single tone at 50 Hz is no comb
+        assert_eq!(detect_mains_hum(&spectrum_of(&[(50.0, 1.0)])), None);
+        assert_eq!(detect_mains_hum(&FrequencySpectrum::empty()), None);
+        let silence =
+            samples_fft_to_spectrum(&[0.0; 1024], SAMPLING_RATE, FrequencyLimit::All, None)
+                .unwrap();
+        assert_eq!(detect_mains_hum(&silence), None);
+    }
+
+    #[test]
+    fn test_detect_mains_hum_low_resolution() {
+        // a resolution of about 172 Hz is much coarser than the spacing of
+        // the harmonics
+        let samples = (0..256)
+            .map(|i| libm::sinf(2.0 * PI * 172.0 * i as f32 / 44100.0))
+            .collect::<Vec<_>>();
+        let spectrum =
+            samples_fft_to_spectrum(&hann_window(&samples), 44100, FrequencyLimit::All, None)
+                .unwrap();
+        assert_eq!(detect_mains_hum(&spectrum), None);
+    }
+}
@@ -73,9 +73,9 @@ SOFTWARE.
 #![deny(rustdoc::all)]
 #![no_std]
 
-// enable std in tests (println!() for example)
+// enable std in tests (println!() for example) and for the `std` feature
 #[cfg_attr(test, macro_use)]
-#[cfg(test)]
+#[cfg(any(test, feature = "std"))]
 extern crate std;
 
 // We use alloc crate, because this is no_std
@@ -85,19 +85,44 @@ extern crate alloc;
 
 use alloc::vec::Vec;
 
+pub use crate::config::{BinValue, LimitStage, SpectrumConfig};
 use crate::error::SpectrumAnalyzerError;
-use crate::fft::{Complex32, FftImpl};
+use crate::fft::FftImpl;
+pub use crate::fft::{Complex32, MAX_SAMPLES_LEN};
 pub use crate::frequency::{Frequency, FrequencyValue};
 pub use crate::limit::FrequencyLimit;
 pub use crate::limit::FrequencyLimitError;
 use crate::scaling::SpectrumScalingFunction;
-pub use crate::spectrum::FrequencySpectrum;
+pub use crate::spectrum::{
+    AggregationMode, AnalysisDiagnostics, FrequencySpectrum, Interpolation, MismatchReport,
+    DEFAULT_SINC_KERNEL_BINS,
+};
 
+pub mod adaptive;
+pub mod calibration;
+mod config;
+pub mod dtmf;
 pub mod error;
+#[cfg(feature = "std")]
+pub mod export;
+pub mod features;
 mod fft;
 mod frequency;
+pub mod goertzel;
+pub mod hilbert;
+pub mod hum;
 mod limit;
+pub mod minimal;
+pub mod parseval;
+pub mod peaks;
+pub mod phase;
+pub mod pipeline;
+pub mod resample;
 pub mod scaling;
+#[cfg(any(test, feature = "signal-gen"))]
+pub mod signal_gen;
+pub mod sparse;
+pub mod spectrogram;
 mod spectrum;
 pub mod windows;
 
@@ -116,8 +141,8 @@ mod tests;
 ///
 /// * `samples` raw audio, e.g. 16bit audio data but as f32.
 ///             You should apply an window function (like Hann) on the data first.
-///             The final frequency resolution is `sample_rate / (N / 2)`
-///             e.g. `44100/(16384/2) == 5.383Hz`, i.e. more samples =>
+///             The final frequency resolution is `sample_rate / N`
+///             e.g. `44100/16384 == 2.692Hz`, i.e. more samples =>
 ///             better accuracy/frequency resolution. The amount of samples must
 ///             be a power of 2. If you don't have enough data, provide zeroes.
 /// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
@@ -125,7 +150,10 @@ mod tests;
 /// * `scaling_fn` See [`crate::scaling::SpectrumScalingFunction`] for details.
 ///
 /// ## Returns value
-/// New object of type [`FrequencySpectrum`].
+/// New object of type [`FrequencySpectrum`]. Without a frequency limit, it
+/// contains `N / 2 + 1` frequency bins from `0 Hz` (DC component) up to and
+/// including the Nyquist frequency `sampling_rate / 2`. See
+/// [`SpectrumConfig::include_nyquist`] to exclude the Nyquist frequency.
 ///
 /// ## Examples
 /// ### Scaling via dynamic closure
@@ -154,33 +182,57 @@ mod tests;
 ///  );
 /// ```
 ///
-/// ## Panics
-/// * When `samples.len()` isn't a power of two less than or equal to `16384` and `microfft` is used
+/// ## Errors
+/// Besides other input errors, the function returns
+/// [`SpectrumAnalyzerError::SamplesLengthTooLarge`] when `samples.len()` is
+/// bigger than [`MAX_SAMPLES_LEN`] and
+/// [`SpectrumAnalyzerError::FrequencyLimitExcludesAllBins`] when the
+/// frequency limit leaves fewer than two frequency bins. A `sampling_rate` of
+/// `0` results in [`SpectrumAnalyzerError::ZeroSamplingRate`].
 pub fn samples_fft_to_spectrum(
     samples: &[f32],
     sampling_rate: u32,
     frequency_limit: FrequencyLimit,
     scaling_fn: Option<&SpectrumScalingFunction>,
 ) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
-    // everything below two samples is unreasonable
-    if samples.len() < 2 {
-        return Err(SpectrumAnalyzerError::TooFewSamples);
-    }
-    // do several checks on input data
-    if samples.iter().any(|x| x.is_nan()) {
-        return Err(SpectrumAnalyzerError::NaNValuesNotSupported);
-    }
-    if samples.iter().any(|x| x.is_infinite()) {
-        return Err(SpectrumAnalyzerError::InfinityValuesNotSupported);
-    }
-    if !samples.len().is_power_of_two() {
-        return Err(SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo);
-    }
-    let max_detectable_frequency = sampling_rate as f32 / 2.0;
-    // verify frequency limit: unwrap error or else ok
-    frequency_limit
-        .verify(max_detectable_frequency)
-        .map_err(SpectrumAnalyzerError::InvalidFrequencyLimit)?;
+    samples_fft_to_spectrum_with_config(
+        samples,
+        sampling_rate,
+        frequency_limit,
+        scaling_fn,
+        &SpectrumConfig::default(),
+    )
+}
+
+/// Like [`samples_fft_to_spectrum`] but with additional options to fine-tune
+/// the analysis pipeline. See [`SpectrumConfig`].
+///
+/// ## Examples
+/// ```rust
+/// use spectrum_analyzer::{samples_fft_to_spectrum_with_config, FrequencyLimit, LimitStage, SpectrumConfig};
+/// use spectrum_analyzer::scaling::scale_to_zero_to_one;
+/// // get data from audio source
+/// let samples = vec![0.0, 1.1, 5.5, -5.5];
+/// let res = samples_fft_to_spectrum_with_config(
+///         &samples,
+///         44100,
+///         FrequencyLimit::Max(12000.0),
+///         // normalized against the full spectrum and not just the bins <= 12000 Hz
+///         Some(&scale_to_zero_to_one),
+///         &SpectrumConfig {
+///             limit_stage: LimitStage::AfterScaling,
+///             ..SpectrumConfig::default()
+///         },
+///  );
+/// ```
+pub fn samples_fft_to_spectrum_with_config(
+    samples: &[f32],
+    sampling_rate: u32,
+    frequency_limit: FrequencyLimit,
+    scaling_fn: Option<&SpectrumScalingFunction>,
+    config: &SpectrumConfig,
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    verify_input(samples, sampling_rate, frequency_limit, config)?;
 
     // With FFT we transform an array of time-domain waveform samples
     // into an array of frequency-domain spectrum samples
@@ -201,13 +253,353 @@ pub fn samples_fft_to_spectrum(
     // 3) calculates the magnitude (absolute value) at each frequency index for each complex value
     // 4) optionally scales the magnitudes
     // 5) collects everything into the struct "FrequencySpectrum"
-    fft_result_to_spectrum(
+    let mut spectrum = fft_result_to_spectrum(
         samples.len(),
         &fft_res,
         sampling_rate,
         frequency_limit,
         scaling_fn,
-    )
+        config,
+    )?;
+    spectrum.set_frame_level(samples);
+    Ok(spectrum)
+}
+
+/// Like [`samples_fft_to_spectrum_with_config`] but returns the spectrum
+/// twice from a single FFT: with the raw (unscaled) values, e.g., for
+/// calculations, and with `scaling_fn` applied, e.g., for display. This is
+/// cheaper than two separate analyses. The scaled spectrum equals the raw
+/// spectrum after [`FrequencySpectrum::apply_scaling_fn`].
+///
+/// ## Parameters
+/// * `samples` See [`samples_fft_to_spectrum`].
+/// * `sampling_rate` See [`samples_fft_to_spectrum`].
+/// * `frequency_limit` See [`samples_fft_to_spectrum`]. Applies to both
+///                     spectra. With [`LimitStage::AfterScaling`], the
+///                     scaling function nevertheless sees the statistics of
+///                     the full spectrum.
+/// * `scaling_fn` Scaling function for the second spectrum. See
+///                [`crate::scaling::SpectrumScalingFunction`].
+/// * `config` See [`SpectrumConfig`]. Applies to both spectra.
+///
+/// ## Return value
+/// `(raw, scaled)` spectra with the same frequencies.
+///
+/// ## Errors
+/// The same as for [`samples_fft_to_spectrum_with_config`].
+pub fn samples_fft_to_spectrum_dual(
+    samples: &[f32],
+    sampling_rate: u32,
+    frequency_limit: FrequencyLimit,
+    scaling_fn: &SpectrumScalingFunction,
+    config: &SpectrumConfig,
+) -> Result<(FrequencySpectrum, FrequencySpectrum), SpectrumAnalyzerError> {
+    verify_input(samples, sampling_rate, frequency_limit, config)?;
+    let fft_res = FftImpl::calc(samples);
+    let (mut raw, post_scaling_limit) = fft_result_to_unscaled_spectrum(
+        samples.len(),
+        &fft_res,
+        sampling_rate,
+        frequency_limit,
+        config,
+    )?;
+
+    // the only additional allocation: the scaled copy of the values
+    let mut scaled = FrequencySpectrum::new(
+        raw.data().to_vec(),
+        raw.frequency_resolution(),
+        raw.samples_len(),
+        &mut [],
+    );
+    scaled.apply_scaling_fn(scaling_fn, &mut [])?;
+
+    if let Some(frequency_limit) = post_scaling_limit {
+        raw = limit_spectrum(&raw, frequency_limit)?;
+        scaled = limit_spectrum(&scaled, frequency_limit)?;
+    }
+    raw.set_frame_level(samples);
+    scaled.set_frame_level(samples);
+    Ok((raw, scaled))
+}
+
+/// Convenient wrapper around [`samples_fft_to_spectrum`] for interleaved
+/// stereo samples (`[L, R, L, R, ...]`). Each frame is downmixed to mono by
+/// averaging the left and the right channel before the spectrum is
+/// calculated.
+///
+/// ## Parameters
+/// * `interleaved` Interleaved stereo samples. The number of frames, i.e.,
+///                 `interleaved.len() / 2`, must be a power of two. Apply a
+///                 window function on the mono samples yourself if you need
+///                 one, e.g., by downmixing manually.
+/// * `sampling_rate` See [`samples_fft_to_spectrum`].
+/// * `frequency_limit` See [`samples_fft_to_spectrum`].
+/// * `scaling_fn` See [`samples_fft_to_spectrum`].
+///
+/// ## Errors
+/// [`SpectrumAnalyzerError::OddInterleavedSamplesLength`] if the number of
+/// samples is odd, and every error of [`samples_fft_to_spectrum`] otherwise.
+pub fn samples_interleaved_stereo_to_mono_spectrum(
+    interleaved: &[f32],
+    sampling_rate: u32,
+    frequency_limit: FrequencyLimit,
+    scaling_fn: Option<&SpectrumScalingFunction>,
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    if interleaved.len() % 2 != 0 {
+        return Err(SpectrumAnalyzerError::OddInterleavedSamplesLength);
+    }
+    let mono_samples = interleaved
+        .chunks_exact(2)
+        .map(|frame| (frame[0] + frame[1]) / 2.0)
+        .collect::<Vec<_>>();
+    samples_fft_to_spectrum(&mono_samples, sampling_rate, frequency_limit, scaling_fn)
+}
+
+/// Applies an FFT on the samples and returns the raw complex FFT result from
+/// the DC component (index 0) to the Nyquist frequency (index `N/2`), i.e.,
+/// `samples.len() / 2 + 1` bins. Bin `k` corresponds to the frequency
+/// `k * sampling_rate / N`. Useful for phase related calculations, see
+/// [`crate::phase`].
+///
+/// The same restrictions as for [`samples_fft_to_spectrum`] apply to
+/// `samples`.
+pub fn samples_fft_to_complex_bins(
+    samples: &[f32],
+) -> Result<Vec<Complex32>, SpectrumAnalyzerError> {
+    verify_samples(samples)?;
+    Ok(FftImpl::calc(samples))
+}
+
+/// Like [`samples_fft_to_spectrum`] but returns the magnitude and the phase
+/// of each frequency bin from a single FFT, e.g., for phase vocoders.
+///
+/// ## Parameters
+/// * `samples` See [`samples_fft_to_spectrum`].
+/// * `sampling_rate` See [`samples_fft_to_spectrum`].
+/// * `frequency_limit` See [`samples_fft_to_spectrum`].
+///
+/// ## Return value
+/// `(frequency, magnitude, phase)`-triples sorted by frequency. The phase is
+/// in radians in interval `[-PI; PI]`, see [`crate::phase::phase`]. The
+/// magnitude is not scaled.
+///
+/// ## Errors
+/// The same as for [`samples_fft_to_spectrum`].
+pub fn samples_fft_to_mag_phase(
+    samples: &[f32],
+    sampling_rate: u32,
+    frequency_limit: FrequencyLimit,
+) -> Result<Vec<(Frequency, FrequencyValue, f32)>, SpectrumAnalyzerError> {
+    verify_input(
+        samples,
+        sampling_rate,
+        frequency_limit,
+        &SpectrumConfig::default(),
+    )?;
+    let complex_bins = FftImpl::calc(samples);
+    frequency_limit
+        .verify_bin_count(complex_bins.len())
+        .map_err(SpectrumAnalyzerError::InvalidFrequencyLimit)?;
+    let frequency_resolution = fft_calc_frequency_resolution(sampling_rate, samples.len() as u32);
+    let frequency_limit = frequency_limit.to_frequency_range(frequency_resolution);
+
+    let bins = complex_bins
+        .iter()
+        .enumerate()
+        .map(|(i, bin)| (i as f32 * frequency_resolution, bin))
+        .filter(|(fr, _bin)| frequency_limit.contains(*fr))
+        .map(|(fr, bin)| {
+            (
+                Frequency::from(fr),
+                FrequencyValue::from(complex_to_magnitude(bin)),
+                phase::phase(bin),
+            )
+        })
+        .collect::<Vec<_>>();
+    // same as for the spectrum
+    if bins.len() < 2 {
+        return Err(SpectrumAnalyzerError::FrequencyLimitExcludesAllBins);
+    }
+    Ok(bins)
+}
+
+/// Minimal hot path that only applies the FFT and calculates the magnitudes,
+/// e.g., for benchmarking the raw FFT throughput or if only the magnitudes are
+/// needed. In contrast to [`samples_fft_to_spectrum`], it neither calculates
+/// frequencies nor statistics, such as the median, and doesn't apply a
+/// frequency limit.
+///
+/// ## Parameters
+/// * `samples` See [`samples_fft_to_spectrum`].
+/// * `scaling_fn` Optional function that is applied to each magnitude. As no
+///                statistics are calculated, this is a plain function
+///                instead of a [`SpectrumScalingFunction`].
+///
+/// ## Return value
+/// Magnitude of each frequency bin from the DC component (index 0) to the
+/// Nyquist frequency (index `N/2`), i.e., `samples.len() / 2 + 1` values.
+/// Bin `k` corresponds to the frequency `k * sampling_rate / N`.
+///
+/// ## Errors
+/// The same input errors as for [`samples_fft_to_spectrum`] and
+/// [`SpectrumAnalyzerError::ScalingError`] if `scaling_fn` returns `NaN` or
+/// infinity.
+pub fn samples_fft_magnitudes_only(
+    samples: &[f32],
+    scaling_fn: Option<&dyn Fn(f32) -> f32>,
+) -> Result<Vec<f32>, SpectrumAnalyzerError> {
+    let complex_bins = samples_fft_to_complex_bins(samples)?;
+    let mut magnitudes = complex_bins
+        .iter()
+        .take(samples.len() / 2 + 1)
+        .map(complex_to_magnitude)
+        .collect::<Vec<_>>();
+
+    if let Some(scaling_fn) = scaling_fn {
+        for magnitude in &mut magnitudes {
+            let scaled_val = scaling_fn(*magnitude);
+            if scaled_val.is_nan() || scaled_val.is_infinite() {
+                return Err(SpectrumAnalyzerError::ScalingError(*magnitude, scaled_val));
+            }
+            *magnitude = scaled_val;
+        }
+    }
+    Ok(magnitudes)
+}
+
+/// Analyzes two signals in the same way and returns the difference `a - b`
+/// of each frequency bin, e.g., to compare a signal before and after a
+/// filter. With a logarithmic scaling, such as
+/// [`scaling::scale_20_times_log10`], the difference is in dB. If `a` is the
+/// output and `b` the input of a filter, the result is the frequency
+/// response of the filter. If `a` is the input instead, it is its inverse.
+///
+/// ## Parameters
+/// * `a` Samples of the first signal. See [`samples_fft_to_spectrum`].
+/// * `b` Samples of the second signal. Must have the same length as `a`.
+/// * `sampling_rate` See [`samples_fft_to_spectrum`].
+/// * `window` Window that is applied to both signals first. Use
+///            [`windows::WindowType::Rectangular`] for no window.
+/// * `frequency_limit` See [`samples_fft_to_spectrum`].
+/// * `scaling_fn` Scaling function that is applied to both spectra before
+///                the difference is calculated. See
+///                [`samples_fft_to_spectrum`].
+///
+/// ## Return value
+/// Spectrum with the difference of each frequency bin.
+///
+/// ## Errors
+/// [`SpectrumAnalyzerError::SamplesLengthMismatch`] if `a` and `b` have
+/// different lengths, and every error of [`samples_fft_to_spectrum`]
+/// otherwise.
+pub fn difference_spectrum(
+    a: &[f32],
+    b: &[f32],
+    sampling_rate: u32,
+    window: windows::WindowType,
+    frequency_limit: FrequencyLimit,
+    scaling_fn: Option<&SpectrumScalingFunction>,
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    if a.len() != b.len() {
+        return Err(SpectrumAnalyzerError::SamplesLengthMismatch(
+            a.len(),
+            b.len(),
+        ));
+    }
+    let spectrum_a =
+        samples_fft_to_spectrum(&window.apply(a), sampling_rate, frequency_limit, scaling_fn)?;
+    let spectrum_b =
+        samples_fft_to_spectrum(&window.apply(b), sampling_rate, frequency_limit, scaling_fn)?;
+
+    let difference = spectrum_a
+        .data()
+        .iter()
+        .zip(spectrum_b.data())
+        .map(|((fr, val_a), (_fr, val_b))| (*fr, *val_a - *val_b));
+    Ok(FrequencySpectrum::from_iter_sorted(
+        difference,
+        spectrum_a.frequency_resolution(),
+        spectrum_a.samples_len(),
+    ))
+}
+
+/// Verifies that the samples can be passed into the FFT: there must be at least
+/// two samples but not more than [`MAX_SAMPLES_LEN`], the length must be a
+/// power of two, and there must be no `NaN` or infinite values.
+fn verify_samples(samples: &[f32]) -> Result<(), SpectrumAnalyzerError> {
+    // everything below two samples is unreasonable
+    if samples.len() < 2 {
+        return Err(SpectrumAnalyzerError::TooFewSamples);
+    }
+    if samples.len() > MAX_SAMPLES_LEN {
+        return Err(SpectrumAnalyzerError::SamplesLengthTooLarge(
+            samples.len(),
+            MAX_SAMPLES_LEN,
+        ));
+    }
+    // do several checks on input data
+    if samples.iter().any(|x| x.is_nan()) {
+        return Err(SpectrumAnalyzerError::NaNValuesNotSupported);
+    }
+    if samples.iter().any(|x| x.is_infinite()) {
+        return Err(SpectrumAnalyzerError::InfinityValuesNotSupported);
+    }
+    if !samples.len().is_power_of_two() {
+        return Err(SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo);
+    }
+    Ok(())
+}
+
+/// Verifies all inputs of [`samples_fft_to_spectrum_with_config`] before the
+/// FFT is calculated.
+fn verify_input(
+    samples: &[f32],
+    sampling_rate: u32,
+    frequency_limit: FrequencyLimit,
+    config: &SpectrumConfig,
+) -> Result<(), SpectrumAnalyzerError> {
+    // check this first, before anything else is done with the samples
+    if samples.len() > config.max_samples_len {
+        return Err(SpectrumAnalyzerError::SamplesLengthTooLarge(
+            samples.len(),
+            config.max_samples_len,
+        ));
+    }
+    verify_samples(samples)?;
+    verify_sampling_rate(sampling_rate)?;
+    if let Some(max_window_duration) = config.max_window_duration {
+        let window_duration = samples.len() as f32 / sampling_rate as f32;
+        if window_duration > max_window_duration {
+            return Err(SpectrumAnalyzerError::WindowDurationTooLong(
+                window_duration,
+                max_window_duration,
+            ));
+        }
+    }
+    if let Some(floor) = config.magnitude_floor {
+        if floor.is_nan() || floor <= 0.0 {
+            return Err(SpectrumAnalyzerError::InvalidMagnitudeFloor(floor));
+        }
+    }
+    if let Some(gain) = config.coherent_gain {
+        if gain.is_nan() || gain <= 0.0 {
+            return Err(SpectrumAnalyzerError::InvalidCoherentGain(gain));
+        }
+    }
+    let max_detectable_frequency = sampling_rate as f32 / 2.0;
+    // verify frequency limit: unwrap error or else ok
+    frequency_limit
+        .verify(max_detectable_frequency)
+        .map_err(SpectrumAnalyzerError::InvalidFrequencyLimit)?;
+    Ok(())
+}
+
+/// Verifies that the sampling rate is valid, i.e., bigger than `0 Hz`.
+const fn verify_sampling_rate(sampling_rate: u32) -> Result<(), SpectrumAnalyzerError> {
+    if sampling_rate == 0 {
+        return Err(SpectrumAnalyzerError::ZeroSamplingRate);
+    }
+    Ok(())
 }
 
 /// Transforms the FFT result into the spectrum by calculating the corresponding frequency of each
@@ -218,29 +610,84 @@ pub fn samples_fft_to_spectrum(
 /// * `samples_len` Length of samples. This is a dedicated field because it can't always be
 ///                 derived from `fft_result.len()`. There are for example differences for
 ///                 `fft_result.len()` in real and complex FFT algorithms.
-/// * `fft_result` Result buffer from FFT. Has the same length as the samples array.
+/// * `fft_result` Bins of the FFT result, starting with the DC component and
+///                including the Nyquist frequency (index `samples_len / 2`),
+///                e.g., from [`FftImpl::calc`]. Further bins are ignored.
 /// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
 /// * `frequency_limit` Frequency limit. See [`FrequencyLimit´]
 /// * `scaling_fn` See [`crate::scaling::SpectrumScalingFunction`].
+/// * `config` See [`SpectrumConfig`].
 ///
 /// ## Return value
 /// New object of type [`FrequencySpectrum`].
 #[inline]
-fn fft_result_to_spectrum(
+fn fft_result_to_spectrum<'a>(
     samples_len: usize,
-    fft_result: &[Complex32],
+    fft_result: impl IntoIterator<Item = &'a Complex32>,
     sampling_rate: u32,
     frequency_limit: FrequencyLimit,
     scaling_fn: Option<&SpectrumScalingFunction>,
+    config: &SpectrumConfig,
 ) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
-    let maybe_min = frequency_limit.maybe_min();
-    let maybe_max = frequency_limit.maybe_max();
+    let (mut spectrum, post_scaling_limit) = fft_result_to_unscaled_spectrum(
+        samples_len,
+        fft_result,
+        sampling_rate,
+        frequency_limit,
+        config,
+    )?;
+
+    // optionally scale
+    if let Some(scaling_fn) = scaling_fn {
+        spectrum.apply_scaling_fn(scaling_fn, &mut [])?
+    }
+
+    // optionally apply the frequency limit on the scaled spectrum
+    if let Some(frequency_limit) = post_scaling_limit {
+        spectrum = limit_spectrum(&spectrum, frequency_limit)?;
+    }
+
+    Ok(spectrum)
+}
+
+/// First part of [`fft_result_to_spectrum`]: creates the spectrum without
+/// scaling. The parameters are the same.
+///
+/// ## Return value
+/// The unscaled spectrum and the frequency limit that still has to be
+/// applied after the scaling, see [`LimitStage::AfterScaling`].
+#[inline]
+fn fft_result_to_unscaled_spectrum<'a>(
+    samples_len: usize,
+    fft_result: impl IntoIterator<Item = &'a Complex32>,
+    sampling_rate: u32,
+    frequency_limit: FrequencyLimit,
+    config: &SpectrumConfig,
+) -> Result<(FrequencySpectrum, Option<FrequencyLimit>), SpectrumAnalyzerError> {
+    // The Nyquist frequency (index samples_len / 2) is optional, see SpectrumConfig.
+    let bin_count = samples_len / 2 + usize::from(config.include_nyquist);
+    frequency_limit
+        .verify_bin_count(bin_count)
+        .map_err(SpectrumAnalyzerError::InvalidFrequencyLimit)?;
 
     let frequency_resolution = fft_calc_frequency_resolution(sampling_rate, samples_len as u32);
 
+    // The frequency limit is either applied while collecting the bins (before
+    // scaling) or on the already scaled spectrum (after scaling).
+    let (pre_scaling_limit, post_scaling_limit) = match config.limit_stage {
+        LimitStage::BeforeScaling => (frequency_limit, None),
+        LimitStage::AfterScaling => (
+            FrequencyLimit::All,
+            Some(frequency_limit.to_frequency_range(frequency_resolution)),
+        ),
+    };
+    let maybe_min = pre_scaling_limit.maybe_min();
+    let maybe_max = pre_scaling_limit.maybe_max();
+    let (first_bin, end_bin) = pre_scaling_limit.maybe_bins().unwrap_or((0, bin_count));
+
     // collect frequency => frequency value in Vector of Pairs/Tuples
     let frequency_vec = fft_result
-        .iter()
+        .into_iter()
         // See https://stackoverflow.com/a/4371627/2891595 for more information as well as
         // https://www.gaussianwaves.com/2015/11/interpreting-fft-results-complex-dft-frequency-bins-and-fftshift/
         //
@@ -251,9 +698,12 @@ fn fft_result_to_spectrum(
         //
         // Indices (samples_len / 2)..len() are mirrored/negative. You can also see this here:
         // https://www.gaussianwaves.com/gaussianwaves/wp-content/uploads/2015/11/realDFT_complexDFT.png
-        .take(samples_len / 2 + 1)
+        .take(bin_count)
         // to (index, fft-result)-pairs
         .enumerate()
+        // optionally select bins by index, see FrequencyLimit::Bins
+        .skip(first_bin)
+        .take(end_bin - first_bin)
         // calc index => corresponding frequency
         .map(|(fft_index, fft_result)| {
             (
@@ -308,28 +758,72 @@ fn fft_result_to_spectrum(
         // #######################
         // FFT result is always complex: calc magnitude
         //   sqrt(re*re + im*im) (re: real part, im: imaginary part)
-        .map(|(fr, complex_res)| (fr, complex_to_magnitude(complex_res)))
+        // or the power without the sqrt
+        .map(|(fr, complex_res)| {
+            let val = match config.bin_value {
+                BinValue::Magnitude => complex_to_magnitude(complex_res),
+                BinValue::Power => complex_to_power(complex_res),
+            };
+            // optionally compensate the attenuation by the window
+            let val = config
+                .coherent_gain
+                .map_or(val, |gain| match config.bin_value {
+                    BinValue::Magnitude => val / gain,
+                    BinValue::Power => val / (gain * gain),
+                });
+            (fr, val)
+        })
+        // optionally drop bins below the minimum magnitude
+        .filter(|(_fr, val)| config.min_magnitude.map_or(true, |min| *val >= min))
+        // optionally clamp to the magnitude floor
+        .map(|(fr, val)| {
+            let val = config.magnitude_floor.map_or(val, |floor| val.max(floor));
+            (fr, val)
+        })
         // transform to my thin convenient orderable f32 wrappers
         .map(|(fr, val)| (Frequency::from(fr), FrequencyValue::from(val)))
         // collect all into an sorted vector (from lowest frequency to highest)
         .collect::<Vec<(Frequency, FrequencyValue)>>();
 
-    let mut working_buffer = vec![(0.0.into(), 0.0.into()); frequency_vec.len()];
+    if frequency_vec.len() < 2 {
+        return Err(if config.min_magnitude.is_some() {
+            SpectrumAnalyzerError::TooFewFrequencyBins
+        } else {
+            SpectrumAnalyzerError::FrequencyLimitExcludesAllBins
+        });
+    }
 
     // create spectrum object
-    let mut spectrum = FrequencySpectrum::new(
+    let spectrum = FrequencySpectrum::new(
         frequency_vec,
         frequency_resolution,
         samples_len as u32,
-        &mut working_buffer,
+        &mut [],
     );
+    Ok((spectrum, post_scaling_limit))
+}
 
-    // optionally scale
-    if let Some(scaling_fn) = scaling_fn {
-        spectrum.apply_scaling_fn(scaling_fn, &mut working_buffer)?
+/// Returns a new spectrum with the bins of `spectrum` within
+/// `frequency_limit`. See [`LimitStage::AfterScaling`].
+fn limit_spectrum(
+    spectrum: &FrequencySpectrum,
+    frequency_limit: FrequencyLimit,
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    let limited_data = spectrum
+        .data()
+        .iter()
+        .filter(|(fr, _fr_val)| frequency_limit.contains(fr.val()))
+        .copied()
+        .collect::<Vec<_>>();
+    if limited_data.len() < 2 {
+        return Err(SpectrumAnalyzerError::FrequencyLimitExcludesAllBins);
     }
-
-    Ok(spectrum)
+    Ok(FrequencySpectrum::new(
+        limited_data,
+        spectrum.frequency_resolution(),
+        spectrum.samples_len(),
+        &mut [],
+    ))
 }
 
 /// Calculate the frequency resolution of the FFT. It is determined by the sampling rate
@@ -361,8 +855,13 @@ fn fft_calc_frequency_resolution(sampling_rate: u32, samples_len: u32) -> f32 {
 /// * `val` A single value from the FFT output buffer of type [`Complex32`].
 fn complex_to_magnitude(val: &Complex32) -> f32 {
     // calculates sqrt(re*re + im*im), i.e. magnitude of complex number
-    let sum = val.re * val.re + val.im * val.im;
-    let sqrt = libm::sqrtf(sum);
+    let sqrt = libm::sqrtf(complex_to_power(val));
     debug_assert!(!sqrt.is_nan(), "sqrt is NaN!");
     sqrt
 }
+
+/// Maps a [`Complex32`] to it's power, i.e., the squared magnitude
+/// `re*re + im*im`. See [`complex_to_magnitude`].
+fn complex_to_power(val: &Complex32) -> f32 {
+    val.re * val.re + val.im * val.im
+}
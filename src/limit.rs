@@ -23,13 +23,16 @@ SOFTWARE.
 */
 //! Module for the struct [`FrequencyLimit`].
 
+use core::fmt::{Display, Formatter};
+
 /// Can be used to specify a desired frequency limit. If you know that you only
 /// need frequencies `f <= 1000Hz`, `1000 <= f <= 6777`, or `10000 <= f`, then this
 /// can help you to accelerate overall computation speed and memory usage.
 ///
 /// Please note that due to frequency inaccuracies the FFT result may not contain
 /// a value for `1000Hz` but for `998.76Hz`!
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FrequencyLimit {
     /// Interested in all frequencies. [0, sampling_rate/2] (Nyquist theorem).
     /// Semantically equivalent to "None" limit at all).
@@ -45,6 +48,14 @@ pub enum FrequencyLimit {
     /// equivalent to [`FrequencyLimit::Max`]. Furthermore, the first value must not be
     /// bigger than the second value.
     Range(f32, f32),
+    /// Only interested in the bins with index `start <= i < end`, e.g.,
+    /// `Bins(1, 129)` for the first 128 bins without the DC component. Bin
+    /// `i` corresponds to the frequency `i * sampling_rate / N`. In contrast
+    /// to [`FrequencyLimit::Range`], the bins are selected by their index
+    /// before their frequency is calculated. Hence, there is no
+    /// floating-point ambiguity at the boundaries. `end` must not be bigger
+    /// than the number of bins, i.e., usually `N / 2 + 1`.
+    Bins(usize, usize),
 }
 
 impl FrequencyLimit {
@@ -70,6 +81,32 @@ impl FrequencyLimit {
         }
     }
 
+    /// Returns the bin range `(start, end)` of [`Self::Bins`], if any.
+    #[inline]
+    #[must_use]
+    pub const fn maybe_bins(&self) -> Option<(usize, usize)> {
+        match self {
+            Self::Bins(start, end) => Some((*start, *end)),
+            _ => None,
+        }
+    }
+
+    /// Converts [`Self::Bins`] into the equivalent [`Self::Range`] in Hertz
+    /// by multiplying the first and the last bin index with
+    /// `frequency_resolution`, the same way as the frequency of each bin is
+    /// calculated. All other variants are returned unchanged.
+    #[inline]
+    #[must_use]
+    pub fn to_frequency_range(&self, frequency_resolution: f32) -> Self {
+        match self {
+            Self::Bins(start, end) => Self::Range(
+                *start as f32 * frequency_resolution,
+                end.saturating_sub(1) as f32 * frequency_resolution,
+            ),
+            _ => *self,
+        }
+    }
+
     /// Returns the minimum value, panics if it's none.
     /// Unwrapped version of [`Self::maybe_min`].
     #[inline]
@@ -86,6 +123,20 @@ impl FrequencyLimit {
         self.maybe_max().expect("Must contain a value!")
     }
 
+    /// Returns whether the given frequency is inside the limit. Both bounds
+    /// are inclusive.
+    ///
+    /// [`Self::Bins`] selects bins by index and doesn't know the frequency
+    /// resolution. Hence, it returns `true` for every frequency, as
+    /// [`Self::All`] does. To check a frequency against the selected bins,
+    /// use `limit.to_frequency_range(frequency_resolution).contains(frequency)`.
+    #[inline]
+    #[must_use]
+    pub fn contains(&self, frequency: f32) -> bool {
+        self.maybe_min().map_or(true, |min| frequency >= min)
+            && self.maybe_max().map_or(true, |max| frequency <= max)
+    }
+
     /// Verifies that the frequency limit has sane values and takes the maximum possible
     /// frequency into account.
     pub fn verify(&self, max_detectable_frequency: f32) -> Result<(), FrequencyLimitError> {
@@ -109,6 +160,24 @@ impl FrequencyLimit {
                     Ok(())
                 }
             }
+            Self::Bins(start, end) => {
+                if start >= end {
+                    Err(FrequencyLimitError::InvalidBinRange(*start, *end))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    /// Verifies that [`Self::Bins`] doesn't exceed the actual number of bins.
+    /// All other variants are always valid.
+    pub const fn verify_bin_count(&self, bin_count: usize) -> Result<(), FrequencyLimitError> {
+        match self {
+            Self::Bins(_start, end) if *end > bin_count => {
+                Err(FrequencyLimitError::BinAboveBinCount(*end, bin_count))
+            }
+            _ => Ok(()),
         }
     }
 }
@@ -124,6 +193,36 @@ pub enum FrequencyLimitError {
     /// Either the corresponding value is below or above the minimum/maximum or the
     /// first member of the tuple is bigger than the second.
     InvalidRange(f32, f32),
+    /// The first bin index of [`FrequencyLimit::Bins`] must be smaller than
+    /// the second one.
+    InvalidBinRange(usize, usize),
+    /// The end (first value) of [`FrequencyLimit::Bins`] is bigger than the
+    /// number of bins (second value).
+    BinAboveBinCount(usize, usize),
+}
+
+impl Display for FrequencyLimitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::ValueBelowMinimum(val) => {
+                write!(f, "the frequency {} is below 0 Hz", val)
+            }
+            Self::ValueAboveNyquist(val) => {
+                write!(f, "the frequency {} is above the Nyquist frequency", val)
+            }
+            Self::InvalidRange(min, max) => {
+                write!(f, "the frequency range [{}; {}] is invalid", min, max)
+            }
+            Self::InvalidBinRange(start, end) => write!(
+                f,
+                "the first bin {} is not smaller than the end {}",
+                start, end
+            ),
+            Self::BinAboveBinCount(end, bin_count) => {
+                write!(f, "the end bin {} exceeds the {} bins", end, bin_count)
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -165,6 +264,47 @@ mod tests {
         let _ = FrequencyLimit::Range(0.0, -1.0).verify(0.0).unwrap_err();
     }
 
+    #[test]
+    fn test_contains() {
+        assert!(FrequencyLimit::All.contains(0.0));
+        assert!(FrequencyLimit::Min(50.0).contains(50.0));
+        assert!(!FrequencyLimit::Min(50.0).contains(49.9));
+        assert!(FrequencyLimit::Max(50.0).contains(50.0));
+        assert!(!FrequencyLimit::Max(50.0).contains(50.1));
+        assert!(FrequencyLimit::Range(50.0, 70.0).contains(60.0));
+        assert!(!FrequencyLimit::Range(50.0, 70.0).contains(70.1));
+
+        // bins by index: only with the frequency resolution
+        let bins = FrequencyLimit::Bins(2, 5);
+        assert!(bins.contains(0.0) && bins.contains(1000.0));
+        let range = bins.to_frequency_range(10.0);
+        assert!(range.contains(20.0) && range.contains(40.0));
+        assert!(!range.contains(10.0) && !range.contains(50.0));
+    }
+
+    #[test]
+    fn test_bins() {
+        let _ = FrequencyLimit::Bins(2, 2).verify(100.0).unwrap_err();
+        let _ = FrequencyLimit::Bins(3, 2).verify(100.0).unwrap_err();
+        FrequencyLimit::Bins(1, 129).verify(100.0).unwrap();
+        FrequencyLimit::Bins(1, 129).verify_bin_count(129).unwrap();
+        let _ = FrequencyLimit::Bins(1, 129)
+            .verify_bin_count(128)
+            .unwrap_err();
+        FrequencyLimit::Range(0.0, 1e6).verify_bin_count(0).unwrap();
+
+        assert_eq!(
+            FrequencyLimit::Bins(1, 129).to_frequency_range(10.0),
+            FrequencyLimit::Range(10.0, 1280.0)
+        );
+        assert_eq!(
+            FrequencyLimit::Min(5.0).to_frequency_range(10.0),
+            FrequencyLimit::Min(5.0)
+        );
+        assert_eq!(FrequencyLimit::Bins(1, 129).maybe_bins(), Some((1, 129)));
+        assert_eq!(FrequencyLimit::All.maybe_bins(), None);
+    }
+
     #[test]
     fn test_ok() {
         FrequencyLimit::Min(50.0).verify(100.0).unwrap();
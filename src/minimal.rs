@@ -0,0 +1,138 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module for a minimal analysis path for embedded targets: from a buffer of
+//! samples to the dominant frequency with a bounded number of allocations.
+//! In contrast to [`crate::samples_fft_to_spectrum`], there is no
+//! [`crate::FrequencySpectrum`], no statistics, no scaling, and no frequency
+//! limit. The Hann window is applied while the samples are copied into the
+//! FFT buffer and the FFT is calculated in-place.
+//!
+//! [`dominant_frequency`] allocates exactly once (the FFT buffer).
+//! [`dominant_frequency_with_scratch`] doesn't allocate at all if you provide
+//! the buffer once, e.g., as `static` or on the stack:
+//!
+//! ```rust
+//! use spectrum_analyzer::minimal::dominant_frequency_with_scratch;
+//! let samples = [0.0; 1024];
+//! // one-time setup
+//! let mut scratch = [0.0; 1024];
+//! let frequency = dominant_frequency_with_scratch(&samples, 44100, &mut scratch).unwrap();
+//! assert_eq!(frequency, 0.0);
+//! ```
+
+use crate::error::SpectrumAnalyzerError;
+use crate::fft::FftImpl;
+use crate::{verify_samples, verify_sampling_rate};
+use alloc::vec;
+use core::f32::consts::PI;
+use libm::cosf;
+
+/// Returns the frequency of the frequency bin with the highest magnitude,
+/// excluding the DC component. Uses the Hann window. Allocates once. See
+/// [`dominant_frequency_with_scratch`] for an alternative without any
+/// allocation.
+///
+/// ## Parameters
+/// * `samples` See [`crate::samples_fft_to_spectrum`]. The window is applied
+///             internally.
+/// * `sampling_rate` See [`crate::samples_fft_to_spectrum`].
+///
+/// ## Return value
+/// See [`dominant_frequency_with_scratch`].
+///
+/// ## Errors
+/// The same input errors as for [`crate::samples_fft_to_spectrum`].
+#[inline]
+pub fn dominant_frequency(
+    samples: &[f32],
+    sampling_rate: u32,
+) -> Result<f32, SpectrumAnalyzerError> {
+    let mut scratch = vec![0.0; samples.len()];
+    dominant_frequency_with_scratch(samples, sampling_rate, &mut scratch)
+}
+
+/// Like [`dominant_frequency`] but uses `scratch` as FFT buffer instead of
+/// allocating one. This function doesn't allocate.
+///
+/// ## Parameters
+/// * `samples` See [`dominant_frequency`].
+/// * `sampling_rate` See [`dominant_frequency`].
+/// * `scratch` Buffer for the windowed samples and the FFT result. Must have
+///             at least `samples.len()` elements; only the first
+///             `samples.len()` elements are used. The content is
+///             overwritten.
+///
+/// ## Return value
+/// Frequency in Hertz of the bin with the highest magnitude, i.e., a multiple
+/// of the frequency resolution `sampling_rate / samples.len()`. Hence, the
+/// error is up to half the frequency resolution. If all bins except the DC
+/// component are zero, e.g., for silence, this is `0.0`.
+///
+/// ## Errors
+/// [`SpectrumAnalyzerError::ScratchBufferTooSmall`] if `scratch` is too
+/// small, and the same input errors as for
+/// [`crate::samples_fft_to_spectrum`] otherwise.
+#[inline]
+pub fn dominant_frequency_with_scratch(
+    samples: &[f32],
+    sampling_rate: u32,
+    scratch: &mut [f32],
+) -> Result<f32, SpectrumAnalyzerError> {
+    verify_sampling_rate(sampling_rate)?;
+    verify_samples(samples)?;
+    if scratch.len() < samples.len() {
+        return Err(SpectrumAnalyzerError::ScratchBufferTooSmall(
+            scratch.len(),
+            samples.len(),
+        ));
+    }
+
+    // Hann window, fused with the copy into the FFT buffer
+    let buffer = &mut scratch[..samples.len()];
+    let samples_len_f32 = samples.len() as f32;
+    for (i, (dst, sample)) in buffer.iter_mut().zip(samples).enumerate() {
+        let multiplier = 0.5 * (1.0 - cosf(2.0 * PI * i as f32 / samples_len_f32));
+        *dst = multiplier * sample;
+    }
+
+    let fft_res = FftImpl::calc_in_place(buffer);
+    // the real value of the Nyquist frequency is packed into the DC component
+    let nyquist_index = fft_res.len();
+    let nyquist_power = fft_res[0].im * fft_res[0].im;
+
+    let (mut max_index, mut max_power) = (0, 0.0);
+    for (i, bin) in fft_res.iter().enumerate().skip(1) {
+        let power = bin.norm_sqr();
+        if power > max_power {
+            max_index = i;
+            max_power = power;
+        }
+    }
+    if nyquist_power > max_power {
+        max_index = nyquist_index;
+    }
+
+    Ok(max_index as f32 * sampling_rate as f32 / samples_len_f32)
+}
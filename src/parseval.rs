@@ -0,0 +1,153 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for checking the energy conservation between the time domain and
+//! the frequency domain ([Parseval's theorem](https://en.wikipedia.org/wiki/Parseval%27s_theorem)).
+//! See [`verify_parseval`].
+
+use crate::windows::WindowType;
+use crate::FrequencySpectrum;
+
+/// Calculates the relative error between the energy of the samples in the
+/// time domain and the energy of the spectrum in the frequency domain. This
+/// is useful to check that a custom processing pipeline preserves physical
+/// units.
+///
+/// The energy in the time domain is `sum((w[n] * x[n])^2)`, i.e., the energy
+/// of the samples after the window `w` was applied, as the window reduces
+/// the energy by its power gain. The energy in the frequency domain is `sum(|X[k]|^2) / N` over all `N` bins of the FFT.
+/// As the spectrum is single-sided, i.e., only contains the bins from `0 Hz`
+/// to the Nyquist frequency, all bins except the DC component and the
+/// Nyquist bin are counted twice.
+///
+/// The error is small (`< 1e-3`) only for a spectrum that was created from
+/// the same samples by [`crate::samples_fft_to_spectrum`] with
+/// [`crate::FrequencyLimit::All`] and without a scaling function. It breaks
+/// if
+/// - the samples given to this function or the window differ from the
+///   analyzed samples, e.g., if the samples were windowed before the FFT but
+///   [`WindowType::Rectangular`] is passed.
+/// - a frequency limit removed some bins.
+/// - a scaling function was applied, e.g., a logarithmic (dB) scaling or
+///   [`crate::scaling::divide_by_N`].
+///
+/// ## Parameters
+/// * `samples` Samples that were analyzed, without the window.
+/// * `spectrum` Spectrum of the windowed samples.
+/// * `window` Window that was applied to the samples before the FFT.
+///            [`WindowType::Rectangular`] if none was applied.
+///
+/// ## Return value
+/// Relative error `|E_time - E_freq| / E_time`. If both energies are zero,
+/// the error is zero.
+#[inline]
+#[must_use]
+pub fn verify_parseval(samples: &[f32], spectrum: &FrequencySpectrum, window: WindowType) -> f32 {
+    let time_energy = window.apply(samples).iter().map(|x| x * x).sum::<f32>();
+
+    let samples_len = spectrum.samples_len() as f32;
+    let nyquist_frequency = spectrum.frequency_resolution() * samples_len / 2.0;
+    let frequency_energy = spectrum
+        .data()
+        .iter()
+        .map(|(fr, val)| {
+            let energy = val.val() * val.val();
+            // bins between DC and Nyquist represent the negative frequencies too
+            if fr.val() == 0.0 || fr.val() == nyquist_frequency {
+                energy
+            } else {
+                2.0 * energy
+            }
+        })
+        .sum::<f32>()
+        / samples_len;
+
+    if time_energy == 0.0 && frequency_energy == 0.0 {
+        0.0
+    } else {
+        (time_energy - frequency_energy).abs() / time_energy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scaling::{divide_by_N, scale_20_times_log10};
+    use crate::windows::hann_window;
+    use crate::{samples_fft_to_spectrum, FrequencyLimit};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_verify_parseval() {
+        // some arbitrary signal
+        let samples = (0..1024)
+            .map(|i| i as f32)
+            .map(|i| libm::sinf(i * 0.1) + 0.3 * libm::cosf(i * 1.3) + 0.1)
+            .collect::<Vec<_>>();
+        let spectrum = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+        assert!(verify_parseval(&samples, &spectrum, WindowType::Rectangular) < 1e-3);
+
+        // with window: the power gain of the window must be accounted for
+        let windowed_samples = hann_window(&samples);
+        let spectrum =
+            samples_fft_to_spectrum(&windowed_samples, 44100, FrequencyLimit::All, None).unwrap();
+        assert!(verify_parseval(&samples, &spectrum, WindowType::Hann) < 1e-3);
+        assert!(verify_parseval(&windowed_samples, &spectrum, WindowType::Rectangular) < 1e-3);
+        assert!(verify_parseval(&samples, &spectrum, WindowType::Rectangular) > 0.1);
+        // the power gains of the Hann (0.375) and the Hamming window (0.3974)
+        // differ by about 6 %
+        float_cmp::assert_approx_eq!(
+            f32,
+            verify_parseval(&samples, &spectrum, WindowType::Hamming),
+            1.0 - 0.375 / 0.3974,
+            epsilon = 0.005
+        );
+        for window in [
+            WindowType::Hamming,
+            WindowType::BlackmanHarris4Term,
+            WindowType::BlackmanHarris7Term,
+        ] {
+            let spectrum =
+                samples_fft_to_spectrum(&window.apply(&samples), 44100, FrequencyLimit::All, None)
+                    .unwrap();
+            assert!(verify_parseval(&samples, &spectrum, window) < 1e-3);
+            assert!(verify_parseval(&samples, &spectrum, WindowType::Hann) > 0.01);
+        }
+
+        // scaling breaks it
+        for scaling_fn in [&divide_by_N as _, &scale_20_times_log10 as _] {
+            let spectrum =
+                samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, Some(scaling_fn))
+                    .unwrap();
+            assert!(verify_parseval(&samples, &spectrum, WindowType::Rectangular) > 0.1);
+        }
+
+        // silence
+        let spectrum =
+            samples_fft_to_spectrum(&[0.0; 16], 44100, FrequencyLimit::All, None).unwrap();
+        assert_eq!(
+            verify_parseval(&[0.0; 16], &spectrum, WindowType::Rectangular),
+            0.0
+        );
+    }
+}
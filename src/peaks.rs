@@ -0,0 +1,772 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for peak detection in a [`FrequencySpectrum`]. See
+//! [`FrequencySpectrum::peaks`].
+
+use crate::windows::WindowType;
+use crate::{Frequency, FrequencySpectrum, FrequencyValue};
+use alloc::vec::Vec;
+
+/// Maximum deviation of the ratio of a neighbour bin to the peak bin from the
+/// ratio predicted by the main lobe of the window. See
+/// [`FrequencySpectrum::classify_peak`].
+const MAIN_LOBE_RATIO_TOLERANCE: f32 = 0.1;
+
+/// Result of [`FrequencySpectrum::classify_peak`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PeakClass {
+    /// The bin and its neighbours have the shape of the main lobe of the
+    /// window, i.e., the peak is consistent with a single sinusoid.
+    Tone,
+    /// The bin is no local maximum or its neighbours don't fit the main lobe
+    /// of the window. Usually, this is a sidelobe caused by leakage of a
+    /// stronger tone or a noise bump.
+    Sidelobe,
+}
+
+/// Describes when a local maximum of the spectrum qualifies as a peak.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Threshold {
+    /// The value of the peak must be bigger than the given value. This works
+    /// well if the noise floor is flat.
+    Absolute(f32),
+    /// The value of the peak must be bigger than `factor` times the median of
+    /// the values of the surrounding bins. The median is a robust estimate of
+    /// the local noise floor. Hence, this works also if the noise floor slopes,
+    /// e.g., for pink noise.
+    RelativeToLocalMedian {
+        /// Number of bins on each side of the peak that are used to estimate
+        /// the local noise floor.
+        window_bins: usize,
+        /// Factor by which the peak must exceed the local median. If the
+        /// spectrum is scaled logarithmically (dB), use an
+        /// [`Threshold::Absolute`] threshold on a spectrum where the noise
+        /// floor was subtracted instead.
+        factor: f32,
+    },
+}
+
+impl Default for Threshold {
+    fn default() -> Self {
+        Self::Absolute(0.0)
+    }
+}
+
+/// Configuration for [`FrequencySpectrum::peaks`].
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct PeakConfig {
+    /// Threshold that each peak must exceed.
+    pub mode: Threshold,
+}
+
+impl FrequencySpectrum {
+    /// Returns all peaks of the spectrum, sorted by frequency. A peak is a
+    /// local maximum, i.e., a bin whose value is bigger than the value of
+    /// its left neighbour and not smaller than the value of its right
+    /// neighbour, that exceeds the threshold of `config`. The first and the
+    /// last bin are never peaks.
+    ///
+    /// ## Parameters
+    /// * `config` See [`PeakConfig`].
+    #[inline]
+    #[must_use]
+    pub fn peaks(&self, config: &PeakConfig) -> Vec<(Frequency, FrequencyValue)> {
+        let data = self.data();
+        let mut neighbourhood = Vec::new();
+        self.local_maxima()
+            .filter(|&i| match config.mode {
+                Threshold::Absolute(threshold) => data[i].1.val() > threshold,
+                Threshold::RelativeToLocalMedian {
+                    window_bins,
+                    factor,
+                } => {
+                    let begin = i.saturating_sub(window_bins);
+                    let end = (i + window_bins + 1).min(data.len());
+                    neighbourhood.clear();
+                    neighbourhood.extend(data[begin..end].iter().map(|(_fr, val)| *val));
+                    neighbourhood.sort_unstable();
+                    let median = neighbourhood[neighbourhood.len() / 2].val();
+                    data[i].1.val() > factor * median
+                }
+            })
+            .map(|i| data[i])
+            .collect()
+    }
+
+    /// Like [`Self::peaks`] but with a frequency-dependent threshold. This is
+    /// useful if the noise floor of the spectrum is known and not flat, e.g.,
+    /// for pink noise. [`Threshold::Absolute`] is the special case of a
+    /// constant function.
+    ///
+    /// ## Parameters
+    /// * `threshold_fn` Maps the frequency of a peak in Hertz to the value
+    ///                  that the peak must exceed.
+    #[inline]
+    #[must_use]
+    pub fn peaks_above<F: Fn(f32) -> f32>(
+        &self,
+        threshold_fn: F,
+    ) -> Vec<(Frequency, FrequencyValue)> {
+        let data = self.data();
+        self.local_maxima()
+            .map(|i| data[i])
+            .filter(|(fr, val)| val.val() > threshold_fn(fr.val()))
+            .collect()
+    }
+
+    /// Returns the `K` strongest peaks, i.e., local maxima as in
+    /// [`Self::peaks`], without a heap allocation, e.g., for microcontrollers.
+    ///
+    /// ## Return value
+    /// The peaks sorted by value in descending order. If there are fewer than
+    /// `K` peaks, the remaining entries are `(0.0, 0.0)`.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+    /// let samples = [0.0, 1.1, 5.5, -5.5, 0.0, 1.1, 5.5, -5.5];
+    /// let spectrum = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+    /// let top = spectrum.top_peaks_into::<2>();
+    /// assert!(top[0].1 >= top[1].1);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn top_peaks_into<const K: usize>(&self) -> [(Frequency, FrequencyValue); K] {
+        let data = self.data();
+        let mut top = [(Frequency::from(0.0), FrequencyValue::from(0.0)); K];
+        let mut len = 0;
+        for i in self.local_maxima() {
+            let peak = data[i];
+            // insertion sort; earlier peaks win on equal values
+            let pos = top[..len].partition_point(|(_fr, val)| *val >= peak.1);
+            if pos == K {
+                continue;
+            }
+            if len < K {
+                len += 1;
+            }
+            top.copy_within(pos..len - 1, pos + 1);
+            top[pos] = peak;
+        }
+        top
+    }
+
+    /// Returns the -3 dB (half-power) bandwidth around a peak, e.g., to
+    /// characterize a resonance. The edges are the frequencies below and
+    /// above the peak where the magnitude falls below `peak / sqrt(2)` for
+    /// the first time. They are linearly interpolated between the two bins
+    /// around the crossing. If the magnitude doesn't fall below this level
+    /// until the border of the spectrum, the edge is the lowest or highest
+    /// frequency of the spectrum.
+    ///
+    /// The values of the spectrum must be linear magnitudes, i.e., no
+    /// logarithmic (dB) scaling function must be applied.
+    ///
+    /// ## Parameters
+    /// * `peak_freq` Frequency of the peak in Hertz, e.g., from
+    ///               [`Self::peaks`]. The closest bin is used as peak.
+    ///
+    /// ## Return value
+    /// `(lower edge, upper edge, bandwidth)` in Hertz.
+    ///
+    /// ## Panics
+    /// If the spectrum is empty. See [`Self::is_empty`].
+    #[inline]
+    #[must_use]
+    pub fn peak_bandwidth_3db(&self, peak_freq: f32) -> (f32, f32, f32) {
+        let data = self.data();
+        let last_index = data.len() - 1;
+        let peak_index = self.closest_index(peak_freq);
+        let level = data[peak_index].1.val() / core::f32::consts::SQRT_2;
+
+        // interpolates the crossing of `level` between bin `outer` (below the
+        // level) and bin `inner` (above the level)
+        let crossing = |outer: usize, inner: usize| {
+            let (outer_fr, outer_val) = (data[outer].0.val(), data[outer].1.val());
+            let (inner_fr, inner_val) = (data[inner].0.val(), data[inner].1.val());
+            outer_fr + (level - outer_val) / (inner_val - outer_val) * (inner_fr - outer_fr)
+        };
+
+        let lower_edge = (0..peak_index)
+            .rev()
+            .find(|&i| data[i].1.val() < level)
+            .map_or_else(|| data[0].0.val(), |i| crossing(i, i + 1));
+        let upper_edge = (peak_index + 1..=last_index)
+            .find(|&i| data[i].1.val() < level)
+            .map_or_else(|| data[last_index].0.val(), |i| crossing(i, i - 1));
+
+        (lower_edge, upper_edge, upper_edge - lower_edge)
+    }
+
+    /// Returns the Q factor of a resonant peak, i.e.,
+    /// `peak_freq / bandwidth_3db`. The higher the Q factor, the sharper the
+    /// resonance. See [`Self::peak_bandwidth_3db`] for the requirements on
+    /// the spectrum.
+    ///
+    /// ## Parameters
+    /// * `peak_freq` Frequency of the peak in Hertz, e.g., from
+    ///               [`Self::peaks`].
+    ///
+    /// ## Panics
+    /// If the spectrum is empty. See [`Self::is_empty`].
+    #[inline]
+    #[must_use]
+    pub fn peak_q_factor(&self, peak_freq: f32) -> f32 {
+        let (_lower_edge, _upper_edge, bandwidth) = self.peak_bandwidth_3db(peak_freq);
+        peak_freq / bandwidth
+    }
+
+    /// Returns the magnitude of a sinusoid corrected for the leakage into the
+    /// neighbouring bins. If the frequency of the sinusoid is between two
+    /// bins, its energy is spread over the main lobe of the window and the
+    /// peak bin underestimates the amplitude by up to 36% (rectangular
+    /// window) or 15% (Hann window). This sums the energy of all bins of the
+    /// main lobe around the peak and divides it by the equivalent noise
+    /// bandwidth of the window, which is the energy of the main lobe of a
+    /// sinusoid exactly on a bin relative to its peak bin.
+    ///
+    /// The values of the spectrum must be linear magnitudes, i.e., no
+    /// logarithmic (dB) scaling function must be applied. Close sinusoids
+    /// whose main lobes overlap distort the result.
+    ///
+    /// ## Parameters
+    /// * `peak_freq` Frequency of the peak in Hertz, e.g., from
+    ///               [`Self::peaks`]. The closest bin is used as peak.
+    /// * `window` The window that was applied to the samples. See
+    ///            [`WindowType::main_lobe_half_width`] and
+    ///            [`WindowType::enbw`].
+    ///
+    /// ## Return value
+    /// The magnitude of the peak bin if the sinusoid were exactly on a bin.
+    /// Unlike the value of the peak bin, this hardly depends on the position
+    /// of the sinusoid between two bins. With the Hann window, the error is
+    /// below 1%. For the rectangular window, the side lobes carry a
+    /// significant part of the energy; the result is up to 10% too low.
+    ///
+    /// ## Panics
+    /// If the spectrum is empty. See [`Self::is_empty`].
+    #[inline]
+    #[must_use]
+    pub fn corrected_peak_amplitude(&self, peak_freq: f32, window: WindowType) -> f32 {
+        let data = self.data();
+        let peak_index = self.closest_index(peak_freq);
+        let half_width = window.main_lobe_half_width();
+        let begin = peak_index.saturating_sub(half_width);
+        let end = (peak_index + half_width).min(data.len() - 1);
+        let energy = data[begin..=end]
+            .iter()
+            .map(|(_fr, fr_val)| fr_val.val() * fr_val.val())
+            .sum::<f32>();
+        libm::sqrtf(energy / window.enbw())
+    }
+
+    /// Classifies whether the bin at `fr` is the main lobe of a single
+    /// sinusoid or rather a leakage sidelobe or noise. The position of the
+    /// sinusoid between two bins is estimated from the ratio of the bigger
+    /// neighbour bin to the peak bin, using the main lobe of the window
+    /// transform. The bin is a [`PeakClass::Tone`] if the ratio of the
+    /// smaller neighbour bin matches the ratio predicted for this position.
+    ///
+    /// The values of the spectrum must be linear magnitudes, i.e., no
+    /// logarithmic (dB) scaling function must be applied. At the first and
+    /// the last bin, the missing neighbour is assumed to equal the other
+    /// neighbour, as at `0 Hz` and at the Nyquist frequency.
+    ///
+    /// ## Parameters
+    /// * `fr` Frequency of the peak, e.g., from [`Self::peaks`]. The closest
+    ///        bin is used.
+    /// * `window` The window that was applied to the samples.
+    ///
+    /// ## Return value
+    /// [`PeakClass::Tone`] if the bin is a local maximum with the shape of
+    /// the main lobe of `window` and [`PeakClass::Sidelobe`] otherwise.
+    #[inline]
+    #[must_use]
+    pub fn classify_peak(&self, fr: Frequency, window: WindowType) -> PeakClass {
+        let data = self.data();
+        if data.len() < 2 {
+            return PeakClass::Sidelobe;
+        }
+        let index = self.closest_index(fr.val());
+        let peak = data[index].1.val();
+        let left = data[index.checked_sub(1).unwrap_or(1)].1.val();
+        let right = data[if index + 1 < data.len() {
+            index + 1
+        } else {
+            index - 1
+        }]
+        .1
+        .val();
+        if !(peak > left && peak >= right) {
+            return PeakClass::Sidelobe;
+        }
+
+        let kernel = |offset_bins: f32| window.kernel(offset_bins);
+        // ratio of the bigger neighbour to the peak bin if the sinusoid is
+        // `delta` bins away from the peak bin towards the bigger neighbour;
+        // increases from `kernel(1) / kernel(0)` (on bin) to `1.0` (between
+        // two bins)
+        let bigger_ratio = |delta: f32| kernel(1.0 - delta) / kernel(delta);
+        let (bigger, smaller) = if left > right {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        let ratio = bigger / peak;
+        if ratio < bigger_ratio(0.0) - MAIN_LOBE_RATIO_TOLERANCE {
+            // narrower than the main lobe
+            return PeakClass::Sidelobe;
+        }
+
+        // bisection for the position of the sinusoid
+        let (mut low, mut high) = (0.0, 0.5);
+        for _ in 0..20 {
+            let delta = (low + high) / 2.0;
+            if bigger_ratio(delta) < ratio {
+                low = delta;
+            } else {
+                high = delta;
+            }
+        }
+        let delta = (low + high) / 2.0;
+        let expected_smaller_ratio = kernel(1.0 + delta) / kernel(delta);
+        if (smaller / peak - expected_smaller_ratio).abs() <= MAIN_LOBE_RATIO_TOLERANCE {
+            PeakClass::Tone
+        } else {
+            PeakClass::Sidelobe
+        }
+    }
+
+    /// Checks which harmonics of `fundamental` are present, e.g., to
+    /// characterize the timbre of an instrument. Harmonic `k` is present if
+    /// there is a peak, i.e., a local maximum as in [`Self::peaks`], within
+    /// `tolerance` of `k * fundamental` that exceeds the median of the
+    /// spectrum, a rough estimate of the noise floor. If no bin is within the
+    /// tolerance, the closest bin is checked.
+    ///
+    /// ## Parameters
+    /// * `fundamental` Frequency of the fundamental in Hertz.
+    /// * `num_harmonics` Number of checked harmonics, including the
+    ///                   fundamental.
+    /// * `tolerance` Maximum deviation of a harmonic from `k * fundamental`
+    ///               in Hertz, e.g., for slightly inharmonic instruments.
+    ///
+    /// ## Return value
+    /// One entry per harmonic, starting with the fundamental (`k = 1`): the
+    /// value of its peak or `None`, if it is missing. Harmonics above the
+    /// spectrum are missing.
+    #[inline]
+    #[must_use]
+    pub fn harmonic_analysis(
+        &self,
+        fundamental: f32,
+        num_harmonics: usize,
+        tolerance: f32,
+    ) -> Vec<Option<FrequencyValue>> {
+        let data = self.data();
+        let is_peak = |i: usize| {
+            i > 0
+                && i + 1 < data.len()
+                && data[i].1 > data[i - 1].1
+                && data[i].1 >= data[i + 1].1
+                && data[i].1 > self.median()
+        };
+        (1..=num_harmonics)
+            .map(|k| {
+                let harmonic = k as f32 * fundamental;
+                if data.is_empty() || harmonic - tolerance > self.max_fr().val() {
+                    return None;
+                }
+                let begin = data.partition_point(|(fr, _)| fr.val() < harmonic - tolerance);
+                let end = data.partition_point(|(fr, _)| fr.val() <= harmonic + tolerance);
+                let strongest = if begin < end {
+                    (begin..end).max_by_key(|&i| data[i].1)?
+                } else {
+                    // no bin within the tolerance: the closest bin
+                    let (closest_fr, _) = self.freq_val_closest(harmonic);
+                    data.partition_point(|(fr, _)| *fr < closest_fr)
+                };
+                is_peak(strongest).then(|| data[strongest].1)
+            })
+            .collect()
+    }
+
+    /// Returns the index of the bin closest to `frequency`. This doesn't
+    /// assume equidistant bins, hence, it also works if the frequency axis
+    /// has gaps, e.g., after [`Self::with_excluded_band`].
+    fn closest_index(&self, frequency: f32) -> usize {
+        let data = self.data();
+        // index of the first bin with a frequency >= `frequency`
+        let upper_index = data.partition_point(|(fr, _)| fr.val() < frequency);
+        if upper_index == 0 {
+            0
+        } else if upper_index == data.len() {
+            data.len() - 1
+        } else if frequency - data[upper_index - 1].0.val() <= data[upper_index].0.val() - frequency
+        {
+            upper_index - 1
+        } else {
+            upper_index
+        }
+    }
+
+    /// Returns the indices of all local maxima. See [`Self::peaks`].
+    fn local_maxima(&self) -> impl Iterator<Item = usize> + '_ {
+        let data = self.data();
+        (1..data.len().saturating_sub(1))
+            .filter(move |&i| data[i].1 > data[i - 1].1 && data[i].1 >= data[i + 1].1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::spectrum_from_pairs;
+    use crate::windows::hann_window;
+    use crate::{samples_fft_to_spectrum, FrequencyLimit};
+    use core::f32::consts::PI;
+
+    /// Pink-noise like spectrum (1/f) with some ripple and a small tone at
+    /// 3000 Hz.
+    fn sloped_spectrum_with_tone() -> FrequencySpectrum {
+        let data = (1..=500).map(|i| {
+            let fr = i as f32 * 10.0;
+            let val = if i == 300 {
+                1.0
+            } else {
+                1000.0 / fr * (1.0 + 0.1 * libm::sinf(i as f32 * 2.3))
+            };
+            (fr, val)
+        });
+        spectrum_from_pairs(data, 10.0, 1000)
+    }
+
+    /// Magnitude response of a resonance at `f0` with a -3 dB bandwidth of
+    /// `bandwidth` (Lorentzian).
+    fn resonance(f0: f32, bandwidth: f32) -> FrequencySpectrum {
+        let data = (0..=2000).map(|i| {
+            let fr = i as f32;
+            let detuning = (fr - f0) / (bandwidth / 2.0);
+            (fr, 1.0 / libm::sqrtf(1.0 + detuning * detuning))
+        });
+        spectrum_from_pairs(data, 1.0, 4000)
+    }
+
+    #[test]
+    fn test_peak_bandwidth_3db() {
+        let spectrum = resonance(1000.0, 100.0);
+        let (lower_edge, upper_edge, bandwidth) = spectrum.peak_bandwidth_3db(1000.0);
+        float_cmp::assert_approx_eq!(f32, lower_edge, 950.0, epsilon = 0.1);
+        float_cmp::assert_approx_eq!(f32, upper_edge, 1050.0, epsilon = 0.1);
+        float_cmp::assert_approx_eq!(f32, bandwidth, 100.0, epsilon = 0.2);
+
+        // the closest bin is used as peak
+        let (_, _, bandwidth) = spectrum.peak_bandwidth_3db(1000.4);
+        float_cmp::assert_approx_eq!(f32, bandwidth, 100.0, epsilon = 0.2);
+
+        // edges between two bins are interpolated
+        let (lower_edge, upper_edge, _) = resonance(1000.0, 15.0).peak_bandwidth_3db(1000.0);
+        float_cmp::assert_approx_eq!(f32, lower_edge, 992.5, epsilon = 0.3);
+        float_cmp::assert_approx_eq!(f32, upper_edge, 1007.5, epsilon = 0.3);
+
+        // the magnitude doesn't fall below -3 dB until the border
+        let (lower_edge, upper_edge, _) = resonance(10.0, 100.0).peak_bandwidth_3db(10.0);
+        assert_eq!(lower_edge, 0.0);
+        float_cmp::assert_approx_eq!(f32, upper_edge, 60.0, epsilon = 0.1);
+
+        // a gap in the frequency axis below the peak doesn't shift the peak
+        let len = 4096;
+        let samples = (0..len)
+            .map(|i| libm::sinf(2.0 * PI * 1000.0 * i as f32 / 44100.0))
+            .collect::<Vec<_>>();
+        let spectrum = samples_fft_to_spectrum(
+            &WindowType::Hann.apply(&samples),
+            44100,
+            FrequencyLimit::All,
+            None,
+        )
+        .unwrap();
+        let (peak_fr, _peak_val) = spectrum.max();
+        let expected = spectrum.peak_bandwidth_3db(peak_fr.val());
+        let (lower_edge, upper_edge, bandwidth) = spectrum
+            .with_excluded_band(100.0, 400.0)
+            .peak_bandwidth_3db(peak_fr.val());
+        assert!(lower_edge < 1000.0 && upper_edge > 1000.0);
+        float_cmp::assert_approx_eq!(f32, lower_edge, expected.0, ulps = 2);
+        float_cmp::assert_approx_eq!(f32, upper_edge, expected.1, ulps = 2);
+        float_cmp::assert_approx_eq!(f32, bandwidth, expected.2, ulps = 2);
+    }
+
+    #[test]
+    fn test_peak_q_factor() {
+        float_cmp::assert_approx_eq!(
+            f32,
+            resonance(1000.0, 100.0).peak_q_factor(1000.0),
+            10.0,
+            epsilon = 0.05
+        );
+        float_cmp::assert_approx_eq!(
+            f32,
+            resonance(1500.0, 30.0).peak_q_factor(1500.0),
+            50.0,
+            epsilon = 0.5
+        );
+    }
+
+    #[test]
+    fn test_peaks_relative_to_local_median() {
+        let spectrum = sloped_spectrum_with_tone();
+        let peaks = spectrum.peaks(&PeakConfig {
+            mode: Threshold::RelativeToLocalMedian {
+                window_bins: 10,
+                factor: 2.0,
+            },
+        });
+        assert_eq!(peaks.len(), 1);
+        assert_eq!(peaks[0].0.val(), 3000.0);
+    }
+
+    #[test]
+    fn test_peaks_absolute() {
+        let spectrum = sloped_spectrum_with_tone();
+        // tuned for the flat region around the tone: finds the ripple on the
+        // slope as well
+        let peaks = spectrum.peaks(&PeakConfig {
+            mode: Threshold::Absolute(0.5),
+        });
+        assert!(peaks.len() > 1);
+        assert!(peaks.iter().any(|(fr, _)| fr.val() < 1000.0));
+        // high enough to ignore the ripple on the slope: misses the tone
+        let peaks = spectrum.peaks(&PeakConfig {
+            mode: Threshold::Absolute(13.0),
+        });
+        assert!(peaks.is_empty());
+    }
+
+    #[test]
+    fn test_peaks_above() {
+        let spectrum = sloped_spectrum_with_tone();
+        // follows the noise floor
+        let peaks = spectrum.peaks_above(|fr| 2.0 * 1000.0 / fr);
+        assert_eq!(peaks.len(), 1);
+        assert_eq!(peaks[0].0.val(), 3000.0);
+        // constant threshold
+        assert_eq!(
+            spectrum.peaks_above(|_fr| 0.5),
+            spectrum.peaks(&PeakConfig {
+                mode: Threshold::Absolute(0.5),
+            })
+        );
+    }
+
+    #[test]
+    fn test_top_peaks_into() {
+        let spectrum = sloped_spectrum_with_tone();
+        let mut expected = spectrum.peaks(&PeakConfig {
+            mode: Threshold::Absolute(f32::NEG_INFINITY),
+        });
+        expected.sort_by(|(_, val_a), (_, val_b)| val_b.cmp(val_a));
+        assert!(expected.len() > 3);
+        assert_eq!(spectrum.top_peaks_into::<3>()[..], expected[..3]);
+        assert_eq!(spectrum.top_peaks_into::<0>(), []);
+
+        // padded with zero-valued entries
+        let single_peak = resonance(1000.0, 100.0);
+        let top = single_peak.top_peaks_into::<3>();
+        assert_eq!(top[0].0.val(), 1000.0);
+        assert_eq!(top[0].1.val(), 1.0);
+        for (fr, val) in &top[1..] {
+            assert_eq!((fr.val(), val.val()), (0.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn test_harmonic_analysis() {
+        let sampling_rate = 8000.0;
+        // 200 Hz with the harmonics 2 and 4; 3 and 5 are missing
+        let samples = (0..4096)
+            .map(|i| {
+                let t = i as f32 / sampling_rate;
+                [(200.0, 1.0), (400.0, 0.5), (800.0, 0.25)]
+                    .iter()
+                    .map(|(fr, amplitude)| amplitude * libm::sinf(2.0 * PI * fr * t))
+                    .sum::<f32>()
+            })
+            .collect::<Vec<_>>();
+        let spectrum = samples_fft_to_spectrum(
+            &hann_window(&samples),
+            sampling_rate as u32,
+            FrequencyLimit::All,
+            None,
+        )
+        .unwrap();
+
+        let harmonics = spectrum.harmonic_analysis(200.0, 6, 5.0);
+        assert_eq!(harmonics.len(), 6);
+        let present = harmonics.iter().map(Option::is_some).collect::<Vec<_>>();
+        assert_eq!(present, [true, true, false, true, false, false]);
+        let fundamental = harmonics[0].unwrap().val();
+        float_cmp::assert_approx_eq!(
+            f32,
+            harmonics[1].unwrap().val() / fundamental,
+            0.5,
+            epsilon = 0.05
+        );
+        float_cmp::assert_approx_eq!(
+            f32,
+            harmonics[3].unwrap().val() / fundamental,
+            0.25,
+            epsilon = 0.05
+        );
+
+        // slightly inharmonic: outside of a small tolerance
+        let harmonics = spectrum.harmonic_analysis(195.0, 2, 1.0);
+        assert_eq!(harmonics, [None, None]);
+        // above the Nyquist frequency
+        assert_eq!(spectrum.harmonic_analysis(3000.0, 2, 5.0)[1], None);
+    }
+
+    #[test]
+    fn test_corrected_peak_amplitude() {
+        let len = 1024;
+        // magnitudes of the peak bin and the corrected amplitude of a sine
+        // with amplitude 1.0 at `bin`, which might be between two bins
+        let analyze = |bin: f32, window: WindowType| {
+            let samples = (0..len)
+                .map(|i| libm::sinf(2.0 * PI * bin * i as f32 / len as f32))
+                .collect::<Vec<_>>();
+            let spectrum =
+                samples_fft_to_spectrum(&window.apply(&samples), len, FrequencyLimit::All, None)
+                    .unwrap();
+            let (peak_fr, peak_val) = spectrum.max();
+            (
+                peak_val.val(),
+                spectrum.corrected_peak_amplitude(peak_fr.val(), window),
+            )
+        };
+
+        for window in [WindowType::Hann, WindowType::Rectangular] {
+            // on a bin, the correction changes almost nothing
+            let (on_bin_peak, on_bin_corrected) = analyze(100.0, window);
+            let expected = len as f32 / 2.0 * window.coherent_gain();
+            float_cmp::assert_approx_eq!(f32, on_bin_peak, expected, epsilon = expected * 0.01);
+
+            // between two bins, the peak bin is much too low, in contrast to
+            // the corrected amplitude
+            let (off_bin_peak, off_bin_corrected) = analyze(100.5, window);
+            assert!(off_bin_peak < 0.9 * on_bin_peak, "{:?}", window);
+            let tolerance = if window == WindowType::Hann {
+                0.01
+            } else {
+                0.1
+            };
+            assert!(
+                (off_bin_corrected - on_bin_peak).abs() < tolerance * on_bin_peak,
+                "{:?}: {} vs {}",
+                window,
+                off_bin_corrected,
+                on_bin_peak
+            );
+            assert!((on_bin_corrected - on_bin_peak).abs() < tolerance * on_bin_peak);
+        }
+
+        // a gap in the frequency axis doesn't change the correction
+        let samples = (0..len)
+            .map(|i| libm::sinf(2.0 * PI * 1000.0 * i as f32 / 44100.0))
+            .collect::<Vec<_>>();
+        let spectrum = samples_fft_to_spectrum(
+            &WindowType::Hann.apply(&samples),
+            44100,
+            FrequencyLimit::All,
+            None,
+        )
+        .unwrap();
+        let expected = spectrum.corrected_peak_amplitude(1000.0, WindowType::Hann);
+        let corrected = spectrum
+            .with_excluded_band(100.0, 400.0)
+            .corrected_peak_amplitude(1000.0, WindowType::Hann);
+        assert!((expected - 256.0).abs() < 256.0 * 0.01, "{}", expected);
+        float_cmp::assert_approx_eq!(f32, corrected, expected, ulps = 2);
+    }
+
+    #[test]
+    fn test_classify_peak() {
+        let len = 1024;
+        let spectrum_of_sine = |bin: f32, window: WindowType| {
+            let samples = (0..len)
+                .map(|i| libm::sinf(2.0 * PI * bin * i as f32 / len as f32))
+                .collect::<Vec<_>>();
+            samples_fft_to_spectrum(&window.apply(&samples), len, FrequencyLimit::All, None)
+                .unwrap()
+        };
+
+        for window in [WindowType::Hann, WindowType::Rectangular] {
+            for bin in [100.0, 100.3, 100.5] {
+                let spectrum = spectrum_of_sine(bin, window);
+                let (peak_fr, _peak_val) = spectrum.max();
+                assert_eq!(
+                    spectrum.classify_peak(peak_fr, window),
+                    PeakClass::Tone,
+                    "{:?}: {}",
+                    window,
+                    bin
+                );
+
+                // strongest bin outside of the main lobe
+                let peak_index = spectrum.closest_index(peak_fr.val());
+                let half_width = window.main_lobe_half_width();
+                let sidelobe = spectrum
+                    .data()
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| i.abs_diff(peak_index) > half_width)
+                    .map(|(_i, (fr, val))| (*fr, *val))
+                    .max_by_key(|(_fr, val)| *val)
+                    .unwrap();
+                assert_eq!(
+                    spectrum.classify_peak(sidelobe.0, window),
+                    PeakClass::Sidelobe,
+                    "{:?}: {}",
+                    window,
+                    bin
+                );
+            }
+        }
+
+        // single bin spike: narrower than the main lobe of the Hann window
+        let spike = FrequencySpectrum::from_iter_sorted(
+            (0..=16).map(|i| ((i as f32).into(), if i == 8 { 1.0 } else { 0.01 }.into())),
+            1.0,
+            32,
+        );
+        assert_eq!(
+            spike.classify_peak(8.0.into(), WindowType::Hann),
+            PeakClass::Sidelobe
+        );
+        assert_eq!(
+            spike.classify_peak(8.0.into(), WindowType::Rectangular),
+            PeakClass::Tone
+        );
+    }
+}
@@ -0,0 +1,217 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for phase related calculations on the complex FFT result, such as
+//! phase unwrapping, the group delay, and coherent averaging.
+
+use crate::Complex32;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+
+/// Returns the phase of the complex number in radians in interval
+/// `[-PI; PI]`.
+#[inline]
+#[must_use]
+pub fn phase(val: &Complex32) -> f32 {
+    libm::atan2f(val.im, val.re)
+}
+
+/// Unwraps a sequence of phases (in radians) in-place by adding or subtracting
+/// multiples of `2 * PI`, so that the difference between two consecutive
+/// phases is never bigger than `PI`.
+pub fn unwrap_phase(phases: &mut [f32]) {
+    let mut offset = 0.0;
+    for i in 1..phases.len() {
+        let delta = phases[i] + offset - phases[i - 1];
+        // remove full turns, so that the difference is in interval [-PI; PI]
+        offset -= 2.0 * PI * libm::roundf(delta / (2.0 * PI));
+        phases[i] += offset;
+    }
+}
+
+/// Calculates the group delay in seconds, i.e., the negative derivative of
+/// the (unwrapped) phase with respect to the angular frequency, for each bin
+/// of a complex spectrum. Useful for filter analysis.
+///
+/// The derivative is approximated by the phase difference between adjacent
+/// bins divided by the angular frequency spacing of the bins. Entry `k` is the
+/// group delay between bin `k` and `k + 1`; the last entry repeats the
+/// previous one, so that the result has the same length as the input.
+///
+/// ## Parameters
+/// * `complex_spectrum` Complex FFT bins from the DC component upwards, e.g.,
+///                      from [`crate::samples_fft_to_complex_bins`].
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `fft_len` Number of samples that were passed into the FFT.
+#[must_use]
+pub fn group_delay(complex_spectrum: &[Complex32], sampling_rate: u32, fft_len: usize) -> Vec<f32> {
+    if complex_spectrum.len() < 2 {
+        return vec![0.0; complex_spectrum.len()];
+    }
+
+    let mut phases = complex_spectrum.iter().map(phase).collect::<Vec<_>>();
+    unwrap_phase(&mut phases);
+
+    // angular frequency between two bins in rad/s
+    let delta_omega = 2.0 * PI * sampling_rate as f32 / fft_len as f32;
+    let mut group_delay = phases
+        .windows(2)
+        .map(|w| -(w[1] - w[0]) / delta_omega)
+        .collect::<Vec<_>>();
+    group_delay.push(group_delay[group_delay.len() - 1]);
+    group_delay
+}
+
+/// Averages the complex bins of repeated acquisitions of the same signal,
+/// e.g., from [`crate::samples_fft_to_complex_bins`]. In contrast to
+/// averaging the magnitudes (incoherent averaging), this preserves the phase.
+/// Hence, components with a consistent phase across all acquisitions, i.e.,
+/// synchronized to the acquisition, keep their magnitude while uncorrelated
+/// noise averages down by `sqrt(complex_spectra.len())`. Take the magnitudes
+/// afterwards.
+///
+/// ## Parameters
+/// * `complex_spectra` Complex bins of each acquisition. All must have the
+///                     same length.
+///
+/// ## Return value
+/// Averaged complex bins. Empty, if `complex_spectra` is empty.
+///
+/// ## Panics
+/// If the spectra have different lengths.
+#[must_use]
+pub fn coherent_average(complex_spectra: &[Vec<Complex32>]) -> Vec<Complex32> {
+    let bins_count = complex_spectra.first().map_or(0, Vec::len);
+    assert!(
+        complex_spectra
+            .iter()
+            .all(|spectrum| spectrum.len() == bins_count),
+        "All spectra must have the same length!"
+    );
+
+    let mut sum = vec![Complex32::new(0.0, 0.0); bins_count];
+    for spectrum in complex_spectra {
+        for (sum, bin) in sum.iter_mut().zip(spectrum) {
+            *sum += bin;
+        }
+    }
+    let count = complex_spectra.len() as f32;
+    sum.into_iter().map(|bin| bin / count).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::samples_fft_to_complex_bins;
+    use crate::signal_gen::white_noise;
+
+    #[test]
+    fn test_unwrap_phase() {
+        let mut phases = [0.0, 3.0, -3.0, -0.5, 2.5, -2.5];
+        unwrap_phase(&mut phases);
+        let expected = [
+            0.0,
+            3.0,
+            2.0 * PI - 3.0,
+            2.0 * PI - 0.5,
+            2.0 * PI + 2.5,
+            4.0 * PI - 2.5,
+        ];
+        for (expected, actual) in expected.iter().zip(phases.iter()) {
+            float_cmp::assert_approx_eq!(f32, *expected, *actual, epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_group_delay_of_delayed_impulse() {
+        // an impulse that is delayed by 3 samples has a constant group delay
+        // of 3 samples
+        let sampling_rate = 1000;
+        let mut samples = [0.0; 64];
+        samples[3] = 1.0;
+        let bins = samples_fft_to_complex_bins(&samples).unwrap();
+        let group_delay = group_delay(&bins, sampling_rate, samples.len());
+        assert_eq!(group_delay.len(), bins.len());
+        for val in group_delay {
+            float_cmp::assert_approx_eq!(f32, 0.003, val, epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_coherent_average() {
+        // tone at bin 16, synchronized to each acquisition, plus white noise
+        let acquisitions = 64;
+        let noise = white_noise(1.0, 256 * acquisitions, 1000, 0x1234_5678);
+        let complex_spectra = noise
+            .chunks_exact(256)
+            .map(|noise| {
+                let samples = noise
+                    .iter()
+                    .enumerate()
+                    .map(|(i, noise)| 0.1 * libm::sinf(2.0 * PI * 16.0 * i as f32 / 256.0) + noise)
+                    .collect::<Vec<_>>();
+                samples_fft_to_complex_bins(&samples).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let coherent = coherent_average(&complex_spectra)
+            .iter()
+            .map(crate::complex_to_magnitude)
+            .collect::<Vec<_>>();
+        let incoherent = (0..coherent.len())
+            .map(|i| {
+                complex_spectra
+                    .iter()
+                    .map(|spectrum| crate::complex_to_magnitude(&spectrum[i]))
+                    .sum::<f32>()
+                    / acquisitions as f32
+            })
+            .collect::<Vec<_>>();
+        let noise_floor = |magnitudes: &[f32]| {
+            magnitudes
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != 16)
+                .map(|(_, magnitude)| magnitude)
+                .sum::<f32>()
+                / (magnitudes.len() - 1) as f32
+        };
+
+        // the tone keeps its magnitude of N/2 * amplitude
+        assert!((coherent[16] - 12.8).abs() < 1.5);
+        // the noise is reduced by ~sqrt(64)
+        assert!(noise_floor(&coherent) < noise_floor(&incoherent) / 4.0);
+        // the tone is hidden in the noise without coherent averaging
+        assert!(
+            coherent[16] / noise_floor(&coherent) > 4.0 * incoherent[16] / noise_floor(&incoherent)
+        );
+
+        assert!(coherent_average(&[]).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_coherent_average_different_lengths() {
+        let _ = coherent_average(&[vec![Complex32::new(1.0, 0.0)], vec![]]);
+    }
+}
@@ -0,0 +1,234 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for [`Pipeline`], which exposes the intermediate results of the
+//! analysis, e.g., for teaching: the windowed samples, the complex FFT result,
+//! and the magnitudes before scaling.
+
+use crate::error::SpectrumAnalyzerError;
+use crate::fft::FftImpl;
+use crate::scaling::SpectrumScalingFunction;
+use crate::windows::WindowType;
+use crate::{
+    complex_to_magnitude, fft_result_to_spectrum, verify_samples, Complex32, FrequencyLimit,
+    FrequencySpectrum, SpectrumConfig,
+};
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+
+/// Observer of a stage of the [`Pipeline`] with the result of that stage.
+pub type StageObserver<'a, T> = &'a mut dyn FnMut(&[T]);
+
+/// The analysis of [`crate::samples_fft_to_spectrum`] split into stages
+/// (window, FFT, magnitudes, scaling) with an optional observer per stage.
+/// Stages without an observer cause no overhead; for example, the magnitudes
+/// are only collected into a separate buffer if there is an observer for
+/// them.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::pipeline::Pipeline;
+/// use spectrum_analyzer::windows::WindowType;
+/// use spectrum_analyzer::FrequencyLimit;
+/// let samples = vec![0.0, 1.1, 5.5, -5.5];
+/// let mut on_magnitudes = |magnitudes: &[f32]| println!("{:?}", magnitudes);
+/// let spectrum = Pipeline::new(WindowType::Hann)
+///     .on_magnitudes(&mut on_magnitudes)
+///     .analyze(&samples, 44100, FrequencyLimit::All, None)
+///     .unwrap();
+/// ```
+pub struct Pipeline<'a> {
+    /// Window that is applied to the samples.
+    window: WindowType,
+    /// Observer of the windowed samples.
+    on_windowed: Option<StageObserver<'a, f32>>,
+    /// Observer of the complex FFT result from the DC component to the
+    /// Nyquist frequency.
+    on_fft: Option<StageObserver<'a, Complex32>>,
+    /// Observer of the magnitudes of the FFT result, before scaling.
+    on_magnitudes: Option<StageObserver<'a, f32>>,
+}
+
+impl<'a> Pipeline<'a> {
+    /// Creates a pipeline without observers.
+    ///
+    /// ## Parameters
+    /// * `window` Window that is applied to the samples. Use
+    ///            [`WindowType::Rectangular`] for no window.
+    #[must_use]
+    pub const fn new(window: WindowType) -> Self {
+        Self {
+            window,
+            on_windowed: None,
+            on_fft: None,
+            on_magnitudes: None,
+        }
+    }
+
+    /// Registers an observer of the windowed samples.
+    #[must_use]
+    pub fn on_windowed(mut self, observer: StageObserver<'a, f32>) -> Self {
+        self.on_windowed = Some(observer);
+        self
+    }
+
+    /// Registers an observer of the complex FFT result from the DC component
+    /// (index 0) to the Nyquist frequency (index `N/2`). See
+    /// [`crate::samples_fft_to_complex_bins`].
+    #[must_use]
+    pub fn on_fft(mut self, observer: StageObserver<'a, Complex32>) -> Self {
+        self.on_fft = Some(observer);
+        self
+    }
+
+    /// Registers an observer of the magnitudes of the complex FFT result,
+    /// before the frequency limit and the scaling function are applied.
+    #[must_use]
+    pub fn on_magnitudes(mut self, observer: StageObserver<'a, f32>) -> Self {
+        self.on_magnitudes = Some(observer);
+        self
+    }
+
+    /// Runs all stages and invokes the registered observers in the order of
+    /// the stages.
+    ///
+    /// ## Parameters
+    /// See [`crate::samples_fft_to_spectrum`]. The window of the pipeline is
+    /// applied to `samples` first.
+    ///
+    /// ## Return value
+    /// The same spectrum as [`crate::samples_fft_to_spectrum`] returns for
+    /// the windowed samples.
+    ///
+    /// ## Errors
+    /// The same as for [`crate::samples_fft_to_spectrum`].
+    pub fn analyze(
+        &mut self,
+        samples: &[f32],
+        sampling_rate: u32,
+        frequency_limit: FrequencyLimit,
+        scaling_fn: Option<&SpectrumScalingFunction>,
+    ) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+        verify_samples(samples)?;
+        frequency_limit
+            .verify(sampling_rate as f32 / 2.0)
+            .map_err(SpectrumAnalyzerError::InvalidFrequencyLimit)?;
+
+        let windowed = self.window.apply(samples);
+        if let Some(on_windowed) = self.on_windowed.as_mut() {
+            on_windowed(&windowed);
+        }
+
+        let fft_res = FftImpl::calc(&windowed);
+        if let Some(on_fft) = self.on_fft.as_mut() {
+            on_fft(&fft_res);
+        }
+
+        if let Some(on_magnitudes) = self.on_magnitudes.as_mut() {
+            let magnitudes = fft_res.iter().map(complex_to_magnitude).collect::<Vec<_>>();
+            on_magnitudes(&magnitudes);
+        }
+
+        let mut spectrum = fft_result_to_spectrum(
+            windowed.len(),
+            &fft_res,
+            sampling_rate,
+            frequency_limit,
+            scaling_fn,
+            &SpectrumConfig::default(),
+        )?;
+        spectrum.set_frame_level(&windowed);
+        Ok(spectrum)
+    }
+}
+
+impl Debug for Pipeline<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("window", &self.window)
+            .field("on_windowed", &self.on_windowed.is_some())
+            .field("on_fft", &self.on_fft.is_some())
+            .field("on_magnitudes", &self.on_magnitudes.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::samples_fft_to_spectrum;
+    use crate::scaling::divide_by_N;
+    use crate::windows::hann_window;
+
+    #[test]
+    fn test_pipeline_stages() {
+        // 1 kHz sine
+        let samples = (0..1024)
+            .map(|i| libm::sinf(2.0 * core::f32::consts::PI * 1000.0 * i as f32 / 44100.0))
+            .collect::<Vec<_>>();
+
+        let mut windowed = Vec::new();
+        let mut complex_bins = Vec::new();
+        let mut magnitudes = Vec::new();
+        let mut on_windowed = |stage: &[f32]| windowed = stage.to_vec();
+        let mut on_fft = |stage: &[Complex32]| complex_bins = stage.to_vec();
+        let mut on_magnitudes = |stage: &[f32]| magnitudes = stage.to_vec();
+        let spectrum = Pipeline::new(WindowType::Hann)
+            .on_windowed(&mut on_windowed)
+            .on_fft(&mut on_fft)
+            .on_magnitudes(&mut on_magnitudes)
+            .analyze(&samples, 44100, FrequencyLimit::All, Some(&divide_by_N))
+            .unwrap();
+
+        assert_eq!(windowed, hann_window(&samples));
+        assert_eq!(complex_bins.len(), 513);
+        assert_eq!(magnitudes.len(), 513);
+        for (bin, magnitude) in complex_bins.iter().zip(&magnitudes) {
+            assert_eq!(complex_to_magnitude(bin), *magnitude);
+        }
+        // the scaling stage
+        for ((_fr, fr_val), magnitude) in spectrum.data().iter().zip(&magnitudes) {
+            assert_eq!(fr_val.val(), magnitude / 1024.0);
+        }
+
+        let expected =
+            samples_fft_to_spectrum(&windowed, 44100, FrequencyLimit::All, Some(&divide_by_N))
+                .unwrap();
+        assert_eq!(spectrum.data(), expected.data());
+    }
+
+    #[test]
+    fn test_pipeline_without_observers() {
+        let samples = [0.0, 1.1, 5.5, -5.5];
+        let spectrum = Pipeline::new(WindowType::Rectangular)
+            .analyze(&samples, 44100, FrequencyLimit::All, None)
+            .unwrap();
+        let expected = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+        assert_eq!(spectrum.data(), expected.data());
+
+        assert!(matches!(
+            Pipeline::new(WindowType::Hann).analyze(&[0.0; 3], 44100, FrequencyLimit::All, None),
+            Err(SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo)
+        ));
+    }
+}
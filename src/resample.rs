@@ -0,0 +1,122 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for sample-rate conversion, e.g., to compare recordings with
+//! different sampling rates. See [`resample_linear`].
+
+use alloc::vec::Vec;
+
+/// Converts the sampling rate of `samples` from `from_rate` to `to_rate` by
+/// linear interpolation between neighbouring samples.
+///
+/// This is not a high-fidelity resampler: there is no anti-aliasing filter.
+/// Hence, when downsampling, frequencies above the new Nyquist frequency
+/// `to_rate / 2` are aliased into the spectrum, and the linear interpolation
+/// attenuates high frequencies slightly. For spectral comparisons of signals
+/// whose content lies well below both Nyquist frequencies, this is adequate.
+/// Use a dedicated resampling crate for anything else.
+///
+/// ## Parameters
+/// * `samples` Samples with sampling rate `from_rate`.
+/// * `from_rate` Sampling rate of `samples` in Hertz.
+/// * `to_rate` Desired sampling rate in Hertz.
+///
+/// ## Return value
+/// Samples with sampling rate `to_rate`. The first sample is the same and the
+/// duration stays the same, i.e., there are
+/// `(samples.len() - 1) * to_rate / from_rate + 1` samples (rounded down).
+///
+/// ## Panics
+/// If `from_rate` or `to_rate` is zero.
+#[must_use]
+pub fn resample_linear(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    assert!(from_rate > 0, "from_rate must be bigger than zero");
+    assert!(to_rate > 0, "to_rate must be bigger than zero");
+    if samples.is_empty() {
+        return Vec::new();
+    }
+
+    let last_index = samples.len() - 1;
+    let resampled_len =
+        (last_index as u64 * u64::from(to_rate) / u64::from(from_rate)) as usize + 1;
+    (0..resampled_len)
+        .map(|i| {
+            // exact integer arithmetic for the position, to not accumulate
+            // rounding errors for long recordings
+            let numerator = i as u64 * u64::from(from_rate);
+            let index = (numerator / u64::from(to_rate)) as usize;
+            let t = (numerator % u64::from(to_rate)) as f32 / to_rate as f32;
+            let a = samples[index];
+            let b = samples[(index + 1).min(last_index)];
+            a + (b - a) * t
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signal_gen::sine_wave;
+    use crate::windows::hann_window;
+    use crate::{samples_fft_to_spectrum, FrequencyLimit};
+
+    #[test]
+    fn test_resample_linear_lengths() {
+        let samples = [0.0, 1.0, 2.0, 3.0, 4.0];
+        assert_eq!(resample_linear(&samples, 44100, 44100), samples);
+        assert_eq!(
+            resample_linear(&samples, 1, 2),
+            [0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 3.5, 4.0]
+        );
+        assert_eq!(resample_linear(&samples, 2, 1), [0.0, 2.0, 4.0]);
+        assert!(resample_linear(&[], 48000, 44100).is_empty());
+        assert_eq!(resample_linear(&[1.0], 48000, 44100), [1.0]);
+    }
+
+    #[test]
+    fn test_resample_linear_keeps_frequency() {
+        let samples = sine_wave(1000.0, 48000, 1000);
+        let resampled = resample_linear(&samples, 48000, 44100);
+        assert_eq!(resampled.len(), 44100);
+
+        let spectrum = samples_fft_to_spectrum(
+            &hann_window(&resampled[..16384]),
+            44100,
+            FrequencyLimit::All,
+            None,
+        )
+        .unwrap();
+        float_cmp::assert_approx_eq!(
+            f32,
+            spectrum.max().0.val(),
+            1000.0,
+            epsilon = 44100.0 / 16384.0
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_resample_linear_zero_rate() {
+        let _ = resample_linear(&[1.0, 2.0], 0, 44100);
+    }
+}
@@ -26,6 +26,7 @@ SOFTWARE.
 //! frequency value (the FFT result). They act as "idea/inspiration". Feel free
 //! to either compose them or create your own derivation from them.
 
+use crate::FrequencySpectrum;
 use alloc::boxed::Box;
 
 /// Helper struct for [`SpectrumScalingFunction`] that is passed into the
@@ -43,13 +44,41 @@ pub struct SpectrumDataStats {
     pub max: f32,
     /// Average frequency value in spectrum.
     pub average: f32,
-    /// Median frequency value in spectrum.
+    /// Median frequency value in spectrum. When created from a spectrum,
+    /// this requires a sorted copy of its data, see
+    /// [`FrequencySpectrum::median`].
     pub median: f32,
+    /// Sum of all frequency values in spectrum, e.g., for a normalization to
+    /// a unit sum.
+    pub sum: f32,
+    /// Sum of the squares of all frequency values in spectrum, i.e., the
+    /// energy, e.g., for a normalization to unit energy.
+    pub sum_of_squares: f32,
     /// Number of samples (`samples.len()`). Already casted to f32, to avoid
     /// repeatedly casting in a loop for each value.
     pub n: f32,
 }
 
+impl From<&FrequencySpectrum> for SpectrumDataStats {
+    /// Creates the statistics from the current data of the spectrum. This is
+    /// useful to apply scaling functions manually, e.g., on own data arrays.
+    /// `n` is the number of analyzed samples, i.e.,
+    /// [`FrequencySpectrum::samples_len`] and not the number of frequency
+    /// bins.
+    #[inline]
+    fn from(spectrum: &FrequencySpectrum) -> Self {
+        Self {
+            min: spectrum.min().1.val(),
+            max: spectrum.max().1.val(),
+            average: spectrum.average().val(),
+            median: spectrum.median().val(),
+            sum: spectrum.sum(),
+            sum_of_squares: spectrum.sum_of_squares(),
+            n: spectrum.samples_len() as f32,
+        }
+    }
+}
+
 /// Describes the type for a function that scales/normalizes the data inside [`crate::FrequencySpectrum`].
 /// The scaling only affects the value/amplitude of the frequency, but not the frequency itself.
 /// It is applied to every single element.
@@ -66,11 +95,64 @@ pub struct SpectrumDataStats {
 /// some implementation problems.
 pub type SpectrumScalingFunction = dyn Fn(f32, &SpectrumDataStats) -> f32;
 
+/// Position of the current frequency bin, which is passed into a
+/// [`PositionalScalingFunction`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BinPosition {
+    /// Index of the current bin in [`crate::FrequencySpectrum::data`].
+    pub index: usize,
+    /// Number of bins of the spectrum.
+    pub bins_count: usize,
+}
+
+/// Like [`SpectrumScalingFunction`] but additionally receives the position
+/// of the current bin, e.g., to taper the first and last bins or for a
+/// compensation that depends on the position. Apply it with
+/// [`crate::FrequencySpectrum::apply_positional_scaling_fn`]. Every
+/// [`SpectrumScalingFunction`] can be used as well, see
+/// [`ignoring_position`].
+pub type PositionalScalingFunction = dyn Fn(f32, &SpectrumDataStats, BinPosition) -> f32;
+
+/// Adapts a [`SpectrumScalingFunction`] to a [`PositionalScalingFunction`]
+/// that ignores the position. As for [`combined`], the function needs a
+/// `'static` lifetime.
+pub fn ignoring_position(
+    scaling_fn: &'static SpectrumScalingFunction,
+) -> Box<PositionalScalingFunction> {
+    Box::new(move |val, stats, _position| scaling_fn(val, stats))
+}
+
+/// Default floor used by the log-based scaling functions. Every magnitude
+/// below this value (including `0.0` and negative values) is clamped to it
+/// before the logarithm is taken, so that `log10(0)` (`-inf`) can't occur.
+/// `20 * log10(DEFAULT_LOG_FLOOR)` equals `-200 dB`.
+pub const DEFAULT_LOG_FLOOR: f32 = 1e-10;
+
+/// Calculates `log10(max(x, floor))`. All log-based scaling functions of this
+/// module use this helper, so that zero or negative magnitudes are handled
+/// consistently: they are clamped to `floor` instead of producing `-inf` or
+/// `NaN`.
+///
+/// ## Parameters
+/// * `x` Value to take the logarithm of.
+/// * `floor` Lower bound for `x`. Must be bigger than `0.0`.
+#[inline]
+#[must_use]
+pub fn safe_log10(x: f32, floor: f32) -> f32 {
+    debug_assert!(floor > 0.0, "floor must be bigger than 0.0!");
+    debug_assert!(!x.is_nan());
+    let x = if x < floor { floor } else { x };
+    libm::log10f(x)
+}
+
 /// Calculates the base 10 logarithm of each frequency magnitude and
 /// multiplies it with 20. This scaling is quite common, you can
 /// find more information for example here:
 /// <https://www.sjsu.edu/people/burford.furman/docs/me120/FFT_tutorial_NI.pdf>
 ///
+/// Magnitudes below [`DEFAULT_LOG_FLOOR`] are clamped to it, see
+/// [`safe_log10`]. Use [`scale_20_times_log10_with_floor`] for a custom floor.
+///
 /// ## Usage
 /// ```rust
 ///use spectrum_analyzer::{samples_fft_to_spectrum, scaling, FrequencyLimit};
@@ -87,12 +169,33 @@ pub type SpectrumScalingFunction = dyn Fn(f32, &SpectrumDataStats) -> f32;
 pub fn scale_20_times_log10(fr_val: f32, _stats: &SpectrumDataStats) -> f32 {
     debug_assert!(!fr_val.is_infinite());
     debug_assert!(!fr_val.is_nan());
-    debug_assert!(fr_val >= 0.0);
-    if fr_val == 0.0 {
-        0.0
-    } else {
-        20.0 * libm::log10f(fr_val)
-    }
+    20.0 * safe_log10(fr_val, DEFAULT_LOG_FLOOR)
+}
+
+/// Like [`scale_20_times_log10`] but with a custom floor instead of
+/// [`DEFAULT_LOG_FLOOR`]. For example, a floor of `1e-5` limits the lowest
+/// possible value to `-100 dB`.
+///
+/// ## Usage
+/// ```rust
+///use spectrum_analyzer::{samples_fft_to_spectrum, scaling, FrequencyLimit};
+///let window = [0.0, 0.1, 0.2, 0.3]; // add real data here
+///let scaling_fn = scaling::scale_20_times_log10_with_floor(1e-5);
+///let spectrum = samples_fft_to_spectrum(
+///     &window,
+///     44100,
+///     FrequencyLimit::All,
+///     Some(&scaling_fn),
+/// );
+/// ```
+#[must_use]
+pub fn scale_20_times_log10_with_floor(floor: f32) -> Box<SpectrumScalingFunction> {
+    assert!(floor > 0.0, "floor must be bigger than 0.0!");
+    Box::new(move |fr_val, _stats| {
+        debug_assert!(!fr_val.is_infinite());
+        debug_assert!(!fr_val.is_nan());
+        20.0 * safe_log10(fr_val, floor)
+    })
 }
 
 /// Scales each frequency value/amplitude in the spectrum to interval `[0.0; 1.0]`.
@@ -175,6 +278,8 @@ mod tests {
             max: data[data.len() - 1],
             average: data.iter().sum::<f32>() / data.len() as f32,
             median: (2.2 + 3.3) / 2.0,
+            sum: data.iter().sum(),
+            sum_of_squares: data.iter().map(|x| x * x).sum(),
             n: data.len() as f32,
         };
         // check that type matches
@@ -189,6 +294,52 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_safe_log10() {
+        float_cmp::assert_approx_eq!(f32, safe_log10(100.0, DEFAULT_LOG_FLOOR), 2.0, ulps = 3);
+        float_cmp::assert_approx_eq!(f32, safe_log10(0.0, DEFAULT_LOG_FLOOR), -10.0, ulps = 3);
+        float_cmp::assert_approx_eq!(f32, safe_log10(-5.0, 1e-3), -3.0, ulps = 3);
+    }
+
+    #[test]
+    fn test_log_scaling_floor() {
+        let stats = SpectrumDataStats {
+            min: 0.0,
+            max: 10.0,
+            average: 5.0,
+            median: 5.0,
+            sum: 10.0,
+            sum_of_squares: 100.0,
+            n: 2.0,
+        };
+        float_cmp::assert_approx_eq!(f32, scale_20_times_log10(10.0, &stats), 20.0, ulps = 3);
+        float_cmp::assert_approx_eq!(f32, scale_20_times_log10(0.0, &stats), -200.0, ulps = 3);
+
+        let scaling_fn = scale_20_times_log10_with_floor(1e-5);
+        float_cmp::assert_approx_eq!(f32, scaling_fn(10.0, &stats), 20.0, ulps = 3);
+        float_cmp::assert_approx_eq!(f32, scaling_fn(0.0, &stats), -100.0, ulps = 3);
+        float_cmp::assert_approx_eq!(f32, scaling_fn(1e-9, &stats), -100.0, ulps = 3);
+    }
+
+    #[test]
+    fn test_ignoring_position() {
+        let stats = SpectrumDataStats {
+            min: 0.0,
+            max: 10.0,
+            average: 5.0,
+            median: 5.0,
+            sum: 20.0,
+            sum_of_squares: 136.0,
+            n: 4.0,
+        };
+        let scaling_fn = ignoring_position(&divide_by_N);
+        let position = BinPosition {
+            index: 1,
+            bins_count: 3,
+        };
+        float_cmp::assert_approx_eq!(f32, scaling_fn(8.0, &stats, position), 2.0, ulps = 3);
+    }
+
     // make sure this compiles
     #[test]
     fn test_combined_compiles() {
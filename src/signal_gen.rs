@@ -0,0 +1,257 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for generating synthetic signals with known properties, e.g., to
+//! exercise the analyzer in tests and examples. Only available with the
+//! `signal-gen` feature.
+//!
+//! All functions return samples in interval `[-1.0; 1.0]`. The number of
+//! samples is `sampling_rate * duration_ms / 1000` (rounded down), or
+//! `sampling_rate * duration_s` for [`log_sweep`].
+
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+use libm::{expf, logf, sinf};
+
+/// Creates a sine wave with an amplitude of `1.0`.
+///
+/// ## Parameters
+/// * `frequency` Frequency in Hertz.
+/// * `sampling_rate` Sampling rate in Hertz, e.g. `44100`.
+/// * `duration_ms` Duration of the signal in milliseconds.
+///
+/// ## Panics
+/// If `sampling_rate` is `0`.
+#[must_use]
+pub fn sine_wave(frequency: f32, sampling_rate: u32, duration_ms: u32) -> Vec<f32> {
+    (0..samples_count(sampling_rate, duration_ms))
+        .map(|i| {
+            let t = i as f32 / sampling_rate as f32;
+            sinf(2.0 * PI * frequency * t)
+        })
+        .collect()
+}
+
+/// Creates white noise, i.e., uniformly distributed samples, with a flat
+/// spectrum on average. The noise is deterministic: the same `seed` always
+/// results in the same samples.
+///
+/// ## Parameters
+/// * `amplitude` Maximum absolute value of the samples, e.g. `1.0`.
+/// * `sampling_rate` Sampling rate in Hertz, e.g. `44100`.
+/// * `duration_ms` Duration of the signal in milliseconds.
+/// * `seed` Seed of the pseudo-random number generator.
+///
+/// ## Panics
+/// If `sampling_rate` is `0`.
+#[must_use]
+pub fn white_noise(amplitude: f32, sampling_rate: u32, duration_ms: u32, seed: u32) -> Vec<f32> {
+    let mut state = seed;
+    (0..samples_count(sampling_rate, duration_ms))
+        .map(|_| {
+            // linear congruential generator; the upper 24 bits are used, as
+            // the lower bits have short periods
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let uniform = (state >> 8) as f32 / (1 << 23) as f32 - 1.0;
+            amplitude * uniform
+        })
+        .collect()
+}
+
+/// Creates a linear chirp (sweep) with an amplitude of `1.0`, i.e., a sine
+/// whose frequency rises (or falls) linearly from `f0` to `f1` over the whole
+/// duration.
+///
+/// ## Parameters
+/// * `f0` Frequency in Hertz at the beginning.
+/// * `f1` Frequency in Hertz at the end.
+/// * `sampling_rate` Sampling rate in Hertz, e.g. `44100`.
+/// * `duration_ms` Duration of the signal in milliseconds.
+///
+/// ## Panics
+/// If `sampling_rate` is `0`.
+#[must_use]
+pub fn chirp(f0: f32, f1: f32, sampling_rate: u32, duration_ms: u32) -> Vec<f32> {
+    let duration = duration_ms as f32 / 1000.0;
+    // zero duration results in no samples anyway
+    let sweep_rate = if duration > 0.0 {
+        (f1 - f0) / duration
+    } else {
+        0.0
+    };
+    (0..samples_count(sampling_rate, duration_ms))
+        .map(|i| {
+            let t = i as f32 / sampling_rate as f32;
+            // the phase is the integral of the instantaneous frequency
+            sinf(2.0 * PI * (f0 * t + 0.5 * sweep_rate * t * t))
+        })
+        .collect()
+}
+
+/// Creates a logarithmic (exponential) sine sweep with an amplitude of `1.0`,
+/// e.g., as test signal to measure the frequency response of a system. See
+/// [`crate::difference_spectrum`] to compare the response with the sweep.
+///
+/// The instantaneous frequency at time `t` is
+/// `f(t) = f_start * (f_end / f_start)^(t / duration_s)`, i.e., the sweep
+/// spends the same time in each octave: `duration_s / log2(f_end / f_start)`
+/// seconds. Hence, unlike with [`chirp`], the energy per octave is constant,
+/// which results in a pink spectrum. The phase is the integral of `f(t)`:
+/// `2 * PI * f_start * duration_s / ln(f_end / f_start) * ((f_end / f_start)^(t / duration_s) - 1)`.
+///
+/// ## Parameters
+/// * `f_start` Frequency in Hertz at the beginning. Must be bigger than `0`.
+/// * `f_end` Frequency in Hertz at the end. Must be bigger than `0`. Can be
+///           smaller than `f_start` for a falling sweep.
+/// * `duration_s` Duration of the signal in seconds.
+/// * `sampling_rate` Sampling rate in Hertz, e.g. `44100`.
+///
+/// ## Panics
+/// If `f_start` or `f_end` is not bigger than `0`, if `duration_s` is
+/// negative, or if `sampling_rate` is `0`.
+#[must_use]
+pub fn log_sweep(f_start: f32, f_end: f32, duration_s: f32, sampling_rate: u32) -> Vec<f32> {
+    assert!(
+        f_start > 0.0 && f_end > 0.0,
+        "the frequencies of a logarithmic sweep must be bigger than 0 Hz"
+    );
+    assert!(duration_s >= 0.0, "the duration must not be negative");
+    assert!(
+        sampling_rate > 0,
+        "the sampling rate must be bigger than 0 Hz"
+    );
+    let samples_count = (duration_s * sampling_rate as f32) as usize;
+    let octaves_ln = logf(f_end / f_start);
+    (0..samples_count)
+        .map(|i| {
+            let t = i as f32 / sampling_rate as f32;
+            // without a sweep, this is the limit of the formula below
+            let phase = if octaves_ln == 0.0 {
+                f_start * t
+            } else {
+                f_start * duration_s / octaves_ln * (expf(t / duration_s * octaves_ln) - 1.0)
+            };
+            sinf(2.0 * PI * phase)
+        })
+        .collect()
+}
+
+/// Returns the number of samples of a signal with the given duration.
+fn samples_count(sampling_rate: u32, duration_ms: u32) -> usize {
+    assert!(
+        sampling_rate > 0,
+        "the sampling rate must be bigger than 0 Hz"
+    );
+    (u64::from(sampling_rate) * u64::from(duration_ms) / 1000) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::windows::hann_window;
+    use crate::{samples_fft_to_spectrum, FrequencyLimit};
+
+    #[test]
+    fn test_sine_wave() {
+        let samples = sine_wave(1000.0, 8000, 128);
+        assert_eq!(samples.len(), 1024);
+        assert!(samples.iter().all(|val| val.abs() <= 1.0));
+        let spectrum =
+            samples_fft_to_spectrum(&hann_window(&samples), 8000, FrequencyLimit::All, None)
+                .unwrap();
+        assert_eq!(spectrum.max().0.val(), 1000.0);
+    }
+
+    #[test]
+    fn test_white_noise() {
+        let samples = white_noise(0.5, 8000, 1000, 42);
+        assert_eq!(samples.len(), 8000);
+        assert_eq!(samples, white_noise(0.5, 8000, 1000, 42));
+        assert_ne!(samples, white_noise(0.5, 8000, 1000, 43));
+        assert!(samples.iter().all(|val| val.abs() <= 0.5));
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+        assert!(mean.abs() < 0.02, "{}", mean);
+    }
+
+    #[test]
+    fn test_chirp() {
+        let samples = chirp(500.0, 3000.0, 8000, 1024);
+        assert_eq!(samples.len(), 8192);
+        let dominant_frequency = |frame: &[f32]| {
+            samples_fft_to_spectrum(&hann_window(frame), 8000, FrequencyLimit::All, None)
+                .unwrap()
+                .max()
+                .0
+                .val()
+        };
+        // the frequency rises over time
+        let start = dominant_frequency(&samples[..1024]);
+        let end = dominant_frequency(&samples[samples.len() - 1024..]);
+        assert!((start - 656.0).abs() < 50.0, "{}", start);
+        assert!((end - 2844.0).abs() < 50.0, "{}", end);
+    }
+
+    #[test]
+    fn test_log_sweep() {
+        let samples = log_sweep(100.0, 3200.0, 1.0, 8000);
+        assert_eq!(samples.len(), 8000);
+        assert!(samples.iter().all(|val| val.abs() <= 1.0));
+        let dominant_frequency = |frame: &[f32]| {
+            samples_fft_to_spectrum(&hann_window(frame), 8000, FrequencyLimit::All, None)
+                .unwrap()
+                .max()
+                .0
+                .val()
+        };
+        // 5 octaves in one second: the frequency doubles every 200 ms
+        let center_frame = |t: f32| {
+            let center = (t * 8000.0) as usize;
+            dominant_frequency(&samples[center - 512..center + 512])
+        };
+        let at_400_ms = center_frame(0.4);
+        let at_800_ms = center_frame(0.8);
+        assert!((at_400_ms - 400.0).abs() < 25.0, "{}", at_400_ms);
+        assert!((at_800_ms - 1600.0).abs() < 25.0, "{}", at_800_ms);
+
+        // constant frequency
+        let constant = log_sweep(1000.0, 1000.0, 0.1, 8000);
+        let sine = sine_wave(1000.0, 8000, 100);
+        assert_eq!(constant.len(), sine.len());
+        assert!(constant
+            .iter()
+            .zip(sine.iter())
+            .all(|(a, b)| (a - b).abs() < 1e-4));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_log_sweep_zero_frequency() {
+        let _ = log_sweep(0.0, 1000.0, 1.0, 8000);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_sampling_rate() {
+        let _ = sine_wave(1000.0, 0, 100);
+    }
+}
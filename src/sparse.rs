@@ -0,0 +1,473 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for [`SparseSpectrum`], a memory efficient representation of
+//! spectra that consist mostly of zeroes, e.g., after thresholding.
+
+#[cfg(feature = "serde")]
+use crate::error::SpectrumAnalyzerError;
+use crate::{Frequency, FrequencySpectrum, FrequencyValue};
+use alloc::vec::Vec;
+#[cfg(feature = "serde")]
+use core::cmp::Ordering;
+
+/// Sparse representation of a [`FrequencySpectrum`] that only stores the
+/// retained bins, e.g., the peaks after thresholding. Together with the
+/// metadata of the frequency axis, the dense spectrum can be restored via
+/// [`SparseSpectrum::to_dense`]; bins that were not retained become `0.0`.
+///
+/// The frequency axis of the dense spectrum may have gaps, e.g., after
+/// [`FrequencySpectrum::with_excluded_band`]. Its frequencies must be
+/// multiples of the frequency resolution, as for every spectrum from the FFT.
+///
+/// The statistics, such as [`SparseSpectrum::max`], are the ones of the
+/// dense spectrum the sparse spectrum was created from.
+///
+/// With the `serde` feature, this type implements `Serialize` and
+/// `Deserialize`. Deserialization rejects data with an inconsistent
+/// frequency axis with
+/// [`crate::error::SpectrumAnalyzerError::InvalidSparseSpectrum`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "SparseSpectrumUnchecked"))]
+pub struct SparseSpectrum {
+    /// Retained (Frequency, FrequencyValue) pairs, sorted by frequency.
+    data: Vec<(Frequency, FrequencyValue)>,
+    /// Index of each retained bin in the dense spectrum.
+    indices: Vec<usize>,
+    /// Frequency axis of the dense spectrum as runs of consecutive bins:
+    /// (number of the first bin, number of bins). The frequency of bin `n`
+    /// is `n * frequency_resolution`.
+    axis: Vec<(usize, usize)>,
+    /// See [`FrequencySpectrum::frequency_resolution`].
+    frequency_resolution: f32,
+    /// See [`FrequencySpectrum::samples_len`].
+    samples_len: u32,
+    /// Number of bins of the dense spectrum.
+    dense_len: usize,
+    /// See [`FrequencySpectrum::average`].
+    average: FrequencyValue,
+    /// See [`FrequencySpectrum::median`].
+    median: FrequencyValue,
+    /// See [`FrequencySpectrum::min`].
+    min: (Frequency, FrequencyValue),
+    /// See [`FrequencySpectrum::max`].
+    max: (Frequency, FrequencyValue),
+}
+
+/// Deserialized [`SparseSpectrum`] before its consistency is checked.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SparseSpectrumUnchecked {
+    data: Vec<(Frequency, FrequencyValue)>,
+    indices: Vec<usize>,
+    axis: Vec<(usize, usize)>,
+    frequency_resolution: f32,
+    samples_len: u32,
+    dense_len: usize,
+    average: FrequencyValue,
+    median: FrequencyValue,
+    min: (Frequency, FrequencyValue),
+    max: (Frequency, FrequencyValue),
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SparseSpectrumUnchecked> for SparseSpectrum {
+    type Error = SpectrumAnalyzerError;
+
+    fn try_from(unchecked: SparseSpectrumUnchecked) -> Result<Self, Self::Error> {
+        let spectrum = Self {
+            data: unchecked.data,
+            indices: unchecked.indices,
+            axis: unchecked.axis,
+            frequency_resolution: unchecked.frequency_resolution,
+            samples_len: unchecked.samples_len,
+            dense_len: unchecked.dense_len,
+            average: unchecked.average,
+            median: unchecked.median,
+            min: unchecked.min,
+            max: unchecked.max,
+        };
+        spectrum.validate()?;
+        Ok(spectrum)
+    }
+}
+
+impl SparseSpectrum {
+    /// Creates a sparse spectrum that retains all bins whose absolute value
+    /// is bigger than `threshold`.
+    ///
+    /// ## Parameters
+    /// * `spectrum` Dense spectrum. It may be empty or have gaps in its
+    ///              frequency axis.
+    /// * `threshold` Bins with an absolute value smaller than or equal to
+    ///               this are dropped. Use `0.0` to retain all non-zero bins.
+    ///
+    /// ## Panics
+    /// In debug builds, if the frequencies of `spectrum` are not distinct
+    /// multiples of its frequency resolution, e.g., after
+    /// [`FrequencySpectrum::to_log_axis`].
+    #[must_use]
+    pub fn from_spectrum(spectrum: &FrequencySpectrum, threshold: f32) -> Self {
+        let frequency_resolution = spectrum.frequency_resolution();
+        let mut axis = Vec::<(usize, usize)>::new();
+        for (fr, _fr_val) in spectrum.data() {
+            let bin = libm::roundf(fr.val() / frequency_resolution) as usize;
+            debug_assert!(
+                float_cmp::approx_eq!(
+                    f32,
+                    fr.val(),
+                    bin as f32 * frequency_resolution,
+                    epsilon = frequency_resolution * 1e-3
+                ),
+                "Frequency {}Hz is not a multiple of the frequency resolution!",
+                fr.val()
+            );
+            match axis.last_mut() {
+                Some((first_bin, count)) if *first_bin + *count == bin => *count += 1,
+                last => {
+                    debug_assert!(
+                        last.map_or(true, |(first_bin, count)| *first_bin + *count < bin),
+                        "Frequencies must be distinct and sorted!"
+                    );
+                    axis.push((bin, 1));
+                }
+            }
+        }
+
+        let (indices, data) = spectrum
+            .data()
+            .iter()
+            .enumerate()
+            .filter(|(_i, (_fr, fr_val))| fr_val.val().abs() > threshold)
+            .map(|(i, bin)| (i, *bin))
+            .unzip();
+
+        Self {
+            data,
+            indices,
+            axis,
+            frequency_resolution,
+            samples_len: spectrum.samples_len(),
+            dense_len: spectrum.data().len(),
+            average: spectrum.average(),
+            median: spectrum.median(),
+            min: spectrum.min(),
+            max: spectrum.max(),
+        }
+    }
+
+    /// Restores the dense spectrum. Bins that were not retained are `0.0`.
+    /// The statistics are recalculated, hence, they equal the ones of the
+    /// original spectrum if only zeroes were dropped. If the original
+    /// spectrum was empty, this is [`FrequencySpectrum::empty`].
+    #[must_use]
+    pub fn to_dense(&self) -> FrequencySpectrum {
+        if self.dense_len == 0 {
+            return FrequencySpectrum::empty();
+        }
+        // same calculation of the frequencies as for the FFT result
+        let mut data = self
+            .bins()
+            .map(|bin| {
+                let fr = bin as f32 * self.frequency_resolution;
+                (fr.into(), 0.0.into())
+            })
+            .collect::<Vec<(Frequency, FrequencyValue)>>();
+        for (index, bin) in self.indices.iter().zip(&self.data) {
+            data[*index] = *bin;
+        }
+        FrequencySpectrum::new(data, self.frequency_resolution, self.samples_len, &mut [])
+    }
+
+    /// Returns the numbers of all bins of the dense spectrum, i.e., the
+    /// frequencies in multiples of the frequency resolution.
+    fn bins(&self) -> impl Iterator<Item = usize> + '_ {
+        self.axis
+            .iter()
+            .flat_map(|(first_bin, count)| *first_bin..*first_bin + *count)
+    }
+
+    /// Checks that the retained bins and the frequency axis are consistent,
+    /// e.g., after deserialization.
+    #[cfg(feature = "serde")]
+    fn validate(&self) -> Result<(), SpectrumAnalyzerError> {
+        let invalid = Err(SpectrumAnalyzerError::InvalidSparseSpectrum);
+        if self.indices.len() != self.data.len()
+            || self
+                .axis
+                .iter()
+                .map(|(_first_bin, count)| count)
+                .sum::<usize>()
+                != self.dense_len
+            || self.axis.iter().any(|(_first_bin, count)| *count == 0)
+            || self
+                .axis
+                .windows(2)
+                .any(|runs| runs[0].0 + runs[0].1 >= runs[1].0)
+        {
+            return invalid;
+        }
+        // also rejects a `NaN` resolution
+        if self.dense_len > 0
+            && self.frequency_resolution.partial_cmp(&0.0) != Some(Ordering::Greater)
+        {
+            return invalid;
+        }
+
+        let mut bins = self.bins();
+        // index of the next bin of `bins`
+        let mut next_index = 0;
+        for (index, (fr, _fr_val)) in self.indices.iter().zip(&self.data) {
+            if *index < next_index {
+                return invalid;
+            }
+            let bin = match bins.nth(index - next_index) {
+                Some(bin) => bin,
+                None => return invalid,
+            };
+            next_index = index + 1;
+            if libm::roundf(fr.val() / self.frequency_resolution) as usize != bin {
+                return invalid;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the retained bins, sorted by frequency.
+    #[inline]
+    #[must_use]
+    pub fn data(&self) -> &[(Frequency, FrequencyValue)] {
+        &self.data
+    }
+
+    /// Returns the number of retained bins.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns whether no bin was retained.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the number of bins of the dense spectrum.
+    #[inline]
+    #[must_use]
+    pub const fn dense_len(&self) -> usize {
+        self.dense_len
+    }
+
+    /// See [`FrequencySpectrum::frequency_resolution`].
+    #[inline]
+    #[must_use]
+    pub const fn frequency_resolution(&self) -> f32 {
+        self.frequency_resolution
+    }
+
+    /// See [`FrequencySpectrum::samples_len`].
+    #[inline]
+    #[must_use]
+    pub const fn samples_len(&self) -> u32 {
+        self.samples_len
+    }
+
+    /// See [`FrequencySpectrum::average`].
+    #[inline]
+    #[must_use]
+    pub const fn average(&self) -> FrequencyValue {
+        self.average
+    }
+
+    /// See [`FrequencySpectrum::median`].
+    #[inline]
+    #[must_use]
+    pub const fn median(&self) -> FrequencyValue {
+        self.median
+    }
+
+    /// See [`FrequencySpectrum::min`].
+    #[inline]
+    #[must_use]
+    pub const fn min(&self) -> (Frequency, FrequencyValue) {
+        self.min
+    }
+
+    /// See [`FrequencySpectrum::max`].
+    #[inline]
+    #[must_use]
+    pub const fn max(&self) -> (Frequency, FrequencyValue) {
+        self.max
+    }
+
+    /// See [`FrequencySpectrum::range`].
+    #[inline]
+    #[must_use]
+    pub fn range(&self) -> FrequencyValue {
+        self.max.1 - self.min.1
+    }
+}
+
+impl From<&FrequencySpectrum> for SparseSpectrum {
+    /// Retains all non-zero bins. See [`SparseSpectrum::from_spectrum`].
+    #[inline]
+    fn from(spectrum: &FrequencySpectrum) -> Self {
+        Self::from_spectrum(spectrum, 0.0)
+    }
+}
+
+impl From<&SparseSpectrum> for FrequencySpectrum {
+    /// See [`SparseSpectrum::to_dense`].
+    #[inline]
+    fn from(spectrum: &SparseSpectrum) -> Self {
+        spectrum.to_dense()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "serde")]
+    use crate::tests::fixtures::spectrum_from_pairs;
+    use crate::{samples_fft_to_spectrum, FrequencyLimit};
+
+    #[test]
+    fn test_sparse_spectrum_round_trip() {
+        // two tones exactly on a bin, noise below the threshold
+        let samples = (0..1024)
+            .map(|i| {
+                let t = i as f32 / 1024.0;
+                libm::sinf(2.0 * core::f32::consts::PI * 100.0 * t)
+                    + 0.5 * libm::sinf(2.0 * core::f32::consts::PI * 300.0 * t)
+            })
+            .collect::<Vec<_>>();
+        let spectrum = samples_fft_to_spectrum(
+            &samples,
+            1024,
+            FrequencyLimit::Range(50.0, 500.0),
+            Some(&|val, _stats| if val > 1.0 { val } else { 0.0 }),
+        )
+        .unwrap();
+        let retained_count = spectrum
+            .data()
+            .iter()
+            .filter(|(_fr, fr_val)| fr_val.val() != 0.0)
+            .count();
+        assert_eq!(retained_count, 2);
+
+        let sparse = SparseSpectrum::from(&spectrum);
+        assert_eq!(sparse.len(), retained_count);
+        assert_eq!(sparse.dense_len(), spectrum.data().len());
+        assert_eq!(sparse.max(), spectrum.max());
+        assert_eq!(sparse.median(), spectrum.median());
+        assert_eq!(sparse.range(), spectrum.range());
+
+        let dense = sparse.to_dense();
+        assert_eq!(dense.data(), spectrum.data());
+        assert_eq!(
+            dense.frequency_resolution(),
+            spectrum.frequency_resolution()
+        );
+        assert_eq!(dense.samples_len(), spectrum.samples_len());
+        assert_eq!(dense.average(), spectrum.average());
+        assert_eq!(dense.min(), spectrum.min());
+        assert_eq!(dense.max(), spectrum.max());
+
+        // threshold: only the louder tone is retained
+        let sparse = SparseSpectrum::from_spectrum(&spectrum, 300.0);
+        assert_eq!(sparse.len(), 1);
+        assert_eq!(sparse.data()[0].0.val(), 100.0);
+        let dense = FrequencySpectrum::from(&sparse);
+        assert_eq!(dense.freq_val_exact(300.0).val(), 0.0);
+        assert_eq!(dense.freq_val_exact(100.0), spectrum.freq_val_exact(100.0));
+        assert_eq!(dense.min_fr(), spectrum.min_fr());
+        assert_eq!(dense.max_fr(), spectrum.max_fr());
+    }
+
+    #[test]
+    fn test_sparse_spectrum_gaps() {
+        let samples = (0..1024)
+            .map(|i| libm::sinf(2.0 * core::f32::consts::PI * 100.0 * i as f32 / 1024.0))
+            .collect::<Vec<_>>();
+        let spectrum = samples_fft_to_spectrum(&samples, 1024, FrequencyLimit::All, None)
+            .unwrap()
+            .with_excluded_band(40.0, 60.0);
+
+        for threshold in [0.0, 1.0] {
+            let sparse = SparseSpectrum::from_spectrum(&spectrum, threshold);
+            assert_eq!(sparse.dense_len(), spectrum.data().len());
+            let dense = sparse.to_dense();
+            assert_eq!(dense.data().len(), spectrum.data().len());
+            for ((fr, fr_val), (original_fr, original_fr_val)) in
+                dense.data().iter().zip(spectrum.data())
+            {
+                assert_eq!(fr, original_fr);
+                if original_fr_val.val().abs() > threshold {
+                    assert_eq!(fr_val, original_fr_val);
+                } else {
+                    assert_eq!(fr_val.val(), 0.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sparse_spectrum_empty() {
+        let sparse = SparseSpectrum::from(&FrequencySpectrum::empty());
+        assert!(sparse.is_empty());
+        assert_eq!(sparse.dense_len(), 0);
+        assert!(sparse.to_dense().is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_sparse_spectrum_serde() {
+        let data = (0..=100).map(|i| {
+            let val = if i % 10 == 0 { 1.0 } else { 0.0 };
+            (i as f32 * 2.0, val)
+        });
+        let spectrum = spectrum_from_pairs(data, 2.0, 1000).with_excluded_band(50.0, 70.0);
+        let sparse = SparseSpectrum::from(&spectrum);
+
+        let json = serde_json::to_string(&sparse).unwrap();
+        let deserialized = serde_json::from_str::<SparseSpectrum>(&json).unwrap();
+        assert_eq!(deserialized, sparse);
+        assert_eq!(deserialized.to_dense().data(), spectrum.data());
+
+        // a retained bin that doesn't match its index, an index beyond the
+        // dense spectrum, and a dense length that doesn't match the axis
+        let mut value = serde_json::to_value(&sparse).unwrap();
+        value["indices"][1] = 11.into();
+        assert!(serde_json::from_value::<SparseSpectrum>(value).is_err());
+        let mut value = serde_json::to_value(&sparse).unwrap();
+        value["indices"][sparse.len() - 1] = sparse.dense_len().into();
+        assert!(serde_json::from_value::<SparseSpectrum>(value).is_err());
+        let mut value = serde_json::to_value(&sparse).unwrap();
+        value["dense_len"] = (sparse.dense_len() + 1).into();
+        assert!(serde_json::from_value::<SparseSpectrum>(value).is_err());
+    }
+}
@@ -0,0 +1,1459 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for analyzing long recordings chunk by chunk (frame by frame). The
+//! result is a list of spectra, i.e., a spectrogram.
+
+use crate::error::SpectrumAnalyzerError;
+use crate::fft::FftImpl;
+use crate::scaling::{safe_log10, SpectrumScalingFunction, DEFAULT_LOG_FLOOR};
+use crate::windows::{windowed_samples_to_complex, WindowType};
+use crate::{
+    complex_to_magnitude, samples_fft_to_complex_bins, samples_fft_to_spectrum, Complex32,
+    Frequency, FrequencyLimit, FrequencySpectrum, SpectrumConfig,
+};
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::{max, min};
+use core::fmt::{Display, Formatter};
+
+/// Describes how [`analyze_file_samples`] splits a recording into chunks.
+#[derive(Debug, Copy, Clone)]
+pub struct ChunkConfig {
+    /// Number of samples per chunk, i.e., the FFT length. Must be a power of
+    /// two.
+    pub chunk_len: usize,
+    /// Number of samples between the beginnings of two consecutive chunks.
+    /// If this is smaller than `chunk_len`, the chunks overlap. Must not be
+    /// zero.
+    pub hop_len: usize,
+    /// Window function that is applied to each chunk. Use
+    /// [`WindowType::Rectangular`] for no window.
+    pub window: WindowType,
+    /// Frequency limit of each spectrum. See [`FrequencyLimit`].
+    pub frequency_limit: FrequencyLimit,
+}
+
+impl ChunkConfig {
+    /// Creates a config with the overlap of two consecutive chunks instead of
+    /// the raw [`ChunkConfig::hop_len`]. The hop length is
+    /// `chunk_len * (1 - fraction)`, rounded to the nearest sample.
+    ///
+    /// ## Parameters
+    /// * `chunk_len` See [`ChunkConfig::chunk_len`].
+    /// * `overlap` See [`Overlap`].
+    /// * `window` See [`ChunkConfig::window`].
+    /// * `frequency_limit` See [`ChunkConfig::frequency_limit`].
+    ///
+    /// ## Errors
+    /// [`SpectrumAnalyzerError::InvalidOverlap`] if the overlap is not in
+    /// interval `[0%; 100%)` and [`SpectrumAnalyzerError::InvalidHopLength`]
+    /// if the resulting hop length is zero.
+    pub fn with_overlap(
+        chunk_len: usize,
+        overlap: Overlap,
+        window: WindowType,
+        frequency_limit: FrequencyLimit,
+    ) -> Result<Self, SpectrumAnalyzerError> {
+        Ok(Self {
+            chunk_len,
+            hop_len: overlap.hop_len(chunk_len)?,
+            window,
+            frequency_limit,
+        })
+    }
+}
+
+/// Overlap of two consecutive chunks. This is an alternative to the raw hop
+/// length in samples, see [`ChunkConfig::with_overlap`]. The overlap must be
+/// in interval `[0%; 100%)`, as an overlap of 100% means that the analysis
+/// doesn't advance.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Overlap {
+    /// Overlap in percent, e.g., `50.0`. Must be in interval `[0.0; 100.0)`.
+    Percent(f32),
+    /// Overlap as fraction, e.g., `0.5`. Must be in interval `[0.0; 1.0)`.
+    Fraction(f32),
+}
+
+impl Overlap {
+    /// Returns the overlap as fraction.
+    #[inline]
+    #[must_use]
+    pub fn fraction(&self) -> f32 {
+        match self {
+            Self::Percent(percent) => percent / 100.0,
+            Self::Fraction(fraction) => *fraction,
+        }
+    }
+
+    /// Calculates the hop length for chunks of `chunk_len` samples, i.e.,
+    /// `chunk_len * (1 - fraction)`, rounded to the nearest sample.
+    ///
+    /// ## Errors
+    /// [`SpectrumAnalyzerError::InvalidOverlap`] if the overlap is not in
+    /// interval `[0%; 100%)` and [`SpectrumAnalyzerError::InvalidHopLength`]
+    /// if the hop length is zero.
+    #[inline]
+    pub fn hop_len(&self, chunk_len: usize) -> Result<usize, SpectrumAnalyzerError> {
+        let fraction = self.fraction();
+        if !(0.0..1.0).contains(&fraction) {
+            return Err(SpectrumAnalyzerError::InvalidOverlap(fraction));
+        }
+        let hop_len = libm::roundf(chunk_len as f32 * (1.0 - fraction)) as usize;
+        if hop_len == 0 {
+            return Err(SpectrumAnalyzerError::InvalidHopLength);
+        }
+        Ok(hop_len)
+    }
+}
+
+/// Analyzes an arbitrarily long recording by splitting it into chunks of
+/// [`ChunkConfig::chunk_len`] samples, each starting [`ChunkConfig::hop_len`]
+/// samples after the previous one. Each chunk is windowed and transformed
+/// into a [`FrequencySpectrum`] via [`samples_fft_to_spectrum`]. The last chunk
+/// is padded with zeroes, if the recording doesn't fill it completely.
+///
+/// ## Parameters
+/// * `samples` The whole recording.
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `config` See [`ChunkConfig`].
+/// * `scaling_fn` Applied to each spectrum. See
+///                [`crate::scaling::SpectrumScalingFunction`].
+/// * `progress` Optional callback that is invoked after each chunk with the
+///              progress in interval `(0.0; 1.0]`. The values are
+///              monotonically increasing and the final value is `1.0`. Useful
+///              for progress bars in GUIs.
+///
+/// ## Return value
+/// One [`FrequencySpectrum`] per chunk, in chronological order.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::FrequencyLimit;
+/// use spectrum_analyzer::spectrogram::{analyze_file_samples, ChunkConfig};
+/// use spectrum_analyzer::windows::WindowType;
+/// let samples = vec![0.0; 10000];
+/// let config = ChunkConfig {
+///     chunk_len: 1024,
+///     hop_len: 512,
+///     window: WindowType::Hann,
+///     frequency_limit: FrequencyLimit::All,
+/// };
+/// let spectra = analyze_file_samples(
+///     &samples,
+///     44100,
+///     &config,
+///     None,
+///     Some(&mut |progress| println!("{:.0}%", progress * 100.0)),
+/// )
+/// .unwrap();
+/// ```
+pub fn analyze_file_samples(
+    samples: &[f32],
+    sampling_rate: u32,
+    config: &ChunkConfig,
+    scaling_fn: Option<&SpectrumScalingFunction>,
+    mut progress: Option<&mut dyn FnMut(f32)>,
+) -> Result<Vec<FrequencySpectrum>, SpectrumAnalyzerError> {
+    let mut chunks = Chunks::new(samples, config)?;
+    let chunks_count = chunks.count;
+    let mut spectra = Vec::with_capacity(chunks_count);
+    // the coefficients are calculated once and each chunk is windowed into
+    // the same buffer
+    let window = config.window.apply(&vec![1.0; config.chunk_len]);
+    let mut windowed = Vec::with_capacity(config.chunk_len);
+
+    while let Some((i, chunk)) = chunks.next_chunk() {
+        windowed.clear();
+        windowed.extend(
+            chunk
+                .iter()
+                .zip(&window)
+                .map(|(sample, coefficient)| sample * coefficient),
+        );
+        let mut spectrum =
+            samples_fft_to_spectrum(&windowed, sampling_rate, config.frequency_limit, scaling_fn)?;
+        // the level of the frame without the window
+        spectrum.set_frame_level(chunk);
+        spectra.push(spectrum);
+
+        if let Some(progress) = progress.as_mut() {
+            progress((i + 1) as f32 / chunks_count as f32);
+        }
+    }
+
+    Ok(spectra)
+}
+
+/// Calculates the spectrum of `full[offset..offset + len]` without an
+/// intermediate copy of the slice. The window is applied while the samples
+/// are written into `buffer`, which is reused across calls. This is useful
+/// to analyze a big buffer at many offsets, e.g., with a sliding window.
+///
+/// ## Parameters
+/// * `full` The whole buffer.
+/// * `offset` Index of the first sample of the analyzed slice.
+/// * `len` Number of samples of the analyzed slice, i.e., the FFT length.
+///         Must be a power of two.
+/// * `window` Coefficients of the window function, e.g., from
+///            `WindowType::Hann.apply(&[1.0; len])`. Calculate them once for
+///            all offsets.
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `frequency_limit` See [`samples_fft_to_spectrum`].
+/// * `scaling_fn` See [`samples_fft_to_spectrum`].
+/// * `buffer` Buffer for the windowed samples. Its content is replaced. It
+///            only allocates if its capacity is smaller than `len`.
+///
+/// ## Return value
+/// The same spectrum as [`samples_fft_to_spectrum`] for the windowed slice.
+/// The frame level is the level of the slice without the window, as in
+/// [`analyze_file_samples`].
+///
+/// ## Errors
+/// [`SpectrumAnalyzerError::SliceOutOfBounds`] if the slice exceeds `full`,
+/// [`SpectrumAnalyzerError::WindowLengthMismatch`] if `window.len()` differs
+/// from `len`, and every error of [`samples_fft_to_spectrum`] otherwise.
+#[allow(clippy::too_many_arguments)]
+pub fn analyze_slice(
+    full: &[f32],
+    offset: usize,
+    len: usize,
+    window: &[f32],
+    sampling_rate: u32,
+    frequency_limit: FrequencyLimit,
+    scaling_fn: Option<&SpectrumScalingFunction>,
+    buffer: &mut Vec<f32>,
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    let end = offset.saturating_add(len);
+    if end > full.len() {
+        return Err(SpectrumAnalyzerError::SliceOutOfBounds(end, full.len()));
+    }
+    if window.len() != len {
+        return Err(SpectrumAnalyzerError::WindowLengthMismatch(
+            window.len(),
+            len,
+        ));
+    }
+    let samples = &full[offset..end];
+
+    buffer.clear();
+    buffer.extend(
+        samples
+            .iter()
+            .zip(window)
+            .map(|(sample, coefficient)| sample * coefficient),
+    );
+    let config = SpectrumConfig::default();
+    crate::verify_input(buffer, sampling_rate, frequency_limit, &config)?;
+    let fft_res = FftImpl::calc_in_place(buffer);
+    // the real value of the Nyquist frequency is packed into the DC component
+    let nyquist = Complex32::new(fft_res[0].im, 0.0);
+    fft_res[0].im = 0.0;
+    let mut spectrum = crate::fft_result_to_spectrum(
+        len,
+        fft_res.iter().chain(core::iter::once(&nyquist)),
+        sampling_rate,
+        frequency_limit,
+        scaling_fn,
+        &config,
+    )?;
+    spectrum.set_frame_level(samples);
+    Ok(spectrum)
+}
+
+/// Describes how a spectrum was produced, e.g., to store it together with
+/// the spectrum for later comparisons. See [`TimedSpectrum::metadata`].
+///
+/// The [`Display`] representation is stable, e.g.,
+/// `window=Hann fft_len=1024 hop_len=512 sampling_rate=44100 frequency_limit=All scaling=none version=1.5.0`.
+/// With the `serde` feature, this type implements `Serialize` and
+/// `Deserialize`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnalysisMetadata {
+    /// Window that was applied to each frame.
+    pub window: WindowType,
+    /// Number of samples of each frame, i.e., the FFT length.
+    pub fft_len: usize,
+    /// Number of samples between the beginnings of two consecutive frames.
+    /// See [`ChunkConfig::hop_len`].
+    pub hop_len: usize,
+    /// Sampling rate in Hertz.
+    pub sampling_rate: u32,
+    /// Frequency limit of the spectrum.
+    pub frequency_limit: FrequencyLimit,
+    /// Optional identifier of the scaling function, as functions can't be
+    /// compared. `None` means that no scaling function was applied. The
+    /// analyzer can't know the identifier, hence, it is
+    /// [`Self::UNNAMED_SCALING`] if a scaling function was applied, unless it
+    /// is set via [`Self::with_scaling`].
+    pub scaling: Option<String>,
+    /// Version of this crate that produced the spectrum.
+    pub crate_version: String,
+}
+
+impl AnalysisMetadata {
+    /// Identifier of a scaling function whose identifier is unknown. See
+    /// [`Self::scaling`]. All unnamed scaling functions are considered equal
+    /// by [`Self::compatible_with`]; use [`Self::with_scaling`] to tell them
+    /// apart.
+    pub const UNNAMED_SCALING: &'static str = "unnamed";
+
+    /// Constructor for the metadata of spectra produced by
+    /// [`analyze_file_samples`] with the given parameters and without a
+    /// scaling function.
+    #[must_use]
+    pub fn new(sampling_rate: u32, config: &ChunkConfig) -> Self {
+        Self {
+            window: config.window,
+            fft_len: config.chunk_len,
+            hop_len: config.hop_len,
+            sampling_rate,
+            frequency_limit: config.frequency_limit,
+            scaling: None,
+            crate_version: String::from(env!("CARGO_PKG_VERSION")),
+        }
+    }
+
+    /// Sets the identifier of the scaling function, e.g., `"20log10"`.
+    #[must_use]
+    pub fn with_scaling(mut self, scaling: &str) -> Self {
+        self.scaling = Some(String::from(scaling));
+        self
+    }
+
+    /// Checks whether spectra with this and the other metadata can be
+    /// combined, e.g., averaged bin by bin. This requires the same window,
+    /// FFT length, sampling rate, frequency limit, and scaling. The hop length
+    /// and the crate version don't matter.
+    #[must_use]
+    pub fn compatible_with(&self, other: &Self) -> bool {
+        self.window == other.window
+            && self.fft_len == other.fft_len
+            && self.sampling_rate == other.sampling_rate
+            && self.frequency_limit == other.frequency_limit
+            && self.scaling == other.scaling
+    }
+}
+
+impl Display for AnalysisMetadata {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        // written out explicitly instead of the `Debug` representations, so
+        // that the representation stays stable
+        let window = match self.window {
+            WindowType::Rectangular => "Rectangular",
+            WindowType::Hann => "Hann",
+            WindowType::Hamming => "Hamming",
+            WindowType::BlackmanHarris4Term => "BlackmanHarris4Term",
+            WindowType::BlackmanHarris7Term => "BlackmanHarris7Term",
+            WindowType::Welch => "Welch",
+        };
+        write!(
+            f,
+            "window={} fft_len={} hop_len={} sampling_rate={} frequency_limit=",
+            window, self.fft_len, self.hop_len, self.sampling_rate,
+        )?;
+        match self.frequency_limit {
+            FrequencyLimit::All => write!(f, "All"),
+            FrequencyLimit::Min(min) => write!(f, "Min({})", min),
+            FrequencyLimit::Max(max) => write!(f, "Max({})", max),
+            FrequencyLimit::Range(min, max) => write!(f, "Range({},{})", min, max),
+            FrequencyLimit::Bins(start, end) => write!(f, "Bins({},{})", start, end),
+        }?;
+        write!(
+            f,
+            " scaling={} version={}",
+            self.scaling.as_deref().unwrap_or("none"),
+            self.crate_version
+        )
+    }
+}
+
+/// A [`FrequencySpectrum`] of a single frame (chunk) of a recording together
+/// with the point in time when the frame begins. See
+/// [`analyze_file_samples_timed`].
+#[derive(Debug)]
+pub struct TimedSpectrum {
+    /// Index of the first sample of the frame in the recording.
+    pub start_sample: usize,
+    /// Point in time in seconds when the frame begins, i.e.,
+    /// `start_sample / sampling_rate`.
+    pub start_seconds: f32,
+    /// Spectrum of the frame.
+    pub spectrum: FrequencySpectrum,
+    /// Describes how the spectrum was produced.
+    pub metadata: AnalysisMetadata,
+}
+
+impl TimedSpectrum {
+    /// Returns the duration of the frame in seconds, i.e.,
+    /// `chunk_len / sampling_rate`. This includes the zero padding of the
+    /// last frame.
+    #[inline]
+    #[must_use]
+    pub fn duration_seconds(&self) -> f32 {
+        1.0 / self.spectrum.frequency_resolution()
+    }
+}
+
+/// Spectrum averaged over several segments of a recording together with the
+/// uncertainty of each bin. See [`average_segments`].
+#[derive(Debug)]
+pub struct AveragedSpectrum {
+    /// Average of the spectra of all segments, bin by bin.
+    pub mean: FrequencySpectrum,
+    /// Sample standard deviation of each bin across the segments. It is
+    /// `0.0` for a single segment. For error bars of [`Self::mean`], divide it
+    /// by `sqrt(num_segments)` to get the standard error.
+    pub std_dev: FrequencySpectrum,
+    /// Number of averaged segments.
+    pub num_segments: usize,
+}
+
+/// Reduces the variance of the spectrum of a noisy signal by averaging the
+/// spectra of several segments, similar to Welch's method, e.g., with
+/// overlapping, Hann windowed segments. Additionally, it estimates how
+/// trustworthy each bin is. See [`AveragedSpectrum`].
+///
+/// ## Parameters
+/// * `samples` The whole recording.
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `config` Describes the segments. See [`ChunkConfig`].
+/// * `scaling_fn` Applied to the spectrum of each segment before averaging.
+///
+/// ## Return value
+/// The averaged spectrum and the uncertainty of each bin.
+///
+/// ## Errors
+/// The same as for [`analyze_file_samples`] and
+/// [`SpectrumAnalyzerError::IncompatibleMetadata`] if the metadata of two
+/// segments is not compatible, see [`AnalysisMetadata::compatible_with`].
+pub fn average_segments(
+    samples: &[f32],
+    sampling_rate: u32,
+    config: &ChunkConfig,
+    scaling_fn: Option<&SpectrumScalingFunction>,
+) -> Result<AveragedSpectrum, SpectrumAnalyzerError> {
+    let frames = analyze_file_samples_timed(samples, sampling_rate, config, scaling_fn, None)?;
+    let first = &verify_compatible(&frames)?.spectrum;
+    let spectra = frames
+        .iter()
+        .map(|frame| &frame.spectrum)
+        .collect::<Vec<_>>();
+    let count = spectra.len() as f32;
+
+    let mut means = vec![0.0; first.data().len()];
+    for spectrum in &spectra {
+        for (mean, (_fr, fr_val)) in means.iter_mut().zip(spectrum.data()) {
+            *mean += fr_val.val() / count;
+        }
+    }
+    let mut variances = vec![0.0; first.data().len()];
+    for spectrum in &spectra {
+        for ((variance, mean), (_fr, fr_val)) in
+            variances.iter_mut().zip(&means).zip(spectrum.data())
+        {
+            *variance += (fr_val.val() - mean) * (fr_val.val() - mean);
+        }
+    }
+    // sample variance of the segments
+    let std_devs = variances.iter().map(|variance| {
+        if spectra.len() > 1 {
+            libm::sqrtf(variance / (count - 1.0))
+        } else {
+            0.0
+        }
+    });
+
+    Ok(AveragedSpectrum {
+        mean: spectrum_with_values(first, means),
+        std_dev: spectrum_with_values(first, std_devs),
+        num_segments: spectra.len(),
+    })
+}
+
+/// Calculates the long-term average spectrum (LTAS) of a whole recording,
+/// e.g., for speech research. This is [`average_segments`] with the usual
+/// defaults: Hann windowed segments with an overlap of 50% whose powers, i.e.,
+/// squared magnitudes, are averaged.
+///
+/// ## Parameters
+/// * `samples` The whole recording with samples in interval `[-1.0; 1.0]`.
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `window_len` Number of samples per segment, i.e., the FFT length. Must
+///                be a power of two, e.g., `1024`.
+///
+/// ## Return value
+/// The average power of each bin in dB relative to full scale (dBFS), i.e.,
+/// a sine with an amplitude of `1.0` has `0 dB` at its bin. Bins without
+/// power are clamped to [`DEFAULT_LOG_FLOOR`].
+///
+/// ## Errors
+/// The same as for [`analyze_file_samples`].
+pub fn long_term_average_spectrum(
+    samples: &[f32],
+    sampling_rate: u32,
+    window_len: usize,
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    let config = ChunkConfig::with_overlap(
+        window_len,
+        Overlap::Percent(50.0),
+        WindowType::Hann,
+        FrequencyLimit::All,
+    )?;
+    let averaged = average_segments(
+        samples,
+        sampling_rate,
+        &config,
+        Some(&|val, _stats| val * val),
+    )?;
+
+    // magnitude of a full scale sine: the coherent gain of the Hann window is 0.5
+    let full_scale_magnitude = window_len as f32 / 4.0;
+    let full_scale_power = full_scale_magnitude * full_scale_magnitude;
+    let mut ltas = averaged.mean;
+    ltas.apply_scaling_fn(
+        &move |power, _stats| 10.0 * safe_log10(power / full_scale_power, DEFAULT_LOG_FLOOR),
+        &mut [],
+    )?;
+    Ok(ltas)
+}
+
+/// Returns the duration in seconds that a frame of `fft_len` samples
+/// represents, i.e., `fft_len / sampling_rate`. This is the time resolution
+/// of a spectrogram and the inverse of the frequency resolution.
+///
+/// ## Parameters
+/// * `fft_len` Number of samples per frame, e.g., [`ChunkConfig::chunk_len`].
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+#[inline]
+#[must_use]
+pub fn window_duration_seconds(fft_len: usize, sampling_rate: u32) -> f32 {
+    fft_len as f32 / sampling_rate as f32
+}
+
+/// Returns the point in time in seconds when the frame with index
+/// `frame_index` begins, i.e., `frame_index * hop_len / sampling_rate`, e.g.,
+/// to label the time axis of a spectrogram.
+///
+/// ## Parameters
+/// * `frame_index` Index of the frame, e.g., in the result of
+///                 [`analyze_file_samples`].
+/// * `hop_len` Number of samples between the beginnings of two consecutive
+///             frames. See [`ChunkConfig::hop_len`].
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+#[inline]
+#[must_use]
+pub fn frame_time_seconds(frame_index: usize, hop_len: usize, sampling_rate: u32) -> f32 {
+    (frame_index * hop_len) as f32 / sampling_rate as f32
+}
+
+/// Like [`analyze_file_samples`] but attaches the point in time to each
+/// spectrum. Frame `i` begins at sample `i * hop_len`.
+///
+/// ## Parameters
+/// See [`analyze_file_samples`].
+///
+/// ## Return value
+/// One [`TimedSpectrum`] per chunk, in chronological order. If `scaling_fn`
+/// is set, the scaling of the metadata is
+/// [`AnalysisMetadata::UNNAMED_SCALING`]. Use [`AnalysisMetadata::with_scaling`]
+/// to distinguish different scaling functions.
+pub fn analyze_file_samples_timed(
+    samples: &[f32],
+    sampling_rate: u32,
+    config: &ChunkConfig,
+    scaling_fn: Option<&SpectrumScalingFunction>,
+    progress: Option<&mut dyn FnMut(f32)>,
+) -> Result<Vec<TimedSpectrum>, SpectrumAnalyzerError> {
+    let spectra = analyze_file_samples(samples, sampling_rate, config, scaling_fn, progress)?;
+    let mut metadata = AnalysisMetadata::new(sampling_rate, config);
+    if scaling_fn.is_some() {
+        metadata = metadata.with_scaling(AnalysisMetadata::UNNAMED_SCALING);
+    }
+    Ok(spectra
+        .into_iter()
+        .enumerate()
+        .map(|(i, spectrum)| TimedSpectrum {
+            start_sample: i * config.hop_len,
+            start_seconds: frame_time_seconds(i, config.hop_len, sampling_rate),
+            spectrum,
+            metadata: metadata.clone(),
+        })
+        .collect())
+}
+
+/// Averages the spectra of several frames bin by bin, e.g., of frames of
+/// different recordings that were analyzed with [`analyze_file_samples_timed`].
+///
+/// ## Parameters
+/// * `frames` Frames to average. Their metadata must be compatible, see
+///            [`AnalysisMetadata::compatible_with`].
+///
+/// ## Errors
+/// [`SpectrumAnalyzerError::IncompatibleMetadata`] if the metadata of two
+/// frames is not compatible and [`SpectrumAnalyzerError::TooFewSamples`] if
+/// `frames` is empty.
+pub fn average_timed_spectra(
+    frames: &[TimedSpectrum],
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    let first = verify_compatible(frames)?;
+    let count = frames.len() as f32;
+    let mut means = vec![0.0; first.spectrum.data().len()];
+    for frame in frames {
+        for (mean, (_fr, fr_val)) in means.iter_mut().zip(frame.spectrum.data()) {
+            *mean += fr_val.val() / count;
+        }
+    }
+    Ok(spectrum_with_values(&first.spectrum, means))
+}
+
+/// Checks that the metadata of all frames is compatible with the metadata of
+/// the first frame before their spectra are combined.
+///
+/// ## Return value
+/// The first frame.
+///
+/// ## Errors
+/// [`SpectrumAnalyzerError::IncompatibleMetadata`] if the metadata of two
+/// frames is not compatible and [`SpectrumAnalyzerError::TooFewSamples`] if
+/// `frames` is empty.
+fn verify_compatible(frames: &[TimedSpectrum]) -> Result<&TimedSpectrum, SpectrumAnalyzerError> {
+    let first = frames.first().ok_or(SpectrumAnalyzerError::TooFewSamples)?;
+    if frames
+        .iter()
+        .any(|frame| !frame.metadata.compatible_with(&first.metadata))
+    {
+        return Err(SpectrumAnalyzerError::IncompatibleMetadata);
+    }
+    Ok(first)
+}
+
+/// Describes what [`analyze_chunks`] does with the remaining samples at the
+/// end of the recording that don't fill a whole chunk.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Remainder {
+    /// The remaining samples are ignored.
+    Drop,
+    /// The remaining samples form a last chunk that is padded with zeroes.
+    Pad,
+}
+
+/// Summary of a recording calculated by [`analyze_chunks`].
+#[derive(Debug)]
+pub struct ChunkedAnalysis {
+    /// Average of the spectra of all chunks, bin by bin.
+    pub average: FrequencySpectrum,
+    /// Maximum of the spectra of all chunks, bin by bin (max hold).
+    pub max_hold: FrequencySpectrum,
+    /// Frequency of the strongest bin of each chunk, in chronological order.
+    /// The length equals the number of chunks.
+    pub peak_frequencies: Vec<Frequency>,
+}
+
+/// Summarizes a recording, e.g., a whole file, in a single call. In contrast
+/// to [`analyze_file_samples`], the chunks don't overlap and only aggregates
+/// are returned instead of the spectrum of each chunk.
+///
+/// ## Parameters
+/// * `samples` The whole recording.
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `chunk_len` Number of samples per chunk, i.e., the FFT length. Must be a
+///               power of two.
+/// * `window` Window function that is applied to each chunk.
+/// * `frequency_limit` Frequency limit of each spectrum.
+/// * `scaling_fn` Applied to the spectrum of each chunk before the
+///                aggregates are calculated.
+/// * `remainder` See [`Remainder`].
+///
+/// ## Return value
+/// The aggregates of all chunks. See [`ChunkedAnalysis`].
+///
+/// ## Errors
+/// [`SpectrumAnalyzerError::TooFewSamples`] if there is no chunk, e.g., if
+/// there are fewer samples than `chunk_len` and the remainder is dropped,
+/// [`SpectrumAnalyzerError::IncompatibleMetadata`] if the metadata of two
+/// chunks is not compatible, see [`AnalysisMetadata::compatible_with`], and
+/// every error of [`analyze_file_samples`] otherwise.
+pub fn analyze_chunks(
+    samples: &[f32],
+    sampling_rate: u32,
+    chunk_len: usize,
+    window: WindowType,
+    frequency_limit: FrequencyLimit,
+    scaling_fn: Option<&SpectrumScalingFunction>,
+    remainder: Remainder,
+) -> Result<ChunkedAnalysis, SpectrumAnalyzerError> {
+    if chunk_len == 0 {
+        // the hop length equals the chunk length
+        return Err(SpectrumAnalyzerError::InvalidHopLength);
+    }
+    let samples = match remainder {
+        Remainder::Drop => &samples[..samples.len() / chunk_len * chunk_len],
+        Remainder::Pad => samples,
+    };
+    let config = ChunkConfig {
+        chunk_len,
+        hop_len: chunk_len,
+        window,
+        frequency_limit,
+    };
+    let frames = analyze_file_samples_timed(samples, sampling_rate, &config, scaling_fn, None)?;
+    let first = &verify_compatible(&frames)?.spectrum;
+    let spectra = frames
+        .iter()
+        .map(|frame| &frame.spectrum)
+        .collect::<Vec<_>>();
+
+    let mut sums = vec![0.0; first.data().len()];
+    let mut maxima = vec![f32::NEG_INFINITY; first.data().len()];
+    for spectrum in &spectra {
+        for (i, (_fr, fr_val)) in spectrum.data().iter().enumerate() {
+            sums[i] += fr_val.val();
+            maxima[i] = maxima[i].max(fr_val.val());
+        }
+    }
+
+    Ok(ChunkedAnalysis {
+        average: spectrum_with_values(first, sums.iter().map(|sum| sum / spectra.len() as f32)),
+        max_hold: spectrum_with_values(first, maxima),
+        peak_frequencies: spectra.iter().map(|spectrum| spectrum.max().0).collect(),
+    })
+}
+
+/// Returns the frame that covers the point in time `t` (in seconds). If
+/// multiple frames cover `t`, because they overlap, this returns the frame
+/// that began most recently. This uses a binary search.
+///
+/// ## Parameters
+/// * `frames` Frames in chronological order, e.g., from
+///            [`analyze_file_samples_timed`].
+/// * `t` Point in time in seconds.
+///
+/// ## Return value
+/// The frame or `None`, if no frame covers `t`.
+#[inline]
+#[must_use]
+pub fn frame_at_time(frames: &[TimedSpectrum], t: f32) -> Option<&TimedSpectrum> {
+    // number of frames that began at or before `t`
+    let count = frames.partition_point(|frame| frame.start_seconds <= t);
+    let frame = frames.get(count.checked_sub(1)?)?;
+    if t < frame.start_seconds + frame.duration_seconds() {
+        Some(frame)
+    } else {
+        None
+    }
+}
+
+/// Segments a recording into active regions, e.g., to trim silence. A frame
+/// is active if its total energy (sum of all squared values) is at most
+/// `threshold_db_below_peak` decibels below the energy of the loudest frame.
+/// Consecutive active frames form a segment. Segments that are separated by
+/// a gap shorter than `min_gap_s` are merged and segments shorter than
+/// `min_duration_s` are dropped afterwards.
+///
+/// ## Parameters
+/// * `frames` Spectra of consecutive frames, e.g., from
+///            [`analyze_file_samples`]. They should be unscaled.
+/// * `hop_seconds` Time between the beginnings of two consecutive frames,
+///                 i.e., `hop_len / sampling_rate`.
+/// * `threshold_db_below_peak` Positive distance in decibels to the energy of
+///                             the loudest frame, e.g., `30.0`.
+/// * `min_duration_s` Minimum duration of a segment in seconds.
+/// * `min_gap_s` Minimum duration of a gap between two segments in seconds.
+///               Shorter gaps are closed.
+///
+/// ## Return value
+/// `(start, end)`-pairs in seconds of all active regions, sorted by time. The
+/// start is the beginning of the first active frame and the end is the
+/// beginning of the first inactive frame after the segment.
+#[inline]
+#[must_use]
+pub fn segment_active_regions(
+    frames: &[FrequencySpectrum],
+    hop_seconds: f32,
+    threshold_db_below_peak: f32,
+    min_duration_s: f32,
+    min_gap_s: f32,
+) -> Vec<(f32, f32)> {
+    let energies = frames
+        .iter()
+        .map(|frame| {
+            frame
+                .data()
+                .iter()
+                .map(|(_fr, val)| val.val() * val.val())
+                .sum::<f32>()
+        })
+        .collect::<Vec<_>>();
+    let max_energy = energies.iter().copied().fold(0.0, f32::max);
+    if max_energy == 0.0 {
+        return Vec::new();
+    }
+
+    let mut segments: Vec<(f32, f32)> = Vec::new();
+    let mut segment_start = None;
+    // iterate one step further to close a segment that lasts until the end
+    for (i, energy) in energies.iter().map(Some).chain([None]).enumerate() {
+        let is_active = energy.map_or(false, |energy| {
+            10.0 * safe_log10(energy / max_energy, DEFAULT_LOG_FLOOR) > -threshold_db_below_peak
+        });
+        match (is_active, segment_start) {
+            (true, None) => segment_start = Some(i),
+            (false, Some(start)) => {
+                let segment = (start as f32 * hop_seconds, i as f32 * hop_seconds);
+                match segments.last_mut() {
+                    Some(previous) if segment.0 - previous.1 < min_gap_s => {
+                        previous.1 = segment.1;
+                    }
+                    _ => segments.push(segment),
+                }
+                segment_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    segments.retain(|(start, end)| end - start >= min_duration_s);
+    segments
+}
+
+/// A local maximum in a spectrogram. See [`spectrogram_peaks`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SpectrogramPeak {
+    /// Index of the frame (time axis).
+    pub frame_idx: usize,
+    /// Frequency in Hertz (frequency axis).
+    pub frequency: f32,
+    /// Value of the spectrum at this point.
+    pub value: f32,
+}
+
+/// Finds all local maxima in a spectrogram, i.e., points that have the
+/// maximum value within a neighborhood of frames and frequency bins around
+/// them. These localized time-frequency events are the foundation of audio
+/// fingerprinting.
+///
+/// This is implemented as a separable sliding window maximum filter, i.e.,
+/// the runtime is `O(frames * bins)` and independent of the size of the
+/// neighborhood.
+///
+/// ## Parameters
+/// * `frames` Spectra of consecutive frames, e.g., from
+///            [`analyze_file_samples`]. All frames must have the same
+///            frequency bins.
+/// * `neighborhood` `(time_bins, freq_bins)`: number of frames and bins on
+///                  each side of a point that are part of its neighborhood.
+/// * `min_value` Minimum value of a peak.
+///
+/// ## Return value
+/// All peaks, sorted by frame and then by frequency. If multiple points in
+/// a neighborhood share the maximum value, all of them are returned.
+#[must_use]
+pub fn spectrogram_peaks(
+    frames: &[FrequencySpectrum],
+    neighborhood: (usize, usize),
+    min_value: f32,
+) -> Vec<SpectrogramPeak> {
+    let (time_bins, freq_bins) = neighborhood;
+    let bins_count = frames.first().map_or(0, |frame| frame.data().len());
+    debug_assert!(
+        frames.iter().all(|frame| frame.data().len() == bins_count),
+        "All frames must have the same number of frequency bins!"
+    );
+    if bins_count == 0 {
+        return Vec::new();
+    }
+
+    let mut deque = VecDeque::new();
+    // maximum filter along the frequency axis, frame by frame
+    let mut max_values = vec![0.0; frames.len() * bins_count];
+    let mut values = Vec::with_capacity(max(bins_count, frames.len()));
+    for (frame, max_values) in frames.iter().zip(max_values.chunks_exact_mut(bins_count)) {
+        values.clear();
+        values.extend(frame.data().iter().map(|(_fr, val)| val.val()));
+        sliding_window_max(&values, freq_bins, max_values, &mut deque);
+    }
+    // maximum filter along the time axis, bin by bin
+    let mut column_max_values = vec![0.0; frames.len()];
+    for bin in 0..bins_count {
+        values.clear();
+        values.extend(max_values.iter().skip(bin).step_by(bins_count));
+        sliding_window_max(&values, time_bins, &mut column_max_values, &mut deque);
+        for (frame_idx, max_value) in column_max_values.iter().enumerate() {
+            max_values[frame_idx * bins_count + bin] = *max_value;
+        }
+    }
+
+    frames
+        .iter()
+        .enumerate()
+        .flat_map(|(frame_idx, frame)| {
+            let max_values = &max_values[frame_idx * bins_count..(frame_idx + 1) * bins_count];
+            frame
+                .data()
+                .iter()
+                .zip(max_values)
+                .filter(|((_fr, val), max_value)| {
+                    val.val() >= min_value && val.val() == **max_value
+                })
+                .map(move |((fr, val), _max_value)| SpectrogramPeak {
+                    frame_idx,
+                    frequency: fr.val(),
+                    value: val.val(),
+                })
+        })
+        .collect()
+}
+
+/// Creates a spectrum with the frequencies of `reference` and the values
+/// `vals`, e.g., the result of a bin-by-bin aggregation of several spectra
+/// with the same frequencies.
+fn spectrum_with_values(
+    reference: &FrequencySpectrum,
+    vals: impl IntoIterator<Item = f32>,
+) -> FrequencySpectrum {
+    let data = reference
+        .data()
+        .iter()
+        .zip(vals)
+        .map(|((fr, _fr_val), val)| (*fr, val.into()));
+    FrequencySpectrum::from_iter_sorted(
+        data,
+        reference.frequency_resolution(),
+        reference.samples_len(),
+    )
+}
+
+/// Calculates the maximum of the window `[i - radius; i + radius]` for each
+/// index `i` of `input` in `O(input.len())`. The deque holds the indices of
+/// the window in decreasing order of their values.
+fn sliding_window_max(
+    input: &[f32],
+    radius: usize,
+    output: &mut [f32],
+    deque: &mut VecDeque<usize>,
+) {
+    deque.clear();
+    for j in 0..input.len() + radius {
+        if j < input.len() {
+            while deque.back().map_or(false, |&back| input[back] <= input[j]) {
+                deque.pop_back();
+            }
+            deque.push_back(j);
+        }
+        if j >= radius {
+            let i = j - radius;
+            while deque.front().map_or(false, |&front| front + radius < i) {
+                deque.pop_front();
+            }
+            output[i] = input[deque[0]];
+        }
+    }
+}
+
+/// Number of frames on each side of a frame whose mean spectral flux forms
+/// the adaptive threshold of [`detect_onsets`].
+const ONSET_MEAN_FRAMES: usize = 8;
+
+/// Detects onsets, e.g., beats or the beginnings of notes, with the spectral
+/// flux of consecutive frames (see [`FrequencySpectrum::spectral_flux`]). The
+/// flux is normalized to its maximum. A frame is an onset, if its flux is a
+/// local maximum and exceeds the mean flux of the surrounding frames by
+/// `threshold`, i.e., the threshold adapts to the loudness of the signal.
+///
+/// ## Parameters
+/// * `samples` The whole recording.
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `window_size` Number of samples per frame. Must be a power of two, e.g.,
+///                 `1024`.
+/// * `hop_size` Number of samples between the beginnings of two consecutive
+///              frames, e.g., `window_size / 2`.
+/// * `threshold` Minimum difference of the normalized flux and the local mean,
+///               in interval `[0.0; 1.0]`, e.g., `0.1`. The smaller, the more
+///               onsets are reported.
+///
+/// ## Return value
+/// Times of the onsets in seconds, in chronological order. The time of an
+/// onset is the center of its frame.
+///
+/// ## Errors
+/// The same as for [`analyze_file_samples`].
+pub fn detect_onsets(
+    samples: &[f32],
+    sampling_rate: u32,
+    window_size: usize,
+    hop_size: usize,
+    threshold: f32,
+) -> Result<Vec<f32>, SpectrumAnalyzerError> {
+    let config = ChunkConfig {
+        chunk_len: window_size,
+        hop_len: hop_size,
+        window: WindowType::Hann,
+        frequency_limit: FrequencyLimit::All,
+    };
+    let frames = analyze_file_samples(samples, sampling_rate, &config, None, None)?;
+
+    // the first frame has no predecessor
+    let mut flux = Vec::with_capacity(frames.len());
+    flux.push(0.0);
+    flux.extend(
+        frames
+            .windows(2)
+            .map(|pair| pair[1].spectral_flux(&pair[0])),
+    );
+    let max_flux = flux.iter().copied().fold(0.0, f32::max);
+    if max_flux == 0.0 {
+        return Ok(Vec::new());
+    }
+    flux.iter_mut().for_each(|val| *val /= max_flux);
+
+    let half_window = window_duration_seconds(window_size, sampling_rate) / 2.0;
+    Ok((0..flux.len())
+        .filter(|&i| {
+            let begin = i.saturating_sub(ONSET_MEAN_FRAMES);
+            let end = min(i + ONSET_MEAN_FRAMES + 1, flux.len());
+            let local_mean = flux[begin..end].iter().sum::<f32>() / (end - begin) as f32;
+            let is_local_max = (i == 0 || flux[i] > flux[i - 1])
+                && (i + 1 == flux.len() || flux[i] >= flux[i + 1]);
+            is_local_max && flux[i] > local_mean + threshold
+        })
+        .map(|i| frame_time_seconds(i, hop_size, sampling_rate) + half_window)
+        .collect())
+}
+
+/// A point of a reassigned spectrogram: `(time in seconds, frequency in
+/// Hertz, magnitude)`. See [`reassigned_spectrogram`].
+pub type ReassignedPoint = (f32, f32, f32);
+
+/// Calculates a spectrogram with the reassignment method, which is much
+/// sharper than the plain spectrogram of [`analyze_file_samples`]. Instead of
+/// the center of its bin and its frame, the energy of each bin is located at
+/// its instantaneous frequency and its group delay corrected time. Hence,
+/// tones and chirps become thin lines and clicks become sharp points.
+///
+/// For each frame, three FFTs are calculated: with the window `h`, the time
+/// weighted window `t * h`, and the derivative of the window `dh/dt`. The
+/// reassigned frequency is `f - Im(X_dh / X_h) * sampling_rate / (2 * PI)` and
+/// the reassigned time is `t + Re(X_th / X_h) / sampling_rate`.
+///
+/// ## Parameters
+/// * `samples` The whole recording.
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `config` Describes the frames. See [`ChunkConfig`]. The frequency limit
+///            applies to the reassigned frequencies.
+///
+/// ## Return value
+/// The points of each frame, in chronological order. Bins without energy are
+/// omitted, as their reassignment is undefined. The points are not on a grid
+/// anymore, i.e., they must be plotted as a scatter plot or accumulated into
+/// a grid by the caller.
+///
+/// ## Errors
+/// The same as for [`analyze_file_samples`].
+pub fn reassigned_spectrogram(
+    samples: &[f32],
+    sampling_rate: u32,
+    config: &ChunkConfig,
+) -> Result<Vec<Vec<ReassignedPoint>>, SpectrumAnalyzerError> {
+    let mut chunks = Chunks::new(samples, config)?;
+
+    let len = config.chunk_len;
+    let window = config.window.apply(&vec![1.0; len]);
+    // relative to the center of the frame, in samples
+    let center = len as f32 / 2.0;
+    let time_weighted_window = window
+        .iter()
+        .enumerate()
+        .map(|(i, coefficient)| (i as f32 - center) * coefficient)
+        .collect::<Vec<_>>();
+    // central differences, in samples
+    let derivative_window = (0..len)
+        .map(|i| (window[(i + 1) % len] - window[(i + len - 1) % len]) / 2.0)
+        .collect::<Vec<_>>();
+    let frequency_resolution = sampling_rate as f32 / len as f32;
+    let frequency_limit = config
+        .frequency_limit
+        .to_frequency_range(frequency_resolution);
+    let to_hertz = sampling_rate as f32 / (2.0 * core::f32::consts::PI);
+
+    let mut frames = Vec::with_capacity(chunks.count);
+    while let Some((i, chunk)) = chunks.next_chunk() {
+        crate::verify_samples(chunk)?;
+        let bins = windowed_complex_bins(chunk, &window)?;
+        let time_weighted_bins = windowed_complex_bins(chunk, &time_weighted_window)?;
+        let derivative_bins = windowed_complex_bins(chunk, &derivative_window)?;
+
+        let begin = i * config.hop_len;
+        let frame_center = (begin as f32 + center) / sampling_rate as f32;
+        let points = bins
+            .iter()
+            .zip(time_weighted_bins.iter().zip(&derivative_bins))
+            .enumerate()
+            .filter(|(_k, (bin, _))| bin.norm_sqr() > 0.0)
+            .map(|(k, (bin, (time_weighted_bin, derivative_bin)))| {
+                let time_offset = (time_weighted_bin / bin).re / sampling_rate as f32;
+                let frequency_offset = -(derivative_bin / bin).im * to_hertz;
+                (
+                    frame_center + time_offset,
+                    k as f32 * frequency_resolution + frequency_offset,
+                    complex_to_magnitude(bin),
+                )
+            })
+            .filter(|(_t, fr, _val)| frequency_limit.contains(*fr))
+            .collect::<Vec<_>>();
+        frames.push(points);
+    }
+
+    Ok(frames)
+}
+
+/// Calculates how much the energy of a frequency band is modulated with
+/// frequencies in `modulation_band`. Speech, for example, has a
+/// characteristic modulation of about 4 Hz (the syllable rate), whereas music
+/// usually has not. Hence, this is a classic feature for speech detection.
+///
+/// The energy of `band` in each frame forms a time series. Its mean is
+/// removed and it is zero padded to the next power of two. The energy of its
+/// spectrum, calculated by [`crate::samples_fft_to_complex_bins`], is summed
+/// up in `modulation_band`.
+///
+/// ## Parameters
+/// * `frames` Spectra of consecutive frames, e.g., from
+///            [`analyze_file_samples`]. They should be unscaled.
+/// * `band` Frequency band `(low, high)` in Hertz whose energy is examined.
+/// * `hop_seconds` Time between the beginnings of two consecutive frames,
+///                 i.e., `hop_len / sampling_rate`.
+/// * `modulation_band` Modulation frequencies `(low, high)` in Hertz, e.g.,
+///                     `(2.0, 8.0)` for speech. Must be lower than the
+///                     frame rate `1 / (2 * hop_seconds)`.
+///
+/// ## Return value
+/// Energy of the modulation in `modulation_band`.
+///
+/// ## Errors
+/// If there are fewer than two frames or more frames than
+/// [`crate::MAX_SAMPLES_LEN`].
+pub fn modulation_energy(
+    frames: &[FrequencySpectrum],
+    band: (f32, f32),
+    hop_seconds: f32,
+    modulation_band: (f32, f32),
+) -> Result<f32, SpectrumAnalyzerError> {
+    let mut band_energies = frames
+        .iter()
+        .map(|frame| frame.band_energy(band.0, band.1))
+        .collect::<Vec<_>>();
+    let mean = band_energies.iter().sum::<f32>() / max(band_energies.len(), 1) as f32;
+    band_energies.iter_mut().for_each(|energy| *energy -= mean);
+    if band_energies.len() > 1 {
+        band_energies.resize(band_energies.len().next_power_of_two(), 0.0);
+    }
+
+    let complex_bins = samples_fft_to_complex_bins(&band_energies)?;
+    let modulation_resolution = 1.0 / (band_energies.len() as f32 * hop_seconds);
+    Ok(complex_bins
+        .iter()
+        .enumerate()
+        .filter(|(i, _bin)| {
+            let fr = *i as f32 * modulation_resolution;
+            fr >= modulation_band.0 && fr <= modulation_band.1
+        })
+        .map(|(_i, bin)| bin.re * bin.re + bin.im * bin.im)
+        .sum())
+}
+
+/// Applies the window to the samples and returns the bins of the complex FFT
+/// from the DC component to the Nyquist frequency, as
+/// [`samples_fft_to_complex_bins`] does. The samples must be verified
+/// already.
+fn windowed_complex_bins(
+    samples: &[f32],
+    window: &[f32],
+) -> Result<Vec<Complex32>, SpectrumAnalyzerError> {
+    let mut bins = windowed_samples_to_complex(samples, window)?;
+    FftImpl::calc_complex(&mut bins);
+    bins.truncate(samples.len() / 2 + 1);
+    Ok(bins)
+}
+
+/// Splits a recording into chunks as described by a [`ChunkConfig`]. The
+/// last chunk is zero padded. A single buffer is reused for every chunk, so
+/// that there is only one allocation for the chunk data, regardless of the
+/// length of the recording. Hence, this is a lending iterator and no
+/// [`Iterator`].
+struct Chunks<'a> {
+    samples: &'a [f32],
+    hop_len: usize,
+    /// Number of chunks that cover the recording.
+    count: usize,
+    /// Index of the next chunk.
+    next_index: usize,
+    buffer: Vec<f32>,
+}
+
+impl<'a> Chunks<'a> {
+    /// ## Errors
+    /// [`SpectrumAnalyzerError::InvalidHopLength`] if the hop length is zero
+    /// and [`SpectrumAnalyzerError::TooFewSamples`] if `samples` is empty.
+    fn new(samples: &'a [f32], config: &ChunkConfig) -> Result<Self, SpectrumAnalyzerError> {
+        if config.hop_len == 0 {
+            return Err(SpectrumAnalyzerError::InvalidHopLength);
+        }
+        if samples.is_empty() {
+            return Err(SpectrumAnalyzerError::TooFewSamples);
+        }
+        Ok(Self {
+            samples,
+            hop_len: config.hop_len,
+            count: chunks_count(samples.len(), config.chunk_len, config.hop_len),
+            next_index: 0,
+            buffer: vec![0.0; config.chunk_len],
+        })
+    }
+
+    /// Returns the index and the samples of the next chunk.
+    fn next_chunk(&mut self) -> Option<(usize, &[f32])> {
+        if self.next_index == self.count {
+            return None;
+        }
+        let index = self.next_index;
+        self.next_index += 1;
+
+        let begin = index * self.hop_len;
+        let end = min(begin + self.buffer.len(), self.samples.len());
+        self.buffer[..end - begin].copy_from_slice(&self.samples[begin..end]);
+        // zero padding, only relevant for the last chunk
+        self.buffer[end - begin..].fill(0.0);
+        Some((index, &self.buffer))
+    }
+}
+
+/// Returns the number of chunks that are required to cover `samples_len`
+/// samples. There is always at least one chunk.
+#[inline]
+const fn chunks_count(samples_len: usize, chunk_len: usize, hop_len: usize) -> usize {
+    if samples_len <= chunk_len {
+        1
+    } else {
+        let remaining = samples_len - chunk_len;
+        // ceiling division
+        1 + (remaining + hop_len - 1) / hop_len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_file_samples_timed() {
+        let samples = vec![0.0; 10000];
+        let config = ChunkConfig {
+            chunk_len: 1024,
+            hop_len: 512,
+            window: WindowType::Hann,
+            frequency_limit: FrequencyLimit::All,
+        };
+        let frames = analyze_file_samples_timed(&samples, 8000, &config, None, None).unwrap();
+        assert_eq!(frames.len(), 19);
+        for (i, frame) in frames.iter().enumerate() {
+            assert_eq!(frame.start_sample, i * 512);
+            assert_eq!(frame.start_seconds, (i * 512) as f32 / 8000.0);
+            assert_eq!(frame.duration_seconds(), 0.128);
+        }
+        // the padded last frame
+        assert_eq!(frames[18].start_sample, 9216);
+        assert_eq!(frames[18].start_seconds, 1.152);
+
+        let index_at =
+            |t: f32| frame_at_time(&frames, t).map(|frame| frame.start_sample / config.hop_len);
+        assert_eq!(index_at(-0.1), None);
+        assert_eq!(index_at(0.0), Some(0));
+        assert_eq!(index_at(0.063), Some(0));
+        assert_eq!(index_at(0.064), Some(1));
+        assert_eq!(index_at(0.07), Some(1));
+        assert_eq!(index_at(1.2), Some(18));
+        assert_eq!(index_at(1.28), None);
+        assert!(frame_at_time(&[], 0.0).is_none());
+    }
+
+    #[test]
+    fn test_verify_compatible() {
+        let config = ChunkConfig {
+            chunk_len: 256,
+            hop_len: 256,
+            window: WindowType::Hann,
+            frequency_limit: FrequencyLimit::All,
+        };
+        let mut frames =
+            analyze_file_samples_timed(&[0.0; 1024], 8000, &config, None, None).unwrap();
+        assert_eq!(verify_compatible(&frames).unwrap().start_sample, 0);
+        assert!(matches!(
+            verify_compatible(&[]),
+            Err(SpectrumAnalyzerError::TooFewSamples)
+        ));
+
+        frames[3].metadata.sampling_rate = 44100;
+        assert!(matches!(
+            verify_compatible(&frames),
+            Err(SpectrumAnalyzerError::IncompatibleMetadata)
+        ));
+    }
+
+    #[test]
+    fn test_sliding_window_max() {
+        let input = [1.0, 3.0, 2.0, 0.0, 0.0, 5.0, 4.0];
+        let mut output = [0.0; 7];
+        let mut deque = VecDeque::new();
+        sliding_window_max(&input, 0, &mut output, &mut deque);
+        assert_eq!(output, input);
+        sliding_window_max(&input, 1, &mut output, &mut deque);
+        assert_eq!(output, [3.0, 3.0, 3.0, 2.0, 5.0, 5.0, 5.0]);
+        sliding_window_max(&input, 10, &mut output, &mut deque);
+        assert_eq!(output, [5.0; 7]);
+    }
+
+    #[test]
+    fn test_chunks_count() {
+        assert_eq!(chunks_count(1, 1024, 1024), 1);
+        assert_eq!(chunks_count(1024, 1024, 1024), 1);
+        assert_eq!(chunks_count(1025, 1024, 1024), 2);
+        assert_eq!(chunks_count(2048, 1024, 1024), 2);
+        assert_eq!(chunks_count(2048, 1024, 512), 3);
+        assert_eq!(chunks_count(2049, 1024, 512), 4);
+    }
+
+    #[test]
+    fn test_overlap() {
+        assert_eq!(Overlap::Percent(50.0).hop_len(1024).unwrap(), 512);
+        assert_eq!(Overlap::Fraction(0.75).hop_len(1024).unwrap(), 256);
+        assert_eq!(Overlap::Percent(0.0).hop_len(1024).unwrap(), 1024);
+        // rounded to the nearest sample
+        assert_eq!(Overlap::Percent(66.6).hop_len(4).unwrap(), 1);
+
+        for overlap in [
+            Overlap::Percent(100.0),
+            Overlap::Percent(-1.0),
+            Overlap::Fraction(1.5),
+            Overlap::Fraction(f32::NAN),
+        ] {
+            assert!(matches!(
+                overlap.hop_len(1024),
+                Err(SpectrumAnalyzerError::InvalidOverlap(_))
+            ));
+        }
+        assert!(matches!(
+            Overlap::Percent(99.9).hop_len(16),
+            Err(SpectrumAnalyzerError::InvalidHopLength)
+        ));
+
+        let config = ChunkConfig::with_overlap(
+            1024,
+            Overlap::Percent(50.0),
+            WindowType::Hann,
+            FrequencyLimit::All,
+        )
+        .unwrap();
+        assert_eq!(config.chunk_len, 1024);
+        assert_eq!(config.hop_len, 512);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let config = ChunkConfig {
+            chunk_len: 4,
+            hop_len: 0,
+            window: WindowType::Rectangular,
+            frequency_limit: FrequencyLimit::All,
+        };
+        let err = analyze_file_samples(&[0.0; 16], 44100, &config, None, None).unwrap_err();
+        assert!(matches!(err, SpectrumAnalyzerError::InvalidHopLength));
+
+        let config = ChunkConfig {
+            hop_len: 4,
+            ..config
+        };
+        let err = analyze_file_samples(&[], 44100, &config, None, None).unwrap_err();
+        assert!(matches!(err, SpectrumAnalyzerError::TooFewSamples));
+
+        let config = ChunkConfig {
+            chunk_len: 3,
+            ..config
+        };
+        let err = analyze_file_samples(&[0.0; 16], 44100, &config, None, None).unwrap_err();
+        assert!(matches!(
+            err,
+            SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo
+        ));
+    }
+
+    /// Analyzing with [`WindowType::Rectangular`] equals analyzing the raw
+    /// samples.
+    #[test]
+    fn test_rectangular_window_equals_raw_samples() {
+        let samples = (0..256)
+            .map(|i| libm::sinf(i as f32 * 0.3) + 0.5 * libm::cosf(i as f32 * 1.7))
+            .collect::<Vec<_>>();
+        let config = ChunkConfig {
+            chunk_len: 256,
+            hop_len: 256,
+            window: WindowType::Rectangular,
+            frequency_limit: FrequencyLimit::All,
+        };
+        let spectra = analyze_file_samples(&samples, 44100, &config, None, None).unwrap();
+        let raw_spectrum =
+            samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+        assert_eq!(spectra.len(), 1);
+        assert_eq!(spectra[0].data(), raw_spectrum.data());
+    }
+
+    /// Windowing into the reused buffer equals windowing each chunk.
+    #[test]
+    fn test_windowed_chunks() {
+        let samples = (0..1000)
+            .map(|i| libm::sinf(i as f32 * 0.3) + 0.5 * libm::cosf(i as f32 * 1.7))
+            .collect::<Vec<_>>();
+        let config = ChunkConfig {
+            chunk_len: 256,
+            hop_len: 128,
+            window: WindowType::Hann,
+            frequency_limit: FrequencyLimit::All,
+        };
+        let spectra = analyze_file_samples(&samples, 44100, &config, None, None).unwrap();
+        let mut chunks = Chunks::new(&samples, &config).unwrap();
+        while let Some((i, chunk)) = chunks.next_chunk() {
+            let spectrum = samples_fft_to_spectrum(
+                &config.window.apply(chunk),
+                44100,
+                FrequencyLimit::All,
+                None,
+            )
+            .unwrap();
+            assert_eq!(spectra[i].data(), spectrum.data());
+        }
+        assert_eq!(spectra.len(), 7);
+    }
+}
@@ -26,9 +26,152 @@ SOFTWARE.
 use self::math::*;
 use crate::error::SpectrumAnalyzerError;
 use crate::frequency::{Frequency, FrequencyValue};
-use crate::scaling::{SpectrumDataStats, SpectrumScalingFunction};
+use crate::scaling::{
+    safe_log10, BinPosition, PositionalScalingFunction, SpectrumDataStats, SpectrumScalingFunction,
+    DEFAULT_LOG_FLOOR,
+};
 use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use core::ops::{Add, AddAssign, Mul, MulAssign};
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// Default for [`Interpolation::Sinc::kernel_bins`].
+pub const DEFAULT_SINC_KERNEL_BINS: usize = 16;
+
+/// Describes how the values of several bins are combined into one value,
+/// e.g., into the value of a frequency band. The right mode depends on the
+/// use case: the energy of a band is the sum of the power of its bins, while
+/// a display usually shows the average or the maximum. See
+/// [`FrequencySpectrum::band_value`],
+/// [`FrequencySpectrum::to_erb_bands_with`],
+/// [`FrequencySpectrum::smooth_log_frequency_with`], and
+/// [`FrequencySpectrum::fractional_octave_smoothed_with`]. The result for no
+/// bins is `0.0`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AggregationMode {
+    /// Sum of the squared values, i.e., the energy. Expects unscaled
+    /// magnitudes.
+    SumPower,
+    /// Sum of the values.
+    SumAmplitude,
+    /// Arithmetic mean of the values.
+    Average,
+    /// Root mean square of the values, i.e., the energy average. Expects
+    /// unscaled magnitudes.
+    Rms,
+    /// Maximum of the values.
+    Max,
+}
+
+impl AggregationMode {
+    /// Combines `values` according to the mode. This is the single
+    /// implementation of all aggregation features.
+    pub(crate) fn aggregate(self, values: impl Iterator<Item = f32>) -> f32 {
+        match self {
+            Self::SumPower => values.map(|val| val * val).sum(),
+            Self::SumAmplitude => values.sum(),
+            Self::Average => mean(values),
+            Self::Rms => libm::sqrtf(mean(values.map(|val| val * val))),
+            Self::Max => values.reduce(f32::max).unwrap_or(0.0),
+        }
+    }
+}
+
+/// Arithmetic mean of `values`, or `0.0` for no values.
+fn mean(values: impl Iterator<Item = f32>) -> f32 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), val| (sum + val, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f32
+    }
+}
+
+/// Interpolation method that is used to calculate values between two
+/// frequency bins. See [`FrequencySpectrum::to_log_axis`] and
+/// [`FrequencySpectrum::interpolate_range`].
+///
+/// [`Interpolation::Cubic`] and [`Interpolation::Sinc`] require equidistant
+/// bins, as for every spectrum from the FFT. For frequency axes with gaps,
+/// e.g., after [`FrequencySpectrum::with_excluded_band`], use
+/// [`Interpolation::Linear`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Linear interpolation between the two neighbouring bins.
+    Linear,
+    /// Cubic (Catmull-Rom) interpolation, using the two neighbouring bins
+    /// and their neighbours. Results in a smoother curve than
+    /// [`Interpolation::Linear`].
+    Cubic,
+    /// Interpolation with the periodic sinc (Dirichlet) kernel, which is the
+    /// interpolation kernel of the DFT, i.e., zero padding in the time
+    /// domain. As the spectrum only contains magnitudes and no phases, this
+    /// is an approximation, which works best for spectra without a window
+    /// function. Results in a display quality curve, e.g., when zooming into
+    /// a narrow frequency range.
+    Sinc {
+        /// Number of bins on each side of the interpolated frequency that
+        /// are taken into account, i.e., the truncation of the kernel. See
+        /// [`DEFAULT_SINC_KERNEL_BINS`].
+        kernel_bins: usize,
+    },
+}
+
+/// Describes all differences between a spectrum and a reference table. See
+/// [`FrequencySpectrum::matches_reference`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MismatchReport {
+    /// `(frequency, expected value, actual value)`-triples of all reference
+    /// entries whose value is not within the tolerance. The frequency is the
+    /// one of the reference table.
+    pub mismatches: Vec<(f32, f32, f32)>,
+    /// Frequencies of the reference table without a bin in the spectrum.
+    pub missing: Vec<f32>,
+    /// Frequencies of the spectrum without an entry in the reference table.
+    pub extra: Vec<f32>,
+}
+
+impl MismatchReport {
+    /// Returns true if there are no differences at all.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.mismatches.is_empty() && self.missing.is_empty() && self.extra.is_empty()
+    }
+}
+
+/// Minimal number of bins below the lowest frequency of a spectrum, so that
+/// [`AnalysisDiagnostics::likely_under_resolved`] is `false`. With fewer bins,
+/// the frequency resolution causes an error of more than 10%.
+const MIN_BINS_BELOW_LOWEST_FREQUENCY: f32 = 10.0;
+
+/// Ratio of the maximum value that a bin must reach to be considered present
+/// in [`AnalysisDiagnostics::lowest_frequency`] (-20 dB).
+const PRESENT_FREQUENCY_RATIO: f32 = 0.1;
+
+/// Diagnostics about the resolution of an analysis, which helps to
+/// understand why results look imprecise. See
+/// [`FrequencySpectrum::analysis_diagnostics`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AnalysisDiagnostics {
+    /// Frequency resolution in Hertz. See
+    /// [`FrequencySpectrum::frequency_resolution`].
+    pub frequency_resolution: f32,
+    /// Duration of the analyzed samples in seconds, i.e.,
+    /// `samples_len / sampling_rate`. Longer windows result in a finer
+    /// frequency resolution.
+    pub window_duration_s: f32,
+    /// The lowest frequency (excluding the DC component) whose value reaches
+    /// at least 10% (-20 dB) of the maximum value, or `None` if there is
+    /// none.
+    pub lowest_frequency: Option<f32>,
+    /// Whether the frequency resolution is likely too coarse to resolve
+    /// [`Self::lowest_frequency`], i.e., there are fewer than ten bins below
+    /// it, which causes an error of more than 10%. Analyze more samples to
+    /// get a finer resolution.
+    pub likely_under_resolved: bool,
+}
 
 /// Convenient wrapper around the processed FFT result which describes each
 /// frequency and its value/amplitude from the analyzed samples. It only
@@ -60,9 +203,15 @@ pub struct FrequencySpectrum {
     /// Average value of frequency value/magnitude/amplitude
     /// corresponding to data in [`FrequencySpectrum::data`].
     average: FrequencyValue,
+    /// Sum of all frequency values. See [`Self::sum`].
+    sum: f32,
+    /// Sum of the squares of all frequency values. See
+    /// [`Self::sum_of_squares`].
+    sum_of_squares: f32,
     /// Median value of frequency value/magnitude/amplitude
-    /// corresponding to data in [`FrequencySpectrum::data`].
-    median: FrequencyValue,
+    /// corresponding to data in [`FrequencySpectrum::data`]. Calculated on
+    /// first access, as it requires sorting. See [`Self::median`].
+    median: LazyStatistic,
     /// Pair of (frequency, frequency value/magnitude/amplitude) where
     /// frequency value is **minimal** inside the spectrum.
     /// Corresponding to data in [`FrequencySpectrum::data`].
@@ -71,6 +220,11 @@ pub struct FrequencySpectrum {
     /// frequency value is **maximum** inside the spectrum.
     /// Corresponding to data in [`FrequencySpectrum::data`].
     max: (Frequency, FrequencyValue),
+    /// RMS of the samples of the analyzed frame. See [`Self::frame_rms`].
+    frame_rms: f32,
+    /// Maximum absolute sample value of the analyzed frame. See
+    /// [`Self::frame_peak`].
+    frame_peak: f32,
 }
 
 impl FrequencySpectrum {
@@ -83,15 +237,16 @@ impl FrequencySpectrum {
     ///                          `data[1].0 - data[0].0`.
     /// * `samples_len` Number of samples. Might be bigger than `data.len()`
     ///                 if the spectrum is obtained with a frequency limit.
-    /// * `working_buffer` Mutable buffer with the same length as `data`
-    ///                    required to calculate certain metrics.
+    /// * `working_buffer` Unused, as the statistics are calculated in a
+    ///                    single pass and the median lazily. Kept for
+    ///                    backwards compatibility; can be empty.
     #[inline]
     #[must_use]
     pub fn new(
         data: Vec<(Frequency, FrequencyValue)>,
         frequency_resolution: f32,
         samples_len: u32,
-        working_buffer: &mut [(Frequency, FrequencyValue)],
+        _working_buffer: &mut [(Frequency, FrequencyValue)],
     ) -> Self {
         debug_assert!(
             data.len() >= 2,
@@ -105,16 +260,75 @@ impl FrequencySpectrum {
             samples_len,
             // default/placeholder values
             average: FrequencyValue::from(-1.0),
-            median: FrequencyValue::from(-1.0),
+            sum: 0.0,
+            sum_of_squares: 0.0,
+            median: LazyStatistic::default(),
             min: (Frequency::from(-1.0), FrequencyValue::from(-1.0)),
             max: (Frequency::from(-1.0), FrequencyValue::from(-1.0)),
+            frame_rms: 0.0,
+            frame_peak: 0.0,
         };
 
         // Important to call this once initially.
-        obj.calc_statistics(working_buffer);
+        obj.calc_statistics();
         obj
     }
 
+    /// Creates a spectrum without any frequency bins. All statistics, such
+    /// as [`Self::average`] and [`Self::max`], are zero. This is a valid
+    /// placeholder, e.g., for a visualizer before the first frame arrived,
+    /// and equal to [`Self::default`].
+    ///
+    /// Statistics, features, scaling, and transformations, such as
+    /// [`Self::shifted`] or [`Self::smooth_log_frequency`], work on an empty
+    /// spectrum and return zero, `None`, or another empty spectrum. Methods
+    /// that return a single frequency or the value at a frequency, such as
+    /// [`Self::max_fr`] or [`Self::freq_val_exact`], have nothing to return
+    /// and panic. Check [`Self::is_empty`] before calling them.
+    #[inline]
+    #[must_use]
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Returns whether the spectrum has no frequency bins, e.g., if it was
+    /// created with [`Self::empty`].
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Creates a new object from an iterator that already yields the
+    /// ([`Frequency`], [`FrequencyValue`])-tuples sorted by frequency in
+    /// ascending order. In contrast to collecting into an intermediate vector
+    /// first, this performs no re-sorting and only allocates the storage of
+    /// the spectrum. The storage is allocated once, if the iterator reports
+    /// an exact size.
+    ///
+    /// In debug builds, this panics if the data is not sorted.
+    ///
+    /// ## Parameters
+    /// * `iter` Iterator over all ([`Frequency`], [`FrequencyValue`])-tuples,
+    ///          sorted by frequency.
+    /// * `frequency_resolution` See [`Self::new`].
+    /// * `samples_len` See [`Self::new`].
+    #[inline]
+    #[must_use]
+    pub fn from_iter_sorted(
+        iter: impl Iterator<Item = (Frequency, FrequencyValue)>,
+        frequency_resolution: f32,
+        samples_len: u32,
+    ) -> Self {
+        let mut data = Vec::with_capacity(iter.size_hint().0);
+        data.extend(iter);
+        debug_assert!(
+            data.windows(2).all(|pair| pair[0].0 <= pair[1].0),
+            "Input data must be sorted by frequency!"
+        );
+        Self::new(data, frequency_resolution, samples_len, &mut [])
+    }
+
     /// Applies the function `scaling_fn` to each element and updates several
     /// metrics about the spectrum, such as `min` and `max`, afterwards
     /// accordingly. It ensures that no value is `NaN` or `Infinity`
@@ -123,32 +337,53 @@ impl FrequencySpectrum {
     ///
     /// ## Parameters
     /// * `scaling_fn` See [`crate::scaling::SpectrumScalingFunction`].
+    /// * `working_buffer` Unused. See [`Self::new`].
     #[inline]
     pub fn apply_scaling_fn(
         &mut self,
         scaling_fn: &SpectrumScalingFunction,
-        working_buffer: &mut [(Frequency, FrequencyValue)],
+        _working_buffer: &mut [(Frequency, FrequencyValue)],
+    ) -> Result<(), SpectrumAnalyzerError> {
+        self.apply_scaling_fn_with_position(|val, stats, _position| scaling_fn(val, stats))
+    }
+
+    /// Like [`Self::apply_scaling_fn`] but the scaling function additionally
+    /// receives the position of each bin. See
+    /// [`crate::scaling::PositionalScalingFunction`].
+    ///
+    /// ## Parameters
+    /// * `scaling_fn` See [`crate::scaling::PositionalScalingFunction`].
+    /// * `working_buffer` Unused. See [`Self::new`].
+    #[inline]
+    pub fn apply_positional_scaling_fn(
+        &mut self,
+        scaling_fn: &PositionalScalingFunction,
+        _working_buffer: &mut [(Frequency, FrequencyValue)],
+    ) -> Result<(), SpectrumAnalyzerError> {
+        self.apply_scaling_fn_with_position(scaling_fn)
+    }
+
+    /// Common implementation of [`Self::apply_scaling_fn`] and
+    /// [`Self::apply_positional_scaling_fn`].
+    fn apply_scaling_fn_with_position(
+        &mut self,
+        scaling_fn: impl Fn(f32, &SpectrumDataStats, BinPosition) -> f32,
     ) -> Result<(), SpectrumAnalyzerError> {
         // This represents statistics about the spectrum in its current state
         // which a scaling function may use to scale values.
         //
         // On the first invocation of this function, these values represent the
         // statistics for the unscaled, hence initial, spectrum.
-        let stats = SpectrumDataStats {
-            min: self.min.1.val(),
-            max: self.max.1.val(),
-            average: self.average.val(),
-            median: self.median.val(),
-            // attention! not necessarily `data.len()`!
-            n: self.samples_len as f32,
-        };
+        let stats = SpectrumDataStats::from(&*self);
 
         // Iterate over the whole spectrum and scale each frequency value.
         // I use a regular for loop instead of for_each(), so that I can
         // early return a result here
-        for (_fr, fr_val) in &mut self.data {
+        let bins_count = self.data.len();
+        for (index, (_fr, fr_val)) in self.data.iter_mut().enumerate() {
             // scale value
-            let scaled_val: f32 = scaling_fn(fr_val.val(), &stats);
+            let position = BinPosition { index, bins_count };
+            let scaled_val: f32 = scaling_fn(fr_val.val(), &stats, position);
 
             // sanity check
             if scaled_val.is_nan() || scaled_val.is_infinite() {
@@ -162,7 +397,7 @@ impl FrequencySpectrum {
             *fr_val = scaled_val.into()
         }
 
-        self.calc_statistics(working_buffer);
+        self.calc_statistics();
         Ok(())
     }
 
@@ -173,11 +408,38 @@ impl FrequencySpectrum {
         self.average
     }
 
-    /// Returns the median frequency value of the spectrum.
+    /// Returns the sum of all frequency values of the spectrum. It is
+    /// calculated in the same pass as [`Self::average`].
+    #[inline]
+    #[must_use]
+    pub const fn sum(&self) -> f32 {
+        self.sum
+    }
+
+    /// Returns the sum of the squares of all frequency values of the
+    /// spectrum, i.e., its energy. It is calculated in the same pass as
+    /// [`Self::average`].
+    #[inline]
+    #[must_use]
+    pub const fn sum_of_squares(&self) -> f32 {
+        self.sum_of_squares
+    }
+
+    /// Returns the median frequency value of the spectrum, i.e., the middle
+    /// value, or the mean of the two middle values for an even number of
+    /// values. It is calculated on first access, as this requires a sorted
+    /// copy of the data, and cached afterwards. Note that applying a scaling
+    /// function accesses it, as [`SpectrumDataStats::median`] is passed to
+    /// the scaling function.
     #[inline]
     #[must_use]
-    pub const fn median(&self) -> FrequencyValue {
-        self.median
+    pub fn median(&self) -> FrequencyValue {
+        let median = self.median.get().unwrap_or_else(|| {
+            let median = self.calc_median();
+            self.median.set(median);
+            median
+        });
+        median.into()
     }
 
     /// Returns the maximum (frequency, frequency value)-pair of the spectrum
@@ -196,6 +458,40 @@ impl FrequencySpectrum {
         self.min
     }
 
+    /// Returns the RMS of the samples of the analyzed frame, i.e., the overall
+    /// level of the frame. In contrast to the frequency values, this is
+    /// independent of the scaling function, e.g.,
+    /// [`crate::scaling::scale_to_zero_to_one`]. It is calculated from the
+    /// samples passed to [`crate::samples_fft_to_spectrum`], i.e., after a
+    /// window function, if the window was applied by the caller. The
+    /// functions of [`crate::spectrogram`] calculate it before applying the
+    /// window. `0.0` for spectra that were not created from samples, e.g.,
+    /// via [`Self::new`].
+    #[inline]
+    #[must_use]
+    pub const fn frame_rms(&self) -> f32 {
+        self.frame_rms
+    }
+
+    /// Returns the maximum absolute sample value of the analyzed frame. See
+    /// [`Self::frame_rms`].
+    #[inline]
+    #[must_use]
+    pub const fn frame_peak(&self) -> f32 {
+        self.frame_peak
+    }
+
+    /// Calculates and stores the level of the analyzed frame. See
+    /// [`Self::frame_rms`] and [`Self::frame_peak`].
+    #[inline]
+    pub(crate) fn set_frame_level(&mut self, samples: &[f32]) {
+        let sum_of_squares = samples.iter().map(|sample| sample * sample).sum::<f32>();
+        self.frame_rms = libm::sqrtf(sum_of_squares / samples.len().max(1) as f32);
+        self.frame_peak = samples
+            .iter()
+            .fold(0.0, |peak: f32, sample| peak.max(sample.abs()));
+    }
+
     /// Returns [`FrequencySpectrum::max().1`] - [`FrequencySpectrum::min().1`],
     /// i.e. the range of the frequency values (not the frequencies itself,
     /// but their amplitudes/values).
@@ -212,6 +508,22 @@ impl FrequencySpectrum {
         &self.data
     }
 
+    /// Returns all frequencies of the underlying data, e.g., for the x-axis
+    /// of a plot. Same order as [`Self::data`].
+    #[inline]
+    #[must_use]
+    pub fn frequencies(&self) -> Vec<f32> {
+        self.data.iter().map(|(fr, _fr_val)| fr.val()).collect()
+    }
+
+    /// Returns all frequency values (magnitudes) of the underlying data,
+    /// e.g., for the y-axis of a plot. Same order as [`Self::data`].
+    #[inline]
+    #[must_use]
+    pub fn magnitudes(&self) -> Vec<f32> {
+        self.data.iter().map(|(_fr, fr_val)| fr_val.val()).collect()
+    }
+
     /// Returns the frequency resolution of this spectrum.
     #[inline]
     #[must_use]
@@ -232,6 +544,9 @@ impl FrequencySpectrum {
     ///
     /// This method could return the Nyquist frequency, if there was no Frequency
     /// limit while obtaining the spectrum.
+    ///
+    /// ## Panics
+    /// If the spectrum is empty. See [`Self::is_empty`].
     #[inline]
     #[must_use]
     pub fn max_fr(&self) -> Frequency {
@@ -243,6 +558,9 @@ impl FrequencySpectrum {
     /// This corresponds to the [`crate::limit::FrequencyLimit`] of the spectrum.
     ///
     /// This method could return the DC component, see [`Self::dc_component`].
+    ///
+    /// ## Panics
+    /// If the spectrum is empty. See [`Self::is_empty`].
     #[inline]
     #[must_use]
     pub fn min_fr(&self) -> Frequency {
@@ -266,7 +584,7 @@ impl FrequencySpectrum {
     #[inline]
     #[must_use]
     pub fn dc_component(&self) -> Option<FrequencyValue> {
-        let (maybe_dc_component, dc_value) = &self.data[0];
+        let (maybe_dc_component, dc_value) = self.data.first()?;
         if maybe_dc_component.val() == 0.0 {
             Some(*dc_value)
         } else {
@@ -285,7 +603,8 @@ impl FrequencySpectrum {
     /// If parameter `search_fr` (frequency) is below the lowest or the maximum
     /// frequency, this function panics! This is because the user provide
     /// the min/max frequency when the spectrum is created and knows about it.
-    /// This is similar to an intended "out of bounds"-access.
+    /// This is similar to an intended "out of bounds"-access. Hence, it also
+    /// panics if the spectrum is empty.
     ///
     /// ## Parameters
     /// - `search_fr` The frequency of that you want the amplitude/value in the spectrum.
@@ -365,7 +684,8 @@ impl FrequencySpectrum {
     ///
     /// ## Panics
     /// If parameter `search_fre` (frequency) is below the lowest or the maximum
-    /// frequency, this function panics!
+    /// frequency, this function panics! Hence, it also panics if the spectrum
+    /// is empty.
     ///
     /// ## Parameters
     /// - `search_fr` The frequency of that you want the amplitude/value in the spectrum.
@@ -444,158 +764,1468 @@ impl FrequencySpectrum {
         self.freq_val_exact(hz)
     }
 
-    /// Returns a [`BTreeMap`] with all value pairs. The key is of type [`u32`]
-    /// because [`f32`] is not [`Ord`].
+    /// Compares two spectra tolerantly, which is useful for tests. Returns
+    /// `true` if both spectra have the same number of bins and each pair of
+    /// bins has approximately the same frequency and frequency value, i.e.,
+    /// they differ by at most `epsilon`.
+    ///
+    /// ## Parameters
+    /// - `other` The spectrum to compare with.
+    /// - `epsilon` Maximum absolute difference of two values to still be
+    ///             considered equal.
     #[inline]
     #[must_use]
-    pub fn to_map(&self) -> BTreeMap<u32, f32> {
-        self.data
-            .iter()
-            .map(|(fr, fr_val)| (fr.val() as u32, fr_val.val()))
+    pub fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.data.len() == other.data.len()
+            && self.data.iter().zip(other.data.iter()).all(
+                |((l_fr, l_fr_val), (r_fr, r_fr_val))| {
+                    float_cmp::approx_eq!(f32, l_fr.val(), r_fr.val(), epsilon = epsilon)
+                        && float_cmp::approx_eq!(
+                            f32,
+                            l_fr_val.val(),
+                            r_fr_val.val(),
+                            epsilon = epsilon
+                        )
+                },
+            )
+    }
+
+    /// Resamples the spectrum onto `num_points` logarithmically spaced
+    /// frequencies between `f_min` and `f_max` (both inclusive), for example
+    /// for Bode-style plots. Each output value is interpolated from the
+    /// spectrum and not aggregated over a band. Frequencies outside of the
+    /// spectrum are clamped to the lowest or highest frequency of the
+    /// spectrum.
+    ///
+    /// ## Parameters
+    /// * `f_min` Lowest frequency of the axis. Must be bigger than zero.
+    /// * `f_max` Highest frequency of the axis. Must be bigger than `f_min`.
+    /// * `num_points` Number of points of the axis. Must be at least two.
+    /// * `interpolation` See [`Interpolation`].
+    ///
+    /// ## Return value
+    /// Vector of `(frequency, value)`-pairs, sorted by frequency.
+    ///
+    /// ## Panics
+    /// If one of the parameters is invalid or the spectrum is empty. In debug
+    /// builds, if `interpolation` requires equidistant bins, but the bins
+    /// are not. See [`Interpolation`].
+    #[inline]
+    #[must_use]
+    pub fn to_log_axis(
+        &self,
+        f_min: f32,
+        f_max: f32,
+        num_points: usize,
+        interpolation: Interpolation,
+    ) -> Vec<(f32, f32)> {
+        assert!(f_min > 0.0, "f_min must be bigger than zero");
+        assert!(f_max > f_min, "f_max must be bigger than f_min");
+        assert!(num_points >= 2, "at least two points are required");
+        debug_assert!(
+            interpolation == Interpolation::Linear || self.is_equidistant(),
+            "Cubic and sinc interpolation require equidistant bins!"
+        );
+
+        let ratio = f_max / f_min;
+        (0..num_points)
+            .map(|i| {
+                let fr = f_min * libm::powf(ratio, i as f32 / (num_points - 1) as f32);
+                let fr_clamped = fr.max(self.min_fr().val()).min(self.max_fr().val());
+                (fr, self.freq_val_interpolated(fr_clamped, interpolation))
+            })
             .collect()
     }
 
-    /// Like [`Self::to_map`] but converts the frequency (x-axis) to [mels]. The
-    /// resulting map contains more results in a higher density the higher the
-    /// mel value gets. This comes from the logarithmic transformation from
-    /// hertz to mels.
+    /// Resamples the spectrum onto `num_points` linearly spaced frequencies
+    /// between `low_hz` and `high_hz` (both inclusive), e.g., to zoom into a
+    /// narrow frequency range of a plot. Frequencies outside of the spectrum
+    /// are clamped to the lowest or highest frequency of the spectrum.
     ///
-    /// [mels]: https://en.wikipedia.org/wiki/Mel_scale
+    /// ## Parameters
+    /// * `low_hz` Lowest frequency of the range.
+    /// * `high_hz` Highest frequency of the range. Must be bigger than
+    ///             `low_hz`.
+    /// * `num_points` Number of points. Must be at least two.
+    /// * `interpolation` See [`Interpolation`].
+    ///
+    /// ## Return value
+    /// Vector of `(frequency, value)`-pairs, sorted by frequency.
+    ///
+    /// ## Panics
+    /// If one of the parameters is invalid or the spectrum is empty. In debug
+    /// builds, if `interpolation` requires equidistant bins, but the bins
+    /// are not. See [`Interpolation`].
     #[inline]
     #[must_use]
-    pub fn to_mel_map(&self) -> BTreeMap<u32, f32> {
-        self.data
-            .iter()
-            .map(|(fr, fr_val)| (hertz_to_mel(fr.val()) as u32, fr_val.val()))
+    pub fn interpolate_range(
+        &self,
+        low_hz: f32,
+        high_hz: f32,
+        num_points: usize,
+        interpolation: Interpolation,
+    ) -> Vec<(f32, f32)> {
+        assert!(high_hz > low_hz, "high_hz must be bigger than low_hz");
+        assert!(num_points >= 2, "at least two points are required");
+        debug_assert!(
+            interpolation == Interpolation::Linear || self.is_equidistant(),
+            "Cubic and sinc interpolation require equidistant bins!"
+        );
+
+        let step = (high_hz - low_hz) / (num_points - 1) as f32;
+        (0..num_points)
+            .map(|i| {
+                let fr = low_hz + i as f32 * step;
+                let fr_clamped = fr.max(self.min_fr().val()).min(self.max_fr().val());
+                (fr, self.freq_val_interpolated(fr_clamped, interpolation))
+            })
             .collect()
     }
 
-    /// Calculates the `min`, `max`, `median`, and `average` of the frequency values/magnitudes/
-    /// amplitudes.
+    /// Fits the values of the spectrum to exactly `k` points, e.g., to get a
+    /// fixed input size for a neural network regardless of the FFT length.
+    /// The `k` points are equally spaced over the whole frequency range of
+    /// the spectrum: the first point is the value of the lowest frequency
+    /// and the last point is the value of the highest frequency. Values in
+    /// between are linearly interpolated from the two neighbouring bins,
+    /// also across gaps in the frequency axis, e.g., after
+    /// [`Self::with_excluded_band`]. No extrapolation takes place. If `k` is
+    /// `1`, the value of the lowest frequency is returned.
+    ///
+    /// ## Parameters
+    /// * `k` Number of points.
     ///
-    /// To do so, it needs to create a sorted copy of the data.
+    /// ## Return value
+    /// Vector of length `k` with the interpolated values. All values are
+    /// equal if the spectrum has a single bin and zero if it is empty.
     #[inline]
-    fn calc_statistics(&mut self, working_buffer: &mut [(Frequency, FrequencyValue)]) {
-        // We create a copy with all data from `self.data` but we sort it by the
-        // frequency value and not the frequency. This way, we can easily find the
-        // median.
+    #[must_use]
+    pub fn fit_to_length(&self, k: usize) -> Vec<f32> {
+        if self.data.len() < 2 {
+            let val = self.data.first().map_or(0.0, |(_fr, fr_val)| fr_val.val());
+            return vec![val; k];
+        }
+        let last_index = self.data.len() - 1;
+        let min_fr = self.min_fr().val();
+        // distance between two output points in Hertz
+        let step = if k > 1 {
+            (self.max_fr().val() - min_fr) / (k - 1) as f32
+        } else {
+            0.0
+        };
+        (0..k)
+            .map(|j| {
+                let fr = min_fr + j as f32 * step;
+                // the bins `index` and `index + 1` enclose `fr`
+                let index = self
+                    .data
+                    .partition_point(|(data_fr, _)| data_fr.val() <= fr)
+                    .saturating_sub(1)
+                    .min(last_index - 1);
+                let ((fr_a, a), (fr_b, b)) = (self.data[index], self.data[index + 1]);
+                let t = ((fr - fr_a.val()) / (fr_b.val() - fr_a.val())).clamp(0.0, 1.0);
+                a.val() + (b.val() - a.val()) * t
+            })
+            .collect()
+    }
 
-        let data_sorted_by_val = {
-            assert_eq!(
-                self.data.len(),
-                working_buffer.len(),
-                "The working buffer must have the same length as `self.data`!"
-            );
+    /// Smooths the spectrum in the log-frequency domain, as audio measurement
+    /// tools do. Each value is replaced by the average of all bins within
+    /// `octave_fraction` octaves around its frequency, i.e., in interval
+    /// `[f * 2^(-octave_fraction/2); f * 2^(octave_fraction/2)]`. Hence, the
+    /// smoothing is perceptually uniform: high frequencies are averaged over
+    /// more bins than low frequencies. The value at `0 Hz` stays unchanged.
+    ///
+    /// ## Parameters
+    /// * `octave_fraction` Width of the averaging window in octaves, e.g.,
+    ///                     `1.0 / 6.0` for 1/6 octave smoothing. Must be
+    ///                     positive.
+    ///
+    /// ## Return value
+    /// New smoothed spectrum with the same frequencies.
+    #[inline]
+    #[must_use]
+    pub fn smooth_log_frequency(&self, octave_fraction: f32) -> Self {
+        self.smooth_log_frequency_with(octave_fraction, AggregationMode::Average)
+    }
 
-            for (i, pair) in self.data.iter().enumerate() {
-                working_buffer[i] = *pair;
-            }
-            working_buffer.sort_by(|(_l_fr, l_fr_val), (_r_fr, r_fr_val)| {
-                // compare by frequency value, from min to max
-                l_fr_val.cmp(r_fr_val)
-            });
+    /// Like [`Self::smooth_log_frequency`] but combines the bins around each
+    /// frequency according to `mode` instead of averaging them, e.g.,
+    /// [`AggregationMode::Max`] for a peak-hold style smoothing.
+    ///
+    /// ## Parameters
+    /// * `octave_fraction` See [`Self::smooth_log_frequency`].
+    /// * `mode` See [`AggregationMode`].
+    ///
+    /// ## Return value
+    /// See [`Self::smooth_log_frequency`].
+    #[inline]
+    #[must_use]
+    pub fn smooth_log_frequency_with(&self, octave_fraction: f32, mode: AggregationMode) -> Self {
+        assert!(octave_fraction > 0.0, "octave_fraction must be positive");
+        self.octave_band_aggregate(octave_fraction, mode)
+    }
 
-            working_buffer
-        };
+    /// Fractional-octave smoothing of a measured frequency response, as in
+    /// acoustics. Like [`Self::smooth_log_frequency`] but each value is
+    /// replaced by the energy average, i.e., the RMS, of all bins within the
+    /// `1 / fraction` octave band centered on its frequency, i.e., in interval
+    /// `[f * 2^(-1/(2 * fraction)); f * 2^(1/(2 * fraction))]`. The energy
+    /// average fills narrow notches, e.g., of comb filtering, less than the
+    /// peaks around them, which matches how they are perceived. Bins whose
+    /// band doesn't cover a neighbouring bin, i.e., low frequencies, and the
+    /// value at `0 Hz` stay unchanged. Expects unscaled magnitudes.
+    ///
+    /// ## Parameters
+    /// * `fraction` Fraction of an octave, e.g., `3.0` for 1/3 octave or `6.0`
+    ///              for 1/6 octave smoothing. Must be positive.
+    ///
+    /// ## Return value
+    /// New smoothed spectrum with the same frequencies.
+    #[inline]
+    #[must_use]
+    pub fn fractional_octave_smoothed(&self, fraction: f32) -> Self {
+        self.fractional_octave_smoothed_with(fraction, AggregationMode::Rms)
+    }
 
-        // sum of all frequency values
-        let sum: f32 = data_sorted_by_val
-            .iter()
-            .map(|fr_val| fr_val.1.val())
-            .fold(0.0, |a, b| a + b);
-
-        // average of all frequency values
-        let avg = sum / data_sorted_by_val.len() as f32;
-        let average: FrequencyValue = avg.into();
-
-        // median of all frequency values
-        let median = {
-            // we assume that data_sorted_by_val.length() is always even, because
-            // it must be a power of 2 (for FFT)
-            let a = data_sorted_by_val[data_sorted_by_val.len() / 2 - 1].1;
-            let b = data_sorted_by_val[data_sorted_by_val.len() / 2].1;
-            (a + b) / 2.0.into()
-        };
+    /// Like [`Self::fractional_octave_smoothed`] but combines the bins of
+    /// each band according to `mode` instead of the energy average, e.g.,
+    /// [`AggregationMode::Average`] for the plain average.
+    ///
+    /// ## Parameters
+    /// * `fraction` See [`Self::fractional_octave_smoothed`].
+    /// * `mode` See [`AggregationMode`].
+    ///
+    /// ## Return value
+    /// See [`Self::fractional_octave_smoothed`].
+    #[inline]
+    #[must_use]
+    pub fn fractional_octave_smoothed_with(&self, fraction: f32, mode: AggregationMode) -> Self {
+        assert!(fraction > 0.0, "fraction must be positive");
+        self.octave_band_aggregate(1.0 / fraction, mode)
+    }
 
-        // Because we sorted the vector from lowest to highest value, the
-        // following lines are correct, i.e., we get min/max value with
-        // the corresponding frequency.
-        let min = data_sorted_by_val[0];
-        let max = data_sorted_by_val[data_sorted_by_val.len() - 1];
+    /// Common implementation of [`Self::smooth_log_frequency_with`] and
+    /// [`Self::fractional_octave_smoothed_with`]: each value is replaced by
+    /// the aggregate of all values within `octave_fraction` octaves around
+    /// its frequency.
+    fn octave_band_aggregate(&self, octave_fraction: f32, mode: AggregationMode) -> Self {
+        if self.is_empty() {
+            return self.empty_like();
+        }
+        let half_width_factor = libm::powf(2.0, octave_fraction / 2.0);
 
-        // check that I get the comparison right (and not from max to min)
-        debug_assert!(min.1 <= max.1, "min must be <= max");
+        let smoothed_data = self.data.iter().enumerate().map(|(i, (fr, fr_val))| {
+            if fr.val() <= 0.0 {
+                return (*fr, *fr_val);
+            }
+            // binary search instead of an index calculation, as the frequency
+            // axis might have gaps
+            let low_fr = fr.val() / half_width_factor;
+            let high_fr = fr.val() * half_width_factor;
+            let begin = self
+                .data
+                .partition_point(|(data_fr, _)| data_fr.val() < low_fr)
+                .min(i);
+            let end = (self
+                .data
+                .partition_point(|(data_fr, _)| data_fr.val() <= high_fr)
+                - 1)
+            .max(i);
+            // the band collapsed to the bin itself
+            if begin == end {
+                return (*fr, *fr_val);
+            }
+            let band = self.data[begin..=end].iter().map(|(_fr, val)| val.val());
+            (*fr, mode.aggregate(band).into())
+        });
+        let mut smoothed =
+            Self::from_iter_sorted(smoothed_data, self.frequency_resolution, self.samples_len);
+        smoothed.frame_rms = self.frame_rms;
+        smoothed.frame_peak = self.frame_peak;
+        smoothed
+    }
 
-        self.min = min;
-        self.max = max;
-        self.average = average;
-        self.median = median;
+    /// Shifts the whole spectrum by `delta_hz` along the frequency axis, e.g.,
+    /// for simple pitch-shift experiments or to align spectra captured at
+    /// slightly different tunings. The value of each bin is moved to the bin
+    /// at `frequency + delta_hz`. If this is between two bins, the value is
+    /// distributed linearly between both. Values that are shifted out of
+    /// range are dropped and bins that receive no value are set to `0.0`.
+    /// See [`Self::shifted_with_fill`] for another fill value.
+    ///
+    /// The bins must be equidistant, i.e., spaced by
+    /// [`Self::frequency_resolution`], as for every spectrum from the FFT.
+    /// Frequency axes with gaps, e.g., after [`Self::with_excluded_band`], or
+    /// logarithmic axes, see [`Self::to_log_axis`], are not supported.
+    ///
+    /// ## Parameters
+    /// * `delta_hz` Shift in Hertz. Might be negative.
+    ///
+    /// ## Return value
+    /// New shifted spectrum with the same frequencies.
+    ///
+    /// ## Panics
+    /// In debug builds, if the bins are not equidistant.
+    #[inline]
+    #[must_use]
+    pub fn shifted(&self, delta_hz: f32) -> Self {
+        self.shifted_with_fill(delta_hz, 0.0)
     }
-}
 
-/*impl FromIterator<(Frequency, FrequencyValue)> for FrequencySpectrum {
+    /// Like [`Self::shifted`] but bins that receive no value are set to
+    /// `fill`, e.g., the noise floor.
+    ///
+    /// ## Panics
+    /// In debug builds, if the bins are not equidistant.
+    #[inline]
+    #[must_use]
+    pub fn shifted_with_fill(&self, delta_hz: f32, fill: f32) -> Self {
+        if self.is_empty() {
+            return self.empty_like();
+        }
+        debug_assert!(self.is_equidistant(), "Shifting requires equidistant bins!");
+        let delta_bins = delta_hz / self.frequency_resolution;
+        let len = self.data.len() as isize;
+        let mut shifted_values = vec![0.0; self.data.len()];
+        let mut is_vacated = vec![true; self.data.len()];
+
+        for (i, (_fr, fr_val)) in self.data.iter().enumerate() {
+            let position = i as f32 + delta_bins;
+            let lower_index = libm::floorf(position);
+            let upper_share = position - lower_index;
+            let lower_index = lower_index as isize;
+            for (index, share) in [
+                (lower_index, 1.0 - upper_share),
+                (lower_index + 1, upper_share),
+            ] {
+                if share > 0.0 && (0..len).contains(&index) {
+                    shifted_values[index as usize] += share * fr_val.val();
+                    is_vacated[index as usize] = false;
+                }
+            }
+        }
+
+        let shifted_data = self
+            .data
+            .iter()
+            .zip(shifted_values.iter().zip(is_vacated))
+            .map(|((fr, _fr_val), (val, is_vacated))| {
+                (*fr, if is_vacated { fill } else { *val }.into())
+            });
+        let mut shifted =
+            Self::from_iter_sorted(shifted_data, self.frequency_resolution, self.samples_len);
+        shifted.frame_rms = self.frame_rms;
+        shifted.frame_peak = self.frame_peak;
+        shifted
+    }
 
+    /// Returns a spectrum without the bins with `low <= frequency <= high`,
+    /// e.g., to ignore known interference, such as mains hum at 50 or 60 Hz.
+    /// All statistics, such as [`Self::average`] and [`Self::max`], are
+    /// calculated without these bins and [`Self::peaks`] doesn't find any
+    /// peak in the excluded band. In contrast to [`Self::stats_in_range`],
+    /// which only considers a range, this removes one.
+    ///
+    /// The frequency axis has a gap afterwards. [`Self::freq_val_exact`]
+    /// interpolates linearly across the gap.
+    ///
+    /// ## Parameters
+    /// * `low` Lowest frequency of the excluded band (inclusive).
+    /// * `high` Highest frequency of the excluded band (inclusive).
+    ///
+    /// ## Panics
+    /// If fewer than two bins remain.
     #[inline]
-    fn from_iter<T: IntoIterator<Item=(Frequency, FrequencyValue)>>(iter: T) -> Self {
-        // 1024 is just a guess: most likely 2048 is a common FFT length,
-        // i.e. 1024 results for the frequency spectrum.
-        let mut vec = Vec::with_capacity(1024);
-        for (fr, val) in iter {
-            vec.push((fr, val))
+    #[must_use]
+    pub fn with_excluded_band(&self, low: f32, high: f32) -> Self {
+        let remaining = self
+            .data
+            .iter()
+            .filter(|(fr, _fr_val)| fr.val() < low || fr.val() > high)
+            .copied()
+            .collect::<Vec<_>>();
+        assert!(
+            remaining.len() >= 2,
+            "At least two frequency bins must remain!"
+        );
+        let mut excluded = Self::from_iter_sorted(
+            remaining.into_iter(),
+            self.frequency_resolution,
+            self.samples_len,
+        );
+        excluded.frame_rms = self.frame_rms;
+        excluded.frame_peak = self.frame_peak;
+        excluded
+    }
+
+    /// Returns the interpolated value at the given frequency, which must lie
+    /// inside the spectrum.
+    fn freq_val_interpolated(&self, search_fr: f32, interpolation: Interpolation) -> f32 {
+        match interpolation {
+            Interpolation::Linear => self.freq_val_exact(search_fr).val(),
+            Interpolation::Cubic => self.freq_val_cubic(search_fr),
+            Interpolation::Sinc { kernel_bins } => self.freq_val_sinc(search_fr, kernel_bins),
         }
+    }
 
-        FrequencySpectrum::new(vec)
+    /// Returns the value at the given frequency, which must lie inside the
+    /// spectrum, interpolated with the periodic sinc kernel. See
+    /// [`Interpolation::Sinc`].
+    fn freq_val_sinc(&self, search_fr: f32, kernel_bins: usize) -> f32 {
+        let position = (search_fr - self.min_fr().val()) / self.frequency_resolution;
+        let index = position as usize;
+        // period of the kernel: the FFT length
+        let period = (self.samples_len as usize).max(self.data.len()) as f32;
+        let begin = (index + 1).saturating_sub(kernel_bins);
+        let end = (index + kernel_bins).min(self.data.len() - 1);
+        (begin..=end)
+            .map(|i| self.data[i].1.val() * dirichlet_kernel(position - i as f32, period))
+            .sum()
     }
-}*/
 
-mod math {
-    // use super::*;
+    /// Returns the Catmull-Rom interpolated value at the given frequency,
+    /// which must lie inside the spectrum.
+    fn freq_val_cubic(&self, search_fr: f32) -> f32 {
+        let last_index = self.data.len() - 1;
+        // bins are equally spaced
+        let position = (search_fr - self.min_fr().val()) / self.frequency_resolution;
+        let index = (position as usize).min(last_index - 1);
+        let t = position - index as f32;
+        let val_at = |i: usize| self.data[i.min(last_index)].1.val();
+        catmull_rom(
+            val_at(index.saturating_sub(1)),
+            val_at(index),
+            val_at(index + 1),
+            val_at(index + 2),
+            t,
+        )
+    }
 
-    /// Calculates the y coordinate of Point C between two given points A and B
-    /// if the x-coordinate of C is known. It does that by putting a linear function
-    /// through the two given points.
+    /// Compares the spectrum with a reference table, e.g., for golden tests.
+    /// Each reference entry is matched with the closest bin of the spectrum.
     ///
     /// ## Parameters
-    /// - `(x1, y1)` x and y of point A
-    /// - `(x2, y2)` x and y of point B
-    /// - `x_coord` x coordinate of searched point C
+    /// * `reference` `(frequency, value)`-pairs, sorted by frequency.
+    /// * `freq_tol_hz` Maximum distance in Hertz between the frequency of a
+    ///                 reference entry and the closest bin.
+    /// * `value_tol` Maximum absolute difference between the value of a
+    ///               reference entry and the value of its bin.
     ///
-    /// ## Return Value
-    /// y coordinate of searched point C
+    /// ## Errors
+    /// A [`MismatchReport`] with every difference if the spectrum doesn't
+    /// match the reference table.
     #[inline]
-    pub fn calculate_y_coord_between_points(
-        (x1, y1): (f32, f32),
-        (x2, y2): (f32, f32),
-        x_coord: f32,
-    ) -> f32 {
-        // e.g. Points (100, 1.0) and (200, 0.0)
-        // y=f(x)=-0.01x + c
-        // 1.0 = f(100) = -0.01x + c
-        // c = 1.0 + 0.01*100 = 2.0
-        // y=f(180)=-0.01*180 + 2.0
+    pub fn matches_reference(
+        &self,
+        reference: &[(f32, f32)],
+        freq_tol_hz: f32,
+        value_tol: f32,
+    ) -> Result<(), MismatchReport> {
+        let mut report = MismatchReport::default();
+        let mut is_matched = vec![false; self.data.len()];
+
+        for (fr, expected) in reference {
+            // index of the first bin with a frequency >= fr
+            let upper_index = self
+                .data
+                .partition_point(|(data_fr, _)| data_fr.val() < *fr);
+            let closest_index = [upper_index.wrapping_sub(1), upper_index]
+                .into_iter()
+                .filter(|&i| i < self.data.len())
+                .min_by(|&a, &b| {
+                    let distance_a = (self.data[a].0.val() - fr).abs();
+                    let distance_b = (self.data[b].0.val() - fr).abs();
+                    distance_a.partial_cmp(&distance_b).unwrap()
+                })
+                .filter(|&i| (self.data[i].0.val() - fr).abs() <= freq_tol_hz);
+            match closest_index {
+                Some(i) => {
+                    is_matched[i] = true;
+                    let actual = self.data[i].1.val();
+                    if (actual - expected).abs() > value_tol {
+                        report.mismatches.push((*fr, *expected, actual));
+                    }
+                }
+                None => report.missing.push(*fr),
+            }
+        }
 
-        // gradient, anstieg
-        let slope = (y2 - y1) / (x2 - x1);
-        // calculate c in y=f(x)=slope * x + c
-        let c = y1 - slope * x1;
+        report.extra = self
+            .data
+            .iter()
+            .zip(is_matched)
+            .filter(|(_, is_matched)| !is_matched)
+            .map(|((fr, _fr_val), _)| fr.val())
+            .collect();
 
-        slope * x_coord + c
+        if report.is_empty() {
+            Ok(())
+        } else {
+            Err(report)
+        }
     }
 
-    /// Converts hertz to [mel](https://en.wikipedia.org/wiki/Mel_scale).
-    pub fn hertz_to_mel(hz: f32) -> f32 {
-        assert!(hz >= 0.0);
-        2595.0 * libm::log10f(1.0 + (hz / 700.0))
+    /// Returns a [`BTreeMap`] with all value pairs. The key is of type [`u32`]
+    /// because [`f32`] is not [`Ord`].
+    #[inline]
+    #[must_use]
+    pub fn to_map(&self) -> BTreeMap<u32, f32> {
+        self.data
+            .iter()
+            .map(|(fr, fr_val)| (fr.val() as u32, fr_val.val()))
+            .collect()
     }
 
-    /// Converts [mel](https://en.wikipedia.org/wiki/Mel_scale) to hertz.
-    pub fn mel_to_hertz(mel: f32) -> f32 {
-        assert!(mel >= 0.0);
-        700.0 * (libm::powf(10.0, mel / 2595.0) - 1.0)
+    /// Returns all value pairs as `(frequency, value)`-tuples of type [`f64`],
+    /// sorted by frequency. This is the format that plotting libraries, such
+    /// as `plotters` and `audio-visualizer`, expect.
+    #[inline]
+    #[must_use]
+    pub fn to_xy_f64(&self) -> Vec<(f64, f64)> {
+        self.to_xy_f64_with(|x, y| (x, y))
     }
 
-    #[cfg(test)]
-    mod tests {
+    /// Returns the frequencies and the frequency values as two parallel
+    /// `ndarray` arrays of the same length, sorted by frequency, e.g., for
+    /// scientific computations. Only available with the `ndarray` feature.
+    ///
+    /// ## Return value
+    /// `(frequencies, values)`. See [`Self::frequencies`] and
+    /// [`Self::magnitudes`].
+    #[cfg(feature = "ndarray")]
+    #[inline]
+    #[must_use]
+    pub fn to_ndarray(&self) -> (ndarray::Array1<f32>, ndarray::Array1<f32>) {
+        (
+            ndarray::Array1::from_vec(self.frequencies()),
+            ndarray::Array1::from_vec(self.magnitudes()),
+        )
+    }
+
+    /// Like [`Self::to_xy_f64`] but applies `transform` to each point, e.g.,
+    /// to convert the frequency to kHz and the value to dB.
+    ///
+    /// ## Parameters
+    /// * `transform` Maps `(frequency, value)` to the plotted point.
+    #[inline]
+    #[must_use]
+    pub fn to_xy_f64_with<F: Fn(f64, f64) -> (f64, f64)>(&self, transform: F) -> Vec<(f64, f64)> {
+        self.data
+            .iter()
+            .map(|(fr, fr_val)| transform(f64::from(fr.val()), f64::from(fr_val.val())))
+            .collect()
+    }
+
+    /// Like [`Self::to_map`] but uses [`Frequency`] as key, which implements
+    /// [`Ord`]. Hence, the frequencies keep their full precision and
+    /// sub-Hertz frequency bins, e.g., for low sampling rates or long FFTs,
+    /// don't collide.
+    #[inline]
+    #[must_use]
+    pub fn to_precise_map(&self) -> BTreeMap<Frequency, FrequencyValue> {
+        self.data.iter().copied().collect()
+    }
+
+    /// Like [`Self::to_map`] but converts the frequency (x-axis) to [mels]. The
+    /// resulting map contains more results in a higher density the higher the
+    /// mel value gets. This comes from the logarithmic transformation from
+    /// hertz to mels.
+    ///
+    /// [mels]: https://en.wikipedia.org/wiki/Mel_scale
+    #[inline]
+    #[must_use]
+    pub fn to_mel_map(&self) -> BTreeMap<u32, f32> {
+        self.data
+            .iter()
+            .map(|(fr, fr_val)| (hertz_to_mel(fr.val()) as u32, fr_val.val()))
+            .collect()
+    }
+
+    /// Groups the bins into `num_bands` bands of equal width on the
+    /// [ERB-rate scale] (equivalent rectangular bandwidth, Glasberg & Moore),
+    /// e.g., for auditory models. The bands cover the range from
+    /// [`Self::min_fr`] to [`Self::max_fr`]. Each bin belongs to exactly one
+    /// band and the energy of a band is the sum of the squared frequency
+    /// values of its bins, as in [`Self::band_energy`].
+    ///
+    /// If the frequency resolution is coarse compared to the bands, e.g., for
+    /// low frequencies or many bands, some bands contain no bin. Their energy
+    /// is `0.0`.
+    ///
+    /// ## Parameters
+    /// * `num_bands` Number of bands.
+    ///
+    /// ## Return value
+    /// `(center frequency in Hertz, energy)`-pairs sorted by frequency.
+    /// Empty, if `num_bands` is zero or the spectrum is empty.
+    ///
+    /// [ERB-rate scale]: https://en.wikipedia.org/wiki/Equivalent_rectangular_bandwidth
+    #[inline]
+    #[must_use]
+    pub fn to_erb_bands(&self, num_bands: usize) -> Vec<(f32, FrequencyValue)> {
+        self.to_erb_bands_with(num_bands, AggregationMode::SumPower)
+    }
+
+    /// Like [`Self::to_erb_bands`] but combines the bins of each band
+    /// according to `mode` instead of summing their energy.
+    ///
+    /// ## Parameters
+    /// * `num_bands` Number of bands.
+    /// * `mode` See [`AggregationMode`].
+    ///
+    /// ## Return value
+    /// See [`Self::to_erb_bands`].
+    #[inline]
+    #[must_use]
+    pub fn to_erb_bands_with(
+        &self,
+        num_bands: usize,
+        mode: AggregationMode,
+    ) -> Vec<(f32, FrequencyValue)> {
+        if num_bands == 0 || self.data.is_empty() {
+            return Vec::new();
+        }
+        let erb_min = hertz_to_erb_rate(self.min_fr().val());
+        let erb_max = hertz_to_erb_rate(self.max_fr().val());
+        let band_width = (erb_max - erb_min) / num_bands as f32;
+        let band_of = |fr: Frequency| {
+            let band = if band_width > 0.0 {
+                ((hertz_to_erb_rate(fr.val()) - erb_min) / band_width) as usize
+            } else {
+                0
+            };
+            // the maximum frequency belongs to the last band
+            band.min(num_bands - 1)
+        };
+
+        // the bins are sorted by frequency, hence, each band is a contiguous
+        // range of bins
+        (0..num_bands)
+            .map(|band| {
+                let begin = self.data.partition_point(|(fr, _)| band_of(*fr) < band);
+                let end = self.data.partition_point(|(fr, _)| band_of(*fr) <= band);
+                let value = mode.aggregate(self.data[begin..end].iter().map(|(_, val)| val.val()));
+                let center = erb_rate_to_hertz(erb_min + (band as f32 + 0.5) * band_width);
+                (center, value.into())
+            })
+            .collect()
+    }
+
+    /// Calculates a simplified psychoacoustic masking threshold, e.g., for
+    /// perceptual coding or denoising experiments. Each bin masks all other
+    /// bins with its level minus `spreading_db_per_bark` per [Bark] of
+    /// distance. The threshold of a bin is the highest masking level that
+    /// any other bin contributes. Components below the threshold are
+    /// considered inaudible. This ignores the absolute threshold of hearing,
+    /// the asymmetry of the spreading function, and the offset between the
+    /// level of the masker and the masking threshold.
+    ///
+    /// The values of the spectrum must be linear magnitudes, i.e., no
+    /// logarithmic (dB) scaling function must be applied. The runtime is
+    /// linear in the number of bins.
+    ///
+    /// ## Parameters
+    /// * `spreading_db_per_bark` Decay of the masking level in dB per Bark,
+    ///                           e.g., `10.0` to `25.0`. Must not be
+    ///                           negative.
+    ///
+    /// ## Return value
+    /// New spectrum with the same frequencies and the masking threshold of
+    /// each bin as linear magnitude. The threshold of a single bin is `0.0`.
+    ///
+    /// ## Panics
+    /// If `spreading_db_per_bark` is negative or `NaN`.
+    ///
+    /// [Bark]: https://en.wikipedia.org/wiki/Bark_scale
+    #[inline]
+    #[must_use]
+    pub fn masking_threshold(&self, spreading_db_per_bark: f32) -> Self {
+        assert!(
+            spreading_db_per_bark >= 0.0,
+            "spreading_db_per_bark must not be negative"
+        );
+        if self.data.len() < 2 {
+            // no other bin that could mask a bin
+            return Self {
+                data: self
+                    .data
+                    .iter()
+                    .map(|(fr, _fr_val)| (*fr, 0.0.into()))
+                    .collect(),
+                ..self.empty_like()
+            };
+        }
+        let barks = self
+            .data
+            .iter()
+            .map(|(fr, _fr_val)| hertz_to_bark(fr.val()))
+            .collect::<Vec<_>>();
+        // attenuation of the masking level from bin i to bin i + 1
+        let decays = barks
+            .windows(2)
+            .map(|pair| libm::powf(10.0, -spreading_db_per_bark * (pair[1] - pair[0]) / 20.0))
+            .collect::<Vec<_>>();
+
+        // As the masking level decays exponentially with the distance, the
+        // highest contribution of all lower and of all higher bins can be
+        // propagated bin by bin.
+        let mut threshold = vec![0.0_f32; self.data.len()];
+        for i in 1..self.data.len() {
+            threshold[i] = threshold[i - 1].max(self.data[i - 1].1.val()) * decays[i - 1];
+        }
+        let mut from_above = 0.0_f32;
+        for i in (0..self.data.len() - 1).rev() {
+            from_above = from_above.max(self.data[i + 1].1.val()) * decays[i];
+            threshold[i] = threshold[i].max(from_above);
+        }
+
+        let threshold_data = self
+            .data
+            .iter()
+            .zip(threshold)
+            .map(|((fr, _fr_val), val)| (*fr, val.into()));
+        let mut masking_threshold =
+            Self::from_iter_sorted(threshold_data, self.frequency_resolution, self.samples_len);
+        masking_threshold.frame_rms = self.frame_rms;
+        masking_threshold.frame_peak = self.frame_peak;
+        masking_threshold
+    }
+
+    /// Calculates the statistics (min, max, average, median) of all bins with
+    /// `low_hz <= frequency <= high_hz`, e.g., of the speech band of a full
+    /// spectrum. The boundaries are found with a binary search and no
+    /// sub-spectrum is created; only the median requires a temporary copy of
+    /// the values in the range. The values equal the statistics of a
+    /// spectrum that is created with the equivalent
+    /// [`crate::FrequencyLimit`].
+    ///
+    /// ## Parameters
+    /// * `low_hz` Lowest frequency of the range (inclusive).
+    /// * `high_hz` Highest frequency of the range (inclusive).
+    ///
+    /// ## Return value
+    /// The statistics or `None`, if the range contains no bin. `n` is
+    /// [`Self::samples_len`], as for [`SpectrumDataStats::from`].
+    #[inline]
+    #[must_use]
+    pub fn stats_in_range(&self, low_hz: f32, high_hz: f32) -> Option<SpectrumDataStats> {
+        let begin = self.data.partition_point(|(fr, _fr_val)| fr.val() < low_hz);
+        let end = self
+            .data
+            .partition_point(|(fr, _fr_val)| fr.val() <= high_hz);
+        if begin >= end {
+            return None;
+        }
+
+        let mut vals = self.data[begin..end]
+            .iter()
+            .map(|(_fr, fr_val)| *fr_val)
+            .collect::<Vec<_>>();
+        vals.sort_unstable();
+        let sum = vals.iter().map(|fr_val| fr_val.val()).sum::<f32>();
+        let sum_of_squares = vals
+            .iter()
+            .map(|fr_val| fr_val.val() * fr_val.val())
+            .sum::<f32>();
+        let median = median_of_sorted(FrequencyValue::as_f32_slice(&vals));
+
+        Some(SpectrumDataStats {
+            min: vals[0].val(),
+            max: vals[vals.len() - 1].val(),
+            average: sum / vals.len() as f32,
+            median,
+            sum,
+            sum_of_squares,
+            n: self.samples_len as f32,
+        })
+    }
+
+    /// Returns the average of the frequency values without the smallest and
+    /// the biggest ones. In contrast to [`Self::average`], a few outliers,
+    /// such as a single strong peak, hardly affect it. Hence, it is a robust
+    /// reference for normalizations.
+    ///
+    /// ## Parameters
+    /// * `fraction` Fraction of the values that is dropped at each end, in
+    ///              interval `[0.0; 0.5)`. `0.0` is the plain average.
+    ///
+    /// ## Panics
+    /// If `fraction` is not in interval `[0.0; 0.5)` or the spectrum is
+    /// empty.
+    #[inline]
+    #[must_use]
+    pub fn trimmed_mean(&self, fraction: f32) -> FrequencyValue {
+        assert!(
+            (0.0..0.5).contains(&fraction),
+            "fraction must be in interval [0.0; 0.5)!"
+        );
+        assert!(!self.is_empty(), "the spectrum must not be empty!");
+        let vals = self.sorted_values();
+        let trimmed_len = (vals.len() as f32 * fraction) as usize;
+        let kept = &vals[trimmed_len..vals.len() - trimmed_len];
+        (kept.iter().sum::<f32>() / kept.len() as f32).into()
+    }
+
+    /// Returns the median absolute deviation (MAD) of the frequency values,
+    /// i.e., the median of the absolute differences between each value and
+    /// the median of all values. It is a robust measure of the spread of the
+    /// values, e.g., of the noise floor, that a few outliers hardly affect.
+    ///
+    /// ## Panics
+    /// If the spectrum is empty.
+    #[inline]
+    #[must_use]
+    pub fn mad(&self) -> FrequencyValue {
+        assert!(!self.is_empty(), "the spectrum must not be empty!");
+        let vals = self.sorted_values();
+        let median = median_of_sorted(&vals);
+        let mut deviations = vals
+            .iter()
+            .map(|val| (val - median).abs())
+            .collect::<Vec<_>>();
+        deviations.sort_unstable_by(f32::total_cmp);
+        median_of_sorted(&deviations).into()
+    }
+
+    /// Returns diagnostics about the resolution of the analysis. See
+    /// [`AnalysisDiagnostics`]. The values of the spectrum should be linear
+    /// magnitudes, i.e., no logarithmic (dB) scaling function should be
+    /// applied.
+    #[inline]
+    #[must_use]
+    pub fn analysis_diagnostics(&self) -> AnalysisDiagnostics {
+        let threshold = self.max.1.val() * PRESENT_FREQUENCY_RATIO;
+        let lowest_frequency = self
+            .data
+            .iter()
+            .find(|(fr, fr_val)| fr.val() > 0.0 && fr_val.val() > 0.0 && fr_val.val() >= threshold)
+            .map(|(fr, _fr_val)| fr.val());
+        AnalysisDiagnostics {
+            frequency_resolution: self.frequency_resolution,
+            window_duration_s: 1.0 / self.frequency_resolution,
+            lowest_frequency,
+            likely_under_resolved: lowest_frequency.map_or(false, |fr| {
+                fr < MIN_BINS_BELOW_LOWEST_FREQUENCY * self.frequency_resolution
+            }),
+        }
+    }
+
+    /// Returns the energy of all bins with `low <= frequency <= high`, i.e.,
+    /// the sum of the squared frequency values. Equivalent to
+    /// [`Self::band_value`] with [`AggregationMode::SumPower`].
+    #[inline]
+    #[must_use]
+    pub fn band_energy(&self, low: f32, high: f32) -> f32 {
+        self.band_value(low, high, AggregationMode::SumPower)
+    }
+
+    /// Combines the values of all bins with `low <= frequency <= high` into
+    /// one value. See [`AggregationMode`].
+    ///
+    /// ## Parameters
+    /// * `low` Lowest frequency of the band (inclusive).
+    /// * `high` Highest frequency of the band (inclusive).
+    /// * `mode` See [`AggregationMode`].
+    #[inline]
+    #[must_use]
+    pub fn band_value(&self, low: f32, high: f32, mode: AggregationMode) -> f32 {
+        mode.aggregate(
+            self.data
+                .iter()
+                .filter(|(fr, _fr_val)| fr.val() >= low && fr.val() <= high)
+                .map(|(_fr, fr_val)| fr_val.val()),
+        )
+    }
+
+    /// Returns the ratio of the energy inside `band_a` and the energy inside
+    /// `band_b`, e.g., `energy(0-300 Hz) / energy(300-4000 Hz)`. Each band is a
+    /// `(low, high)` frequency tuple; both bounds are inclusive. See
+    /// [`Self::band_energy`].
+    ///
+    /// If `band_b` contains no energy, the result is `0.0` if `band_a` has no
+    /// energy either. Otherwise, it is [`f32::MAX`].
+    #[inline]
+    #[must_use]
+    pub fn band_energy_ratio(&self, band_a: (f32, f32), band_b: (f32, f32)) -> f32 {
+        let energy_a = self.band_energy(band_a.0, band_a.1);
+        let energy_b = self.band_energy(band_b.0, band_b.1);
+        if energy_b != 0.0 {
+            energy_a / energy_b
+        } else if energy_a == 0.0 {
+            0.0
+        } else {
+            f32::MAX
+        }
+    }
+
+    /// Returns the spectral centroid in Hertz, i.e., the frequency values
+    /// weighted mean of the frequencies. It describes the "center of mass"
+    /// of the spectrum. Returns `0.0` if all frequency values are zero.
+    ///
+    /// More information: <https://en.wikipedia.org/wiki/Spectral_centroid>
+    #[inline]
+    #[must_use]
+    pub fn spectral_centroid(&self) -> f32 {
+        let (weighted_sum, sum) =
+            self.data
+                .iter()
+                .fold((0.0, 0.0), |(weighted_sum, sum), (fr, fr_val)| {
+                    (weighted_sum + fr.val() * fr_val.val(), sum + fr_val.val())
+                });
+        if sum == 0.0 {
+            0.0
+        } else {
+            weighted_sum / sum
+        }
+    }
+
+    /// Returns the spectral flux relative to the spectrum of the previous
+    /// frame, i.e., the sum of the increases of all frequency values. Decreases
+    /// are ignored (half-wave rectification). Hence, it is big for onsets,
+    /// such as the beginning of a note, but not for offsets. See
+    /// [`crate::spectrogram::detect_onsets`].
+    ///
+    /// ## Parameters
+    /// * `previous` Spectrum of the previous frame with the same frequency
+    ///              bins.
+    ///
+    /// ## Panics
+    /// If the spectra have a different number of bins.
+    #[inline]
+    #[must_use]
+    pub fn spectral_flux(&self, previous: &Self) -> f32 {
+        assert_eq!(
+            self.data.len(),
+            previous.data.len(),
+            "The spectra must have the same number of bins!"
+        );
+        self.data
+            .iter()
+            .zip(&previous.data)
+            .map(|((_fr, fr_val), (_previous_fr, previous_fr_val))| {
+                (fr_val.val() - previous_fr_val.val()).max(0.0)
+            })
+            .sum()
+    }
+
+    /// Returns the spectral flatness (Wiener entropy), i.e., the ratio of the
+    /// geometric mean and the arithmetic mean of the power spectrum. The value
+    /// is in interval `[0.0; 1.0]`: values close to `1.0` indicate a
+    /// noise-like spectrum and values close to `0.0` a tonal spectrum. Returns
+    /// `0.0` if all frequency values are zero.
+    ///
+    /// Zero bins are clamped to [`crate::scaling::DEFAULT_LOG_FLOOR`] for the
+    /// geometric mean, see [`crate::scaling::safe_log10`].
+    ///
+    /// More information: <https://en.wikipedia.org/wiki/Spectral_flatness>
+    #[inline]
+    #[must_use]
+    pub fn spectral_flatness(&self) -> f32 {
+        let len = self.data.len() as f32;
+        let (log_sum, sum) = self
+            .data
+            .iter()
+            .map(|(_fr, fr_val)| fr_val.val() * fr_val.val())
+            .fold((0.0, 0.0), |(log_sum, sum), power| {
+                (log_sum + safe_log10(power, DEFAULT_LOG_FLOOR), sum + power)
+            });
+        if sum == 0.0 {
+            return 0.0;
+        }
+        let geometric_mean = libm::powf(10.0, log_sum / len);
+        let arithmetic_mean = sum / len;
+        geometric_mean / arithmetic_mean
+    }
+
+    /// Returns the spectral rolloff, i.e., the lowest frequency for which the
+    /// energy of all bins up to and including that frequency is at least
+    /// `fraction` (e.g. `0.85`) of the total energy of the spectrum. Returns
+    /// the lowest frequency of the spectrum if it has no energy at all.
+    ///
+    /// ## Parameters
+    /// - `fraction` Fraction of the total energy in interval `[0.0; 1.0]`.
+    ///
+    /// ## Panics
+    /// If the spectrum is empty. See [`Self::is_empty`].
+    #[inline]
+    #[must_use]
+    pub fn spectral_rolloff(&self, fraction: f32) -> Frequency {
+        debug_assert!((0.0..=1.0).contains(&fraction));
+        let total_energy = self.band_energy(self.min_fr().val(), self.max_fr().val());
+        let threshold = total_energy * fraction;
+        let mut energy = 0.0;
+        for (fr, fr_val) in &self.data {
+            energy += fr_val.val() * fr_val.val();
+            if energy >= threshold {
+                return *fr;
+            }
+        }
+        self.max_fr()
+    }
+
+    /// Returns the cumulative energy curve of the spectrum, i.e., for each
+    /// bin the fraction of the total energy of all bins up to and including
+    /// that frequency. The curve is monotonically non-decreasing and ends at
+    /// `1.0`. This is the basis for [`Self::spectral_rolloff`] and
+    /// [`Self::occupied_bandwidth`]. All fractions are `0.0` if the spectrum
+    /// has no energy at all.
+    #[inline]
+    #[must_use]
+    pub fn cumulative_energy(&self) -> Vec<(Frequency, f32)> {
+        let total_energy = self
+            .data
+            .iter()
+            .map(|(_fr, fr_val)| fr_val.val() * fr_val.val())
+            .sum::<f32>();
+        let mut energy = 0.0;
+        self.data
+            .iter()
+            .map(|(fr, fr_val)| {
+                energy += fr_val.val() * fr_val.val();
+                let fraction = if total_energy == 0.0 {
+                    0.0
+                } else {
+                    // the division of the same sums makes the last value
+                    // exactly 1.0
+                    energy / total_energy
+                };
+                (*fr, fraction)
+            })
+            .collect()
+    }
+
+    /// Returns the occupied bandwidth, i.e., the band that contains `fraction`
+    /// (e.g. `0.99`) of the total energy of the spectrum, with equal parts of
+    /// the remaining energy below and above the band.
+    ///
+    /// ## Parameters
+    /// * `fraction` Fraction of the total energy in interval `[0.0; 1.0]`.
+    ///
+    /// ## Return value
+    /// Lower and upper frequency (both inclusive) of the band. The whole
+    /// spectrum if it has no energy at all and `None` if it is empty.
+    #[inline]
+    #[must_use]
+    pub fn occupied_bandwidth(&self, fraction: f32) -> Option<(Frequency, Frequency)> {
+        debug_assert!((0.0..=1.0).contains(&fraction));
+        let cumulative = self.cumulative_energy();
+        let (_fr, total) = cumulative.last()?;
+        if *total == 0.0 {
+            return Some((self.min_fr(), self.max_fr()));
+        }
+        let outside = (1.0 - fraction) / 2.0;
+        // the lower edge is the first bin above the energy below the band,
+        // the upper edge the first bin that reaches the energy up to the band
+        let low = cumulative
+            .iter()
+            .find(|(_fr, energy)| *energy > outside)
+            .map_or_else(|| self.max_fr(), |(fr, _energy)| *fr);
+        let high = cumulative
+            .iter()
+            .find(|(_fr, energy)| *energy >= 1.0 - outside)
+            .map_or_else(|| self.max_fr(), |(fr, _energy)| *fr);
+        Some((low, high))
+    }
+
+    /// Returns the spectral slope (tilt), i.e., the slope of the least-squares
+    /// regression line through all `(frequency, magnitude in dB)`-pairs, e.g.,
+    /// for timbre or material classification. Negative values indicate that
+    /// the magnitude decreases towards high frequencies. Returns `0.0` if all
+    /// bins have the same frequency.
+    ///
+    /// Expects unscaled magnitudes; `20 * log10` is applied to each
+    /// magnitude. Zero bins are clamped to
+    /// [`crate::scaling::DEFAULT_LOG_FLOOR`], see
+    /// [`crate::scaling::safe_log10`].
+    ///
+    /// ## Return value
+    /// The slope in dB/Hz.
+    #[inline]
+    #[must_use]
+    pub fn spectral_slope(&self) -> f32 {
+        let points = self
+            .data
+            .iter()
+            .map(|(fr, fr_val)| {
+                (
+                    f64::from(fr.val()),
+                    f64::from(20.0 * safe_log10(fr_val.val(), DEFAULT_LOG_FLOOR)),
+                )
+            })
+            .collect::<Vec<_>>();
+        let len = points.len() as f64;
+        let mean_x = points.iter().map(|(x, _y)| x).sum::<f64>() / len;
+        let mean_y = points.iter().map(|(_x, y)| y).sum::<f64>() / len;
+        // sums of the centered values in f64, as the squared frequencies are
+        // large
+        let (covariance, variance) =
+            points
+                .iter()
+                .fold((0.0, 0.0), |(covariance, variance), (x, y)| {
+                    let dx = x - mean_x;
+                    (covariance + dx * (y - mean_y), variance + dx * dx)
+                });
+        if variance <= 0.0 {
+            return 0.0;
+        }
+        (covariance / variance) as f32
+    }
+
+    /// Returns the highest frequency whose magnitude exceeds the noise floor
+    /// by at least `snr_db`, e.g., as "brightness ceiling" of a voice, i.e.,
+    /// up to which frequency the harmonic structure is clearly above the
+    /// noise. The noise floor is estimated by the [`Self::median`] of the
+    /// spectrum, as most bins of a harmonic spectrum contain noise.
+    ///
+    /// ## Parameters
+    /// * `snr_db` Minimum distance in dB to the noise floor, e.g., `20.0`.
+    ///            Expects unscaled magnitudes, i.e., `20 * log10` is applied
+    ///            to the ratio of magnitude and noise floor.
+    ///
+    /// ## Return value
+    /// `None` if no bin exceeds the noise floor by `snr_db`.
+    #[inline]
+    #[must_use]
+    pub fn highest_significant_frequency(&self, snr_db: f32) -> Option<Frequency> {
+        let threshold = self.median().val() * libm::powf(10.0, snr_db / 20.0);
+        self.data
+            .iter()
+            .rev()
+            .find(|(_fr, fr_val)| fr_val.val() > threshold)
+            .map(|(fr, _fr_val)| *fr)
+    }
+
+    /// Returns whether all neighbouring bins are [`Self::frequency_resolution`]
+    /// apart, i.e., the frequency axis has no gaps. Used in debug assertions
+    /// of operations that calculate with bin indices instead of frequencies.
+    fn is_equidistant(&self) -> bool {
+        self.data.windows(2).all(|bins| {
+            let distance = bins[1].0.val() - bins[0].0.val();
+            (distance - self.frequency_resolution).abs() <= self.frequency_resolution * 1e-3
+        })
+    }
+
+    /// Returns a copy of all frequency values, sorted in ascending order.
+    fn sorted_values(&self) -> Vec<f32> {
+        let mut vals = self
+            .data
+            .iter()
+            .map(|(_fr, fr_val)| fr_val.val())
+            .collect::<Vec<_>>();
+        vals.sort_unstable_by(f32::total_cmp);
+        vals
+    }
+
+    /// Applies `f` to each frequency value and updates the statistics
+    /// afterwards.
+    fn apply_to_values(&mut self, f: impl Fn(f32) -> f32) {
+        self.apply_to_bins(|_fr, fr_val| f(fr_val));
+    }
+
+    /// Applies `f` to each (frequency, frequency value)-pair, which returns
+    /// the new frequency value, and updates the statistics afterwards.
+    pub(crate) fn apply_to_bins(&mut self, f: impl Fn(f32, f32) -> f32) {
+        for (fr, fr_val) in &mut self.data {
+            *fr_val = f(fr.val(), fr_val.val()).into();
+        }
+        self.calc_statistics();
+    }
+
+    /// Calculates the `min`, `max`, and `average` of the frequency
+    /// values/magnitudes/amplitudes in a single pass and resets the median,
+    /// which is calculated lazily. See [`Self::median`].
+    #[inline]
+    fn calc_statistics(&mut self) {
+        // an empty spectrum has the same statistics as `Self::empty`
+        let first = self.data.first().copied().unwrap_or_default();
+        let mut min = first;
+        let mut max = first;
+        let mut sum = 0.0;
+        let mut sum_of_squares = 0.0;
+        for pair in &self.data {
+            // the first minimum and the last maximum, regarding the frequency,
+            // as with a stable sort by the frequency value
+            if pair.1 < min.1 {
+                min = *pair;
+            }
+            if pair.1 >= max.1 {
+                max = *pair;
+            }
+            sum += pair.1.val();
+            sum_of_squares += pair.1.val() * pair.1.val();
+        }
+
+        self.min = min;
+        self.max = max;
+        self.average = if self.data.is_empty() {
+            0.0
+        } else {
+            sum / self.data.len() as f32
+        }
+        .into();
+        self.sum = sum;
+        self.sum_of_squares = sum_of_squares;
+        self.median = LazyStatistic::default();
+    }
+
+    /// Returns a spectrum without any bins but with the frequency resolution,
+    /// the number of samples, and the frame level of `self`. Transformations
+    /// return this for an empty spectrum.
+    fn empty_like(&self) -> Self {
+        Self {
+            frequency_resolution: self.frequency_resolution,
+            samples_len: self.samples_len,
+            frame_rms: self.frame_rms,
+            frame_peak: self.frame_peak,
+            ..Self::default()
+        }
+    }
+
+    /// Calculates the median of the frequency values. See [`Self::median`].
+    fn calc_median(&self) -> f32 {
+        if self.data.is_empty() {
+            return 0.0;
+        }
+        median_of_sorted(&self.sorted_values())
+    }
+}
+
+/// A statistic of a [`FrequencySpectrum`] that is calculated on first access
+/// and cached afterwards. It stores the bits of the `f32` value in an atomic,
+/// so that [`FrequencySpectrum`] stays `Send` and `Sync`. If two threads
+/// access it at the same time, both calculate the same value.
+struct LazyStatistic(AtomicU32);
+
+impl LazyStatistic {
+    /// Bits of a NaN, which never is a valid statistic, that mark a value that
+    /// is not calculated yet.
+    const NOT_CALCULATED: u32 = u32::MAX;
+
+    /// Returns the value, if it was calculated already.
+    fn get(&self) -> Option<f32> {
+        let bits = self.0.load(Ordering::Relaxed);
+        (bits != Self::NOT_CALCULATED).then(|| f32::from_bits(bits))
+    }
+
+    /// Caches the calculated value.
+    fn set(&self, val: f32) {
+        self.0.store(val.to_bits(), Ordering::Relaxed);
+    }
+}
+
+impl Default for LazyStatistic {
+    /// A value that is not calculated yet.
+    fn default() -> Self {
+        Self(AtomicU32::new(Self::NOT_CALCULATED))
+    }
+}
+
+impl Debug for LazyStatistic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("LazyStatistic").field(&self.get()).finish()
+    }
+}
+
+impl MulAssign<f32> for FrequencySpectrum {
+    /// Multiplies each frequency value by `rhs`, e.g., for a gain adjustment,
+    /// and updates the statistics afterwards.
+    #[inline]
+    fn mul_assign(&mut self, rhs: f32) {
+        self.apply_to_values(|fr_val| fr_val * rhs);
+    }
+}
+
+impl Mul<f32> for FrequencySpectrum {
+    type Output = Self;
+
+    /// See [`MulAssign`].
+    #[inline]
+    fn mul(mut self, rhs: f32) -> Self {
+        self *= rhs;
+        self
+    }
+}
+
+impl AddAssign<f32> for FrequencySpectrum {
+    /// Adds `rhs` to each frequency value, e.g., an offset to a spectrum in
+    /// decibels, and updates the statistics afterwards.
+    #[inline]
+    fn add_assign(&mut self, rhs: f32) {
+        self.apply_to_values(|fr_val| fr_val + rhs);
+    }
+}
+
+impl Add<f32> for FrequencySpectrum {
+    type Output = Self;
+
+    /// See [`AddAssign`].
+    #[inline]
+    fn add(mut self, rhs: f32) -> Self {
+        self += rhs;
+        self
+    }
+}
+
+impl TryFrom<BTreeMap<usize, f32>> for FrequencySpectrum {
+    type Error = SpectrumAnalyzerError;
+
+    /// Creates a spectrum from a map of frequencies (in Hertz) to frequency
+    /// values, e.g., an old map based result. As the map contains no
+    /// metadata, the frequency resolution is the distance between the first
+    /// two frequencies and [`Self::samples_len`] is `2 * (map.len() - 1)`,
+    /// which is correct if the map covers all bins from the DC component to
+    /// the Nyquist frequency. Note that the integer keys might be truncated
+    /// frequencies.
+    ///
+    /// ## Errors
+    /// [`SpectrumAnalyzerError::TooFewFrequencyBins`] if the map has fewer
+    /// than two entries, and [`SpectrumAnalyzerError::NaNValuesNotSupported`]
+    /// or [`SpectrumAnalyzerError::InfinityValuesNotSupported`] for invalid
+    /// values.
+    fn try_from(map: BTreeMap<usize, f32>) -> Result<Self, Self::Error> {
+        if map.len() < 2 {
+            return Err(SpectrumAnalyzerError::TooFewFrequencyBins);
+        }
+        if map.values().any(|val| val.is_nan()) {
+            return Err(SpectrumAnalyzerError::NaNValuesNotSupported);
+        }
+        if map.values().any(|val| val.is_infinite()) {
+            return Err(SpectrumAnalyzerError::InfinityValuesNotSupported);
+        }
+
+        let mut frequencies = map.keys();
+        // at least two keys, sorted ascending
+        let frequency_resolution = match (frequencies.next(), frequencies.next()) {
+            (Some(first), Some(second)) => (second - first) as f32,
+            _ => unreachable!(),
+        };
+        let samples_len = 2 * (map.len() as u32 - 1);
+        // the map is sorted by frequency
+        Ok(Self::from_iter_sorted(
+            map.into_iter()
+                .map(|(fr, fr_val)| ((fr as f32).into(), fr_val.into())),
+            frequency_resolution,
+            samples_len,
+        ))
+    }
+}
+
+mod math {
+    // use super::*;
+
+    /// Calculates the y coordinate of Point C between two given points A and B
+    /// if the x-coordinate of C is known. It does that by putting a linear function
+    /// through the two given points.
+    ///
+    /// ## Parameters
+    /// - `(x1, y1)` x and y of point A
+    /// - `(x2, y2)` x and y of point B
+    /// - `x_coord` x coordinate of searched point C
+    ///
+    /// ## Return Value
+    /// y coordinate of searched point C
+    #[inline]
+    pub fn calculate_y_coord_between_points(
+        (x1, y1): (f32, f32),
+        (x2, y2): (f32, f32),
+        x_coord: f32,
+    ) -> f32 {
+        // e.g. Points (100, 1.0) and (200, 0.0)
+        // y=f(x)=-0.01x + c
+        // 1.0 = f(100) = -0.01x + c
+        // c = 1.0 + 0.01*100 = 2.0
+        // y=f(180)=-0.01*180 + 2.0
+
+        // gradient, anstieg
+        let slope = (y2 - y1) / (x2 - x1);
+        // calculate c in y=f(x)=slope * x + c
+        let c = y1 - slope * x1;
+
+        slope * x_coord + c
+    }
+
+    /// Calculates the y coordinate between the points `p1` and `p2` with a
+    /// [Catmull-Rom spline](https://en.wikipedia.org/wiki/Cubic_Hermite_spline#Catmull%E2%80%93Rom_spline).
+    /// All four points must be equally spaced.
+    ///
+    /// ## Parameters
+    /// - `p0`..`p3` y coordinates of four consecutive points
+    /// - `t` relative position between `p1` (`0.0`) and `p2` (`1.0`)
+    ///
+    /// ## Return Value
+    /// y coordinate of searched point
+    #[inline]
+    pub fn catmull_rom(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+        let t2 = t * t;
+        let t3 = t2 * t;
+        0.5 * (2.0 * p1
+            + (p2 - p0) * t
+            + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+            + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+    }
+
+    /// Converts hertz to [mel](https://en.wikipedia.org/wiki/Mel_scale).
+    pub fn hertz_to_mel(hz: f32) -> f32 {
+        assert!(hz >= 0.0);
+        2595.0 * libm::log10f(1.0 + (hz / 700.0))
+    }
+
+    /// Converts [mel](https://en.wikipedia.org/wiki/Mel_scale) to hertz.
+    pub fn mel_to_hertz(mel: f32) -> f32 {
+        assert!(mel >= 0.0);
+        700.0 * (libm::powf(10.0, mel / 2595.0) - 1.0)
+    }
+
+    /// Calculates the periodic sinc (Dirichlet) kernel
+    /// `sin(PI * x) / (n * sin(PI * x / n))`, i.e., the interpolation kernel
+    /// of a DFT of length `n` at the distance of `x` bins.
+    pub fn dirichlet_kernel(x: f32, n: f32) -> f32 {
+        let denominator = n * libm::sinf(core::f32::consts::PI * x / n);
+        if denominator.abs() < 1e-6 {
+            1.0
+        } else {
+            libm::sinf(core::f32::consts::PI * x) / denominator
+        }
+    }
+
+    /// Returns the median of non-empty values that are sorted in ascending
+    /// order. For an even number of values, this is the average of the two
+    /// middle values.
+    pub fn median_of_sorted(vals: &[f32]) -> f32 {
+        let mid = vals.len() / 2;
+        if vals.len() % 2 == 0 {
+            (vals[mid - 1] + vals[mid]) / 2.0
+        } else {
+            vals[mid]
+        }
+    }
+
+    /// Converts hertz to the [ERB-rate scale](https://en.wikipedia.org/wiki/Equivalent_rectangular_bandwidth)
+    /// (Glasberg & Moore), i.e., the number of equivalent rectangular
+    /// bandwidths below `hz`.
+    pub fn hertz_to_erb_rate(hz: f32) -> f32 {
+        assert!(hz >= 0.0);
+        21.4 * libm::log10f(1.0 + 0.004_37 * hz)
+    }
+
+    /// Converts the [ERB-rate scale](https://en.wikipedia.org/wiki/Equivalent_rectangular_bandwidth)
+    /// to hertz. Inverse of [`hertz_to_erb_rate`].
+    pub fn erb_rate_to_hertz(erb_rate: f32) -> f32 {
+        assert!(erb_rate >= 0.0);
+        (libm::powf(10.0, erb_rate / 21.4) - 1.0) / 0.004_37
+    }
+
+    /// Converts hertz to the [Bark scale](https://en.wikipedia.org/wiki/Bark_scale)
+    /// (Zwicker & Terhardt), i.e., the critical band rate in interval
+    /// `[0; 25]`.
+    pub fn hertz_to_bark(hz: f32) -> f32 {
+        assert!(hz >= 0.0);
+        13.0 * libm::atanf(0.000_76 * hz) + 3.5 * libm::atanf((hz / 7500.0) * (hz / 7500.0))
+    }
+
+    #[cfg(test)]
+    mod tests {
         use super::*;
 
         #[test]
@@ -620,6 +2250,19 @@ mod math {
             );
         }
 
+        #[test]
+        fn test_catmull_rom() {
+            assert_eq!(catmull_rom(0.0, 1.0, 2.0, 3.0, 0.0), 1.0);
+            assert_eq!(catmull_rom(0.0, 1.0, 2.0, 3.0, 1.0), 2.0);
+            // linear data stays linear
+            float_cmp::assert_approx_eq!(
+                f32,
+                catmull_rom(0.0, 1.0, 2.0, 3.0, 0.25),
+                1.25,
+                ulps = 3
+            );
+        }
+
         #[test]
         fn test_mel() {
             float_cmp::assert_approx_eq!(f32, hertz_to_mel(0.0), 0.0, epsilon = 0.1);
@@ -630,7 +2273,40 @@ mod math {
 
             float_cmp::assert_approx_eq!(f32, conv(0.0), 0.0, epsilon = 0.1);
             float_cmp::assert_approx_eq!(f32, conv(1000.0), 1000.0, epsilon = 0.1);
-            float_cmp::assert_approx_eq!(f32, conv(10000.0), 10000.0, epsilon = 0.1);
+            float_cmp::assert_approx_eq!(f32, conv(10000.0), 10000.0, epsilon = 0.1);
+        }
+
+        #[test]
+        fn test_dirichlet_kernel() {
+            float_cmp::assert_approx_eq!(f32, dirichlet_kernel(0.0, 1024.0), 1.0);
+            float_cmp::assert_approx_eq!(f32, dirichlet_kernel(1.0, 1024.0), 0.0, epsilon = 1e-6);
+            float_cmp::assert_approx_eq!(f32, dirichlet_kernel(-3.0, 1024.0), 0.0, epsilon = 1e-6);
+            // approximately sinc(0.5) = 2 / PI
+            float_cmp::assert_approx_eq!(
+                f32,
+                dirichlet_kernel(0.5, 1024.0),
+                core::f32::consts::FRAC_2_PI,
+                epsilon = 1e-3
+            );
+        }
+
+        #[test]
+        fn test_bark() {
+            float_cmp::assert_approx_eq!(f32, hertz_to_bark(0.0), 0.0, epsilon = 0.01);
+            float_cmp::assert_approx_eq!(f32, hertz_to_bark(1000.0), 8.51, epsilon = 0.01);
+            float_cmp::assert_approx_eq!(f32, hertz_to_bark(10000.0), 22.4, epsilon = 0.1);
+        }
+
+        #[test]
+        fn test_erb_rate() {
+            float_cmp::assert_approx_eq!(f32, hertz_to_erb_rate(0.0), 0.0, epsilon = 0.01);
+            float_cmp::assert_approx_eq!(f32, hertz_to_erb_rate(1000.0), 15.62, epsilon = 0.01);
+
+            let conv = |hz: f32| erb_rate_to_hertz(hertz_to_erb_rate(hz));
+
+            float_cmp::assert_approx_eq!(f32, conv(0.0), 0.0, epsilon = 0.1);
+            float_cmp::assert_approx_eq!(f32, conv(1000.0), 1000.0, epsilon = 0.1);
+            float_cmp::assert_approx_eq!(f32, conv(10000.0), 10000.0, epsilon = 1.0);
         }
     }
 }
@@ -638,6 +2314,7 @@ mod math {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tests::fixtures::spectrum_from_pairs;
 
     /// Test if a frequency spectrum can be sent to other threads.
     #[test]
@@ -649,6 +2326,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_lazy_median() {
+        let data = [
+            (0.0, 4.0),
+            (50.0, 1.0),
+            (100.0, 9.0),
+            (150.0, 1.0),
+            (200.0, 9.0),
+            (250.0, 3.0),
+        ]
+        .iter()
+        .map(|(fr, val)| (Frequency::from(*fr), FrequencyValue::from(*val)))
+        .collect::<Vec<_>>();
+
+        // only min and max: the median is never calculated
+        let spectrum = FrequencySpectrum::new(data.clone(), 50.0, 12, &mut []);
+        assert_eq!(spectrum.min(), (50.0.into(), 1.0.into()));
+        assert_eq!(spectrum.max(), (200.0.into(), 9.0.into()));
+        assert_eq!(spectrum.average().val(), 27.0 / 6.0);
+        assert_eq!(spectrum.median.get(), None);
+
+        // the median is calculated on first access and cached
+        let mut spectrum = FrequencySpectrum::new(data, 50.0, 12, &mut []);
+        assert_eq!(spectrum.median().val(), 3.5);
+        assert_eq!(spectrum.median.get(), Some(3.5));
+        assert_eq!(spectrum.median().val(), 3.5);
+
+        // and recalculated after the values changed
+        spectrum *= 2.0;
+        assert_eq!(spectrum.median.get(), None);
+        assert_eq!(spectrum.median().val(), 7.0);
+
+        let _: &dyn Sync = &spectrum;
+    }
+
     #[test]
     #[allow(clippy::cognitive_complexity)]
     fn test_spectrum_basic() {
@@ -748,11 +2460,8 @@ mod tests {
             );
             assert_eq!(200.0 - 0.0, spectrum.range().val(), "range() must work");
             assert_eq!(80.55556, spectrum.average().val(), "average() must work");
-            assert_eq!(
-                (50 + 100) as f32 / 2.0,
-                spectrum.median().val(),
-                "median() must work"
-            );
+            // middle value of the nine sorted values
+            assert_eq!(100.0, spectrum.median().val(), "median() must work");
             assert_eq!(
                 50.0,
                 spectrum.frequency_resolution(),
@@ -982,6 +2691,612 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_to_log_axis() {
+        // resonance peak at 1000 Hz
+        let data = (0..=2205)
+            .map(|i| i as f32 * 10.0)
+            .map(|fr| (fr, 1.0 / (1.0 + ((fr - 1000.0) / 50.0).powi(2))));
+        let spectrum = spectrum_from_pairs(data, 10.0, 4410);
+
+        for interpolation in [Interpolation::Linear, Interpolation::Cubic] {
+            let axis = spectrum.to_log_axis(20.0, 20000.0, 31, interpolation);
+            assert_eq!(axis.len(), 31);
+            float_cmp::assert_approx_eq!(f32, axis[0].0, 20.0, epsilon = 1e-3);
+            float_cmp::assert_approx_eq!(f32, axis[30].0, 20000.0, epsilon = 1e-1);
+            // geometric spacing
+            for points in axis.windows(2) {
+                float_cmp::assert_approx_eq!(
+                    f32,
+                    points[1].0 / points[0].0,
+                    libm::powf(10.0, 0.1),
+                    epsilon = 1e-4
+                );
+            }
+            // peak at 20 * 10^1.7 = ~1002 Hz
+            let (peak_index, _) = axis
+                .iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| a.1.partial_cmp(&b.1).unwrap())
+                .unwrap();
+            assert_eq!(peak_index, 17);
+            float_cmp::assert_approx_eq!(f32, axis[17].1, 0.998, epsilon = 1e-2);
+
+            // clamped to the value of the highest frequency
+            let axis = spectrum.to_log_axis(10000.0, 40000.0, 3, interpolation);
+            float_cmp::assert_approx_eq!(f32, axis[2].0, 40000.0, epsilon = 1e-1);
+            float_cmp::assert_approx_eq!(f32, axis[2].1, spectrum.data()[2205].1.val(), ulps = 3);
+        }
+
+        // between bins
+        let cubic = spectrum.to_log_axis(995.0, 1005.0, 3, Interpolation::Cubic);
+        let linear = spectrum.to_log_axis(995.0, 1005.0, 3, Interpolation::Linear);
+        // linear interpolation cuts off the top of the peak
+        assert!(cubic[0].1 > linear[0].1);
+        assert!(cubic[0].1 < 1.0);
+    }
+
+    #[test]
+    fn test_spectrum_data_stats_from_spectrum() {
+        let mut spectrum_vector = vec![
+            (0.0_f32.into(), 5.0_f32.into()),
+            (50.0.into(), 50.0.into()),
+            (100.0.into(), 100.0.into()),
+            (150.0.into(), 1.0.into()),
+        ];
+        let data = spectrum_vector.clone();
+        let mut spectrum = FrequencySpectrum::new(
+            spectrum_vector.clone(),
+            50.0,
+            // a frequency limit was applied
+            16,
+            &mut spectrum_vector,
+        );
+
+        let stats = SpectrumDataStats::from(&spectrum);
+        assert_eq!(stats.min, 1.0);
+        assert_eq!(stats.max, 100.0);
+        assert_eq!(stats.average, 39.0);
+        assert_eq!(stats.median, 27.5);
+        assert_eq!(stats.n, 16.0);
+
+        let manually_scaled = data
+            .iter()
+            .map(|(_fr, val)| crate::scaling::scale_to_zero_to_one(val.val(), &stats))
+            .collect::<Vec<_>>();
+        spectrum
+            .apply_scaling_fn(&crate::scaling::scale_to_zero_to_one, &mut spectrum_vector)
+            .unwrap();
+        let scaled = spectrum
+            .data()
+            .iter()
+            .map(|(_fr, val)| val.val())
+            .collect::<Vec<_>>();
+        assert_eq!(scaled, manually_scaled);
+    }
+
+    #[test]
+    fn test_frequencies_and_magnitudes() {
+        let mut spectrum_vector = vec![
+            (0.0_f32.into(), 5.0_f32.into()),
+            (50.0.into(), 50.0.into()),
+            (100.0.into(), 1.0.into()),
+        ];
+        let spectrum =
+            FrequencySpectrum::new(spectrum_vector.clone(), 50.0, 4, &mut spectrum_vector);
+        assert_eq!(spectrum.frequencies(), [0.0, 50.0, 100.0]);
+        assert_eq!(spectrum.magnitudes(), [5.0, 50.0, 1.0]);
+    }
+
+    #[test]
+    fn test_smooth_log_frequency() {
+        // impulses at 1000 Hz and 8000 Hz
+        let data = (0..=1000).map(|i| i as f32 * 10.0).map(|fr| {
+            let val = if fr == 1000.0 || fr == 8000.0 {
+                1.0
+            } else {
+                0.0
+            };
+            (fr, val)
+        });
+        let spectrum = spectrum_from_pairs(data, 10.0, 2000);
+
+        let smoothed = spectrum.smooth_log_frequency(1.0 / 3.0);
+        assert_eq!(smoothed.frequencies(), spectrum.frequencies());
+        let width_around = |low: f32, high: f32| {
+            smoothed
+                .data()
+                .iter()
+                .filter(|(fr, val)| fr.val() >= low && fr.val() <= high && val.val() > 0.0)
+                .count()
+        };
+        let width_low = width_around(500.0, 2000.0);
+        let width_high = width_around(4000.0, 10000.0);
+        // a 1/3 octave at 1000 Hz is ~23 bins wide
+        assert!((20..=26).contains(&width_low), "{}", width_low);
+        // eight times wider at 8000 Hz
+        assert!(width_high > 7 * width_low && width_high < 9 * width_low);
+        // energy is spread, not amplified
+        assert!(smoothed.max().1.val() < 0.1);
+
+        // constant spectrum stays constant
+        let spectrum = spectrum_from_pairs((0..=100).map(|i| (i as f32 * 10.0, 3.0)), 10.0, 200);
+        let smoothed = spectrum.smooth_log_frequency(1.0 / 6.0);
+        assert!(smoothed.magnitudes().iter().all(|&val| val == 3.0));
+    }
+
+    #[test]
+    fn test_fractional_octave_smoothed() {
+        // comb filter, i.e., a signal plus its delayed copy: notches every
+        // 200 Hz from 100 Hz on
+        let resolution = 44100.0 / 8192.0;
+        let data = (0..=4096).map(|i| {
+            let fr = i as f32 * resolution;
+            let phase = 2.0 * core::f32::consts::PI * fr / 200.0;
+            let (re, im) = (1.0 + 0.9 * libm::cosf(phase), 0.9 * libm::sinf(phase));
+            (fr.into(), libm::sqrtf(re * re + im * im).into())
+        });
+        let spectrum = FrequencySpectrum::from_iter_sorted(data, resolution, 8192);
+        let smoothed = spectrum.fractional_octave_smoothed(3.0);
+        assert_eq!(smoothed.frequencies(), spectrum.frequencies());
+
+        // ratio of the deepest notch to the highest peak
+        let notch_depth = |spectrum: &FrequencySpectrum, low: f32, high: f32| {
+            let vals = spectrum
+                .data()
+                .iter()
+                .filter(|(fr, _val)| fr.val() >= low && fr.val() <= high)
+                .map(|(_fr, val)| val.val())
+                .collect::<Vec<_>>();
+            let min = vals.iter().copied().fold(f32::MAX, f32::min);
+            let max = vals.iter().copied().fold(0.0, f32::max);
+            min / max
+        };
+        assert!(notch_depth(&spectrum, 200.0, 600.0) < 0.1);
+        assert!(notch_depth(&spectrum, 8000.0, 12000.0) < 0.1);
+        let depth_low = notch_depth(&smoothed, 200.0, 600.0);
+        let depth_high = notch_depth(&smoothed, 8000.0, 12000.0);
+        // the band is narrower than the distance of the notches at low
+        // frequencies but covers several notches at high frequencies
+        assert!(depth_low < 0.5, "{}", depth_low);
+        assert!(depth_high > 0.9, "{}", depth_high);
+
+        // the lowest bins stay unchanged
+        assert_eq!(&smoothed.data()[..4], &spectrum.data()[..4]);
+    }
+
+    #[test]
+    fn test_shifted() {
+        // peak at 1000 Hz
+        let data = (0..=400)
+            .map(|i| i as f32 * 10.0)
+            .map(|fr| (fr, 1.0 / (1.0 + ((fr - 1000.0) / 20.0).powi(2))));
+        let spectrum = spectrum_from_pairs(data, 10.0, 800);
+
+        let shifted = spectrum.shifted(500.0);
+        assert_eq!(shifted.frequencies(), spectrum.frequencies());
+        assert_eq!(shifted.max().0.val(), 1500.0);
+        assert_eq!(shifted.max().1.val(), 1.0);
+        // the energy is constant
+        float_cmp::assert_approx_eq!(
+            f32,
+            shifted.sum_of_squares(),
+            spectrum.sum_of_squares(),
+            epsilon = spectrum.sum_of_squares() * 0.01
+        );
+        // vacated bins
+        assert_eq!(shifted.data()[0].1.val(), 0.0);
+        assert_eq!(
+            spectrum.shifted_with_fill(500.0, 0.5).data()[49].1.val(),
+            0.5
+        );
+
+        // fractional shift: the peak is distributed between 1000 Hz and 1010 Hz
+        let shifted = spectrum.shifted(5.0);
+        float_cmp::assert_approx_eq!(
+            f32,
+            shifted.freq_val_exact(1000.0).val(),
+            shifted.freq_val_exact(1010.0).val(),
+            epsilon = 1e-5
+        );
+        float_cmp::assert_approx_eq!(
+            f32,
+            shifted.sum_of_squares(),
+            spectrum.sum_of_squares(),
+            epsilon = spectrum.sum_of_squares() * 0.05
+        );
+
+        // negative shift
+        let shifted = spectrum.shifted(-300.0);
+        assert_eq!(shifted.max().0.val(), 700.0);
+    }
+
+    #[test]
+    fn test_matches_reference() {
+        let mut spectrum_vector = vec![
+            (0.0_f32.into(), 5.0_f32.into()),
+            (50.0.into(), 50.0.into()),
+            (100.0.into(), 100.0.into()),
+            (150.0.into(), 1.0.into()),
+        ];
+        let spectrum =
+            FrequencySpectrum::new(spectrum_vector.clone(), 50.0, 8, &mut spectrum_vector);
+
+        let reference = [(0.0, 5.0), (50.5, 50.1), (100.0, 99.95), (149.9, 1.0)];
+        assert_eq!(spectrum.matches_reference(&reference, 1.0, 0.2), Ok(()));
+
+        let reference = [(0.0, 5.0), (50.0, 40.0), (120.0, 100.0), (150.0, 1.5)];
+        let report = spectrum
+            .matches_reference(&reference, 1.0, 0.2)
+            .unwrap_err();
+        assert_eq!(
+            report,
+            MismatchReport {
+                mismatches: vec![(50.0, 40.0, 50.0), (150.0, 1.5, 1.0)],
+                missing: vec![120.0],
+                extra: vec![100.0],
+            }
+        );
+        assert!(!report.is_empty());
+    }
+
+    #[test]
+    fn test_scalar_operators() {
+        let mut spectrum_vector = vec![
+            (0.0_f32.into(), 5.0_f32.into()),
+            (50.0.into(), 50.0.into()),
+            (100.0.into(), 1.0.into()),
+            (150.0.into(), 10.0.into()),
+        ];
+        let spectrum =
+            FrequencySpectrum::new(spectrum_vector.clone(), 50.0, 8, &mut spectrum_vector);
+
+        let mut spectrum = spectrum * 2.0;
+        assert_eq!(spectrum.magnitudes(), [10.0, 100.0, 2.0, 20.0]);
+        assert_eq!(spectrum.max(), (50.0.into(), 100.0.into()));
+        assert_eq!(spectrum.min(), (100.0.into(), 2.0.into()));
+        assert_eq!(spectrum.average().val(), 33.0);
+        assert_eq!(spectrum.median().val(), 15.0);
+
+        spectrum += 1.0;
+        assert_eq!(spectrum.magnitudes(), [11.0, 101.0, 3.0, 21.0]);
+        assert_eq!(spectrum.average().val(), 34.0);
+
+        spectrum *= -1.0;
+        assert_eq!(spectrum.max(), (100.0.into(), (-3.0).into()));
+        assert_eq!(spectrum.min(), (50.0.into(), (-101.0).into()));
+
+        let spectrum = spectrum + 101.0;
+        assert_eq!(spectrum.min().1.val(), 0.0);
+        assert_eq!(spectrum.frequencies(), [0.0, 50.0, 100.0, 150.0]);
+    }
+
+    #[test]
+    fn test_fit_to_length() {
+        let spectrum =
+            spectrum_from_pairs((0..5).map(|i| (i as f32 * 10.0, i as f32 * 2.0)), 10.0, 8);
+
+        assert!(spectrum.fit_to_length(0).is_empty());
+        assert_eq!(spectrum.fit_to_length(1), [0.0]);
+        assert_eq!(spectrum.fit_to_length(2), [0.0, 8.0]);
+        assert_eq!(spectrum.fit_to_length(3), [0.0, 4.0, 8.0]);
+        assert_eq!(spectrum.fit_to_length(5), [0.0, 2.0, 4.0, 6.0, 8.0]);
+        assert_eq!(
+            spectrum.fit_to_length(9),
+            [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]
+        );
+    }
+
+    #[test]
+    fn test_gapped_frequency_axis() {
+        // the value equals the frequency, with a gap between 300 and 500 Hz
+        let spectrum = spectrum_from_pairs(
+            (0..=100).map(|i| (i as f32 * 10.0, i as f32 * 10.0)),
+            10.0,
+            200,
+        );
+        let gapped = spectrum.with_excluded_band(300.0, 500.0);
+        assert!(!gapped.is_equidistant());
+
+        // the points are equally spaced in Hertz, also across the gap
+        assert_eq!(
+            gapped.fit_to_length(11),
+            [0.0, 100.0, 200.0, 300.0, 400.0, 500.0, 600.0, 700.0, 800.0, 900.0, 1000.0]
+        );
+
+        // the bands only contain bins within the band, i.e., the average of
+        // a linear spectrum stays close to the center of the band
+        let smoothed = gapped.smooth_log_frequency(1.0);
+        let expected = spectrum.smooth_log_frequency(1.0);
+        for (fr, fr_val) in smoothed.data() {
+            if fr.val() >= 800.0 {
+                assert_eq!(*fr_val, expected.freq_val_exact(fr.val()));
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Shifting requires equidistant bins!")]
+    fn test_gapped_frequency_axis_shifted() {
+        let _ = spectrum_from_pairs((0..=100).map(|i| (i as f32 * 10.0, 1.0)), 10.0, 200)
+            .with_excluded_band(300.0, 500.0)
+            .shifted(10.0);
+    }
+
+    #[test]
+    fn test_try_from_map() {
+        let map = [(0, 1.0), (10, 5.0), (20, 3.0), (30, 2.0)]
+            .into_iter()
+            .collect::<BTreeMap<usize, f32>>();
+        let spectrum = FrequencySpectrum::try_from(map.clone()).unwrap();
+        assert_eq!(spectrum.data().len(), 4);
+        assert_eq!(spectrum.frequency_resolution(), 10.0);
+        assert_eq!(spectrum.samples_len(), 6);
+        assert_eq!(spectrum.max().0.val(), 10.0);
+        assert_eq!(spectrum.max().1.val(), 5.0);
+        assert_eq!(spectrum.min().1.val(), 1.0);
+        assert_eq!(spectrum.freq_val_exact(20.0).val(), 3.0);
+        // round trip
+        let round_trip = spectrum
+            .to_map()
+            .into_iter()
+            .map(|(fr, fr_val)| (fr as usize, fr_val))
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(round_trip, map);
+
+        assert!(matches!(
+            FrequencySpectrum::try_from(BTreeMap::new()),
+            Err(SpectrumAnalyzerError::TooFewFrequencyBins)
+        ));
+        assert!(matches!(
+            FrequencySpectrum::try_from(core::iter::once((0, 1.0)).collect::<BTreeMap<_, _>>()),
+            Err(SpectrumAnalyzerError::TooFewFrequencyBins)
+        ));
+        assert!(matches!(
+            FrequencySpectrum::try_from(
+                [(0, 1.0), (1, f32::NAN)]
+                    .into_iter()
+                    .collect::<BTreeMap<_, _>>()
+            ),
+            Err(SpectrumAnalyzerError::NaNValuesNotSupported)
+        ));
+    }
+
+    #[test]
+    fn test_from_iter_sorted() {
+        let mut spectrum_vector = vec![
+            (0.0_f32.into(), 5.0_f32.into()),
+            (50.0.into(), 50.0.into()),
+            (100.0.into(), 100.0.into()),
+            (150.0.into(), 1.0.into()),
+        ];
+        let data = spectrum_vector.clone();
+        let expected = FrequencySpectrum::new(
+            spectrum_vector.clone(),
+            50.0,
+            spectrum_vector.len() as _,
+            &mut spectrum_vector,
+        );
+
+        let spectrum =
+            FrequencySpectrum::from_iter_sorted(data.iter().copied(), 50.0, data.len() as _);
+        assert_eq!(spectrum.data(), expected.data());
+        assert_eq!(spectrum.max(), expected.max());
+        assert_eq!(spectrum.median(), expected.median());
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn test_from_iter_sorted_panics_on_unsorted_input() {
+        let _ = FrequencySpectrum::from_iter_sorted(
+            [(50.0_f32.into(), 5.0_f32.into()), (0.0.into(), 50.0.into())].into_iter(),
+            50.0,
+            2,
+        );
+    }
+
+    #[test]
+    fn test_approx_eq() {
+        let mut spectrum_vector = vec![
+            (0.0_f32.into(), 5.0_f32.into()),
+            (50.0.into(), 200.0.into()),
+        ];
+        let spectrum = FrequencySpectrum::new(
+            spectrum_vector.clone(),
+            50.0,
+            spectrum_vector.len() as _,
+            &mut spectrum_vector,
+        );
+
+        let mut other_vector = vec![
+            (0.0_f32.into(), 5.01_f32.into()),
+            (50.0.into(), 199.99.into()),
+        ];
+        let other = FrequencySpectrum::new(
+            other_vector.clone(),
+            50.0,
+            other_vector.len() as _,
+            &mut other_vector,
+        );
+
+        let mut longer_vector = vec![
+            (0.0_f32.into(), 5.0_f32.into()),
+            (50.0.into(), 200.0.into()),
+            (100.0.into(), 0.0.into()),
+        ];
+        let longer = FrequencySpectrum::new(
+            longer_vector.clone(),
+            50.0,
+            longer_vector.len() as _,
+            &mut longer_vector,
+        );
+
+        assert!(spectrum.approx_eq(&spectrum, 0.0));
+        assert!(spectrum.approx_eq(&other, 0.1));
+        assert!(other.approx_eq(&spectrum, 0.1));
+        assert!(!spectrum.approx_eq(&other, 0.001));
+        assert!(!spectrum.approx_eq(&longer, 0.1));
+    }
+
+    #[test]
+    fn test_highest_significant_frequency() {
+        // harmonics of 100 Hz with decreasing magnitudes above a noise floor
+        // of 1.0
+        let harmonics = [1000.0, 500.0, 50.0, 12.0, 8.0];
+        let data = (0..16)
+            .map(|i| {
+                let val = if (1..=harmonics.len()).contains(&i) {
+                    harmonics[i - 1]
+                } else {
+                    1.0
+                };
+                (Frequency::from(i as f32 * 100.0), FrequencyValue::from(val))
+            })
+            .collect::<Vec<_>>();
+        let spectrum = FrequencySpectrum::new(data, 100.0, 32, &mut []);
+        assert_eq!(spectrum.median().val(), 1.0);
+
+        // 20 dB: factor 10
+        assert_eq!(
+            spectrum.highest_significant_frequency(20.0),
+            Some(400.0.into())
+        );
+        // 40 dB: factor 100
+        assert_eq!(
+            spectrum.highest_significant_frequency(40.0),
+            Some(200.0.into())
+        );
+        assert_eq!(spectrum.highest_significant_frequency(80.0), None);
+    }
+
+    #[test]
+    fn test_spectral_features() {
+        let mut spectrum_vector = vec![
+            (0.0_f32.into(), 0.0_f32.into()),
+            (100.0.into(), 3.0.into()),
+            (200.0.into(), 4.0.into()),
+            (300.0.into(), 0.0.into()),
+        ];
+        let spectrum = FrequencySpectrum::new(
+            spectrum_vector.clone(),
+            100.0,
+            spectrum_vector.len() as _,
+            &mut spectrum_vector,
+        );
+
+        assert_eq!(spectrum.band_energy(0.0, 300.0), 25.0);
+        assert_eq!(spectrum.band_energy(50.0, 150.0), 9.0);
+        assert_eq!(
+            spectrum.band_energy_ratio((0.0, 100.0), (150.0, 300.0)),
+            9.0 / 16.0
+        );
+        assert_eq!(
+            spectrum.band_energy_ratio((0.0, 100.0), (300.0, 300.0)),
+            f32::MAX
+        );
+        assert_eq!(spectrum.band_energy_ratio((0.0, 0.0), (300.0, 300.0)), 0.0);
+        assert_eq!(spectrum.spectral_centroid(), (300.0 + 800.0) / 7.0);
+        assert_eq!(spectrum.spectral_rolloff(0.3).val(), 100.0);
+        assert_eq!(spectrum.spectral_rolloff(0.9).val(), 200.0);
+        assert!(spectrum.spectral_flatness() < 0.01);
+
+        let mut flat_vector = vec![
+            (0.0_f32.into(), 2.0_f32.into()),
+            (100.0.into(), 2.0.into()),
+            (200.0.into(), 2.0.into()),
+            (300.0.into(), 2.0.into()),
+        ];
+        let flat = FrequencySpectrum::new(
+            flat_vector.clone(),
+            100.0,
+            flat_vector.len() as _,
+            &mut flat_vector,
+        );
+        float_cmp::assert_approx_eq!(f32, flat.spectral_flatness(), 1.0, epsilon = 0.001);
+        assert_eq!(flat.spectral_slope(), 0.0);
+
+        // -6 dB per 1000 Hz
+        let tilted = FrequencySpectrum::from_iter_sorted(
+            (0..=1000).map(|i| {
+                let fr = i as f32 * 10.0;
+                (
+                    fr.into(),
+                    libm::powf(10.0, -6.0 * fr / 1000.0 / 20.0).into(),
+                )
+            }),
+            10.0,
+            2000,
+        );
+        float_cmp::assert_approx_eq!(f32, tilted.spectral_slope(), -0.006, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_empty() {
+        let mut spectrum = FrequencySpectrum::empty();
+        assert!(spectrum.is_empty());
+        assert!(spectrum.data().is_empty());
+        assert_eq!(spectrum.average().val(), 0.0);
+        assert_eq!(spectrum.median().val(), 0.0);
+        assert_eq!(spectrum.min().1.val(), 0.0);
+        assert_eq!(spectrum.max().1.val(), 0.0);
+        assert_eq!(spectrum.range().val(), 0.0);
+        assert_eq!(spectrum.samples_len(), 0);
+
+        // statistics, scaling, and transformations work on an empty spectrum
+        assert_eq!(spectrum.dc_component(), None);
+        assert_eq!(spectrum.fit_to_length(3), vec![0.0; 3]);
+        assert!(spectrum.smooth_log_frequency(1.0 / 3.0).is_empty());
+        assert!(spectrum.fractional_octave_smoothed(3.0).is_empty());
+        assert!(spectrum.shifted(10.0).is_empty());
+        assert!(spectrum.masking_threshold(10.0).is_empty());
+        assert!(spectrum
+            .to_erb_bands(4)
+            .iter()
+            .all(|(_fr, val)| val.val() == 0.0));
+        assert_eq!(spectrum.spectral_centroid(), 0.0);
+        assert_eq!(spectrum.band_energy(0.0, 100.0), 0.0);
+        spectrum
+            .apply_scaling_fn(&crate::scaling::divide_by_N, &mut [])
+            .unwrap();
+        spectrum *= 2.0;
+        spectrum += 1.0;
+        assert!(spectrum.is_empty());
+        assert_eq!(spectrum.average().val(), 0.0);
+        assert_eq!(spectrum.max().1.val(), 0.0);
+
+        let mut spectrum_vector =
+            vec![(0.0_f32.into(), 5.0_f32.into()), (50.0.into(), 10.0.into())];
+        spectrum = FrequencySpectrum::new(
+            spectrum_vector.clone(),
+            50.0,
+            spectrum_vector.len() as _,
+            &mut spectrum_vector,
+        );
+        assert!(!spectrum.is_empty());
+
+        // a single bin
+        let spectrum = FrequencySpectrum {
+            data: vec![(10.0.into(), 4.0.into())],
+            ..FrequencySpectrum::empty()
+        };
+        assert_eq!(spectrum.fit_to_length(2), vec![4.0; 2]);
+        assert_eq!(spectrum.masking_threshold(10.0).data()[0].1.val(), 0.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_empty_max_fr_panics() {
+        let _ = FrequencySpectrum::empty().max_fr();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_empty_freq_val_exact_panics() {
+        let _ = FrequencySpectrum::empty().freq_val_exact(10.0);
+    }
+
     #[test]
     fn test_mel_getter() {
         let mut spectrum_vector = vec![
@@ -997,4 +3312,309 @@ mod tests {
         );
         let _ = spectrum.mel_val(450.0);
     }
+
+    #[test]
+    fn test_to_erb_bands() {
+        // 10 Hz resolution from 0 Hz to 8000 Hz with a peak at 1000 Hz
+        let data = (0..=800).map(|i| {
+            let val = if i == 100 { 10.0 } else { 0.1 };
+            (i as f32 * 10.0, val)
+        });
+        let spectrum = spectrum_from_pairs(data, 10.0, 1600);
+
+        let bands = spectrum.to_erb_bands(20);
+        assert_eq!(bands.len(), 20);
+        assert!(bands.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        // all energy is preserved
+        let total = bands
+            .iter()
+            .map(|(_center, energy)| energy.val())
+            .sum::<f32>();
+        float_cmp::assert_approx_eq!(
+            f32,
+            total,
+            spectrum.band_energy(0.0, 8000.0),
+            epsilon = 1e-2
+        );
+        // the band around 1000 Hz contains the peak
+        let (center, energy) = bands
+            .iter()
+            .max_by(|(_, a), (_, b)| a.cmp(b))
+            .copied()
+            .unwrap();
+        assert!(energy.val() >= 100.0);
+        assert!((700.0..1400.0).contains(&center), "{}", center);
+
+        // more bands than bins at low frequencies: empty bands have no energy
+        let bands = spectrum.to_erb_bands(1000);
+        assert_eq!(bands.len(), 1000);
+        assert_eq!(bands[1].1.val(), 0.0);
+
+        assert!(spectrum.to_erb_bands(0).is_empty());
+        assert!(FrequencySpectrum::empty().to_erb_bands(10).is_empty());
+    }
+
+    /// 1000 small values and a single huge outlier.
+    fn spectrum_with_outlier() -> FrequencySpectrum {
+        let data = (0..1001).map(|i| {
+            let val = if i == 500 {
+                1_000_000.0
+            } else {
+                1.0 + (i % 3) as f32 * 0.1
+            };
+            (i as f32, val)
+        });
+        spectrum_from_pairs(data, 1.0, 2000)
+    }
+
+    #[test]
+    fn test_trimmed_mean() {
+        let spectrum = spectrum_with_outlier();
+        assert!(spectrum.average().val() > 900.0);
+        float_cmp::assert_approx_eq!(f32, spectrum.trimmed_mean(0.01).val(), 1.1, epsilon = 1e-2);
+        float_cmp::assert_approx_eq!(
+            f32,
+            spectrum.trimmed_mean(0.0).val(),
+            spectrum.average().val(),
+            epsilon = 1e-2
+        );
+    }
+
+    #[test]
+    fn test_mad() {
+        let spectrum = spectrum_with_outlier();
+        float_cmp::assert_approx_eq!(f32, spectrum.mad().val(), 0.1, epsilon = 1e-5);
+
+        // symmetric: 1, 2, 3, 4 => median 2.5 and deviations 0.5, 0.5, 1.5, 1.5
+        let spectrum = spectrum_from_pairs((1..=4).map(|i| (i as f32, i as f32)), 1.0, 8);
+        assert_eq!(spectrum.mad().val(), 1.0);
+    }
+
+    #[test]
+    fn test_with_excluded_band() {
+        // flat noise floor with mains hum at 50 Hz and a tone at 200 Hz
+        let data = (0..=100).map(|i| {
+            let fr = i as f32 * 5.0;
+            let val = match i {
+                10 => 100.0,
+                40 => 10.0,
+                _ => 1.0,
+            };
+            (fr, val)
+        });
+        let spectrum = spectrum_from_pairs(data, 5.0, 200);
+        assert_eq!(spectrum.max().0.val(), 50.0);
+
+        let without_hum = spectrum.with_excluded_band(45.0, 55.0);
+        assert_eq!(without_hum.data().len(), 98);
+        assert_eq!(without_hum.max().0.val(), 200.0);
+        assert!(without_hum.average() < spectrum.average());
+        assert_eq!(without_hum.frequency_resolution(), 5.0);
+        assert_eq!(without_hum.samples_len(), 200);
+        assert!(without_hum
+            .data()
+            .iter()
+            .all(|(fr, _)| fr.val() < 45.0 || fr.val() > 55.0));
+        // interpolated across the gap
+        assert_eq!(without_hum.freq_val_exact(50.0).val(), 1.0);
+
+        // an empty band changes nothing
+        assert_eq!(
+            spectrum.with_excluded_band(51.0, 54.0).data(),
+            spectrum.data()
+        );
+    }
+
+    #[test]
+    fn test_apply_positional_scaling_fn() {
+        let mut spectrum = spectrum_from_pairs((0..16).map(|i| (i as f32 * 10.0, 1.0)), 10.0, 30);
+        // zero out the first two and the last two bins
+        let taper = |val: f32, _stats: &SpectrumDataStats, position: BinPosition| {
+            if position.index < 2 || position.index + 2 >= position.bins_count {
+                0.0
+            } else {
+                val
+            }
+        };
+        let mut working_buffer = spectrum.data().to_vec();
+        spectrum
+            .apply_positional_scaling_fn(&taper, &mut working_buffer)
+            .unwrap();
+
+        let values = spectrum
+            .data()
+            .iter()
+            .map(|(_fr, val)| val.val())
+            .collect::<Vec<_>>();
+        assert_eq!(values[..2], [0.0, 0.0]);
+        assert!(values[2..14].iter().all(|val| *val == 1.0));
+        assert_eq!(values[14..], [0.0, 0.0]);
+        // the statistics are updated
+        assert_eq!(spectrum.min().1.val(), 0.0);
+        assert_eq!(spectrum.average().val(), 0.75);
+    }
+
+    #[test]
+    fn test_to_xy_f64() {
+        let spectrum = spectrum_from_pairs(
+            (0..8).map(|i| (i as f32 * 500.0, i as f32 * 0.5)),
+            500.0,
+            14,
+        );
+
+        let points = spectrum.to_xy_f64();
+        assert_eq!(points.len(), 8);
+        assert!(points.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(points[3], (1500.0, 1.5));
+
+        // kHz and dB
+        let points = spectrum.to_xy_f64_with(|hz, val| (hz / 1000.0, 20.0 * val.log10()));
+        assert_eq!(points.len(), 8);
+        assert_eq!(points[2].0, 1.0);
+        float_cmp::assert_approx_eq!(f64, points[2].1, 0.0, epsilon = 1e-9);
+        float_cmp::assert_approx_eq!(f64, points[4].1, 20.0 * 2.0_f64.log10(), epsilon = 1e-9);
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn test_to_ndarray() {
+        let spectrum = spectrum_from_pairs(
+            (0..8).map(|i| (i as f32 * 500.0, i as f32 * 0.5)),
+            500.0,
+            14,
+        );
+
+        let (frequencies, values) = spectrum.to_ndarray();
+        assert_eq!(frequencies.len(), 8);
+        assert_eq!(values.len(), 8);
+        assert_eq!(frequencies[3], 1500.0);
+        assert_eq!(values[3], 1.5);
+        assert_eq!(values.sum(), spectrum.average().val() * 8.0);
+    }
+
+    #[test]
+    fn test_masking_threshold() {
+        // a loud tone at 1 kHz above a flat floor
+        let resolution = 44100.0 / 8192.0;
+        let tone_index = (1000.0 / resolution) as usize;
+        let data = (0..=4096).map(|i| {
+            let val = if i == tone_index { 1.0 } else { 0.001 };
+            ((i as f32 * resolution).into(), val.into())
+        });
+        let spectrum = FrequencySpectrum::from_iter_sorted(data, resolution, 8192);
+        let tone_bark = hertz_to_bark(spectrum.data()[tone_index].0.val());
+
+        let threshold = spectrum.masking_threshold(10.0);
+        assert_eq!(threshold.frequencies(), spectrum.frequencies());
+        for (i, (fr, val)) in threshold.data().iter().enumerate() {
+            let val = val.val();
+            if i == tone_index {
+                // only masked by the floor
+                assert!(val <= 0.001, "{}", val);
+                continue;
+            }
+            let distance = (hertz_to_bark(fr.val()) - tone_bark).abs();
+            let masked_by_tone = libm::powf(10.0, -10.0 * distance / 20.0);
+            if masked_by_tone > 0.01 {
+                float_cmp::assert_approx_eq!(f32, val, masked_by_tone, epsilon = 0.001);
+            } else if distance > 10.0 {
+                // the floor masks its neighbors
+                assert!(val > 0.0009 && val <= 0.001, "{}: {}", fr, val);
+            }
+        }
+        // 1 Bark above the tone, the threshold is 10 dB below it
+        let one_bark_above = threshold
+            .data()
+            .iter()
+            .find(|(fr, _val)| hertz_to_bark(fr.val()) >= tone_bark + 1.0)
+            .unwrap();
+        float_cmp::assert_approx_eq!(
+            f32,
+            20.0 * libm::log10f(one_bark_above.1.val()),
+            -10.0,
+            epsilon = 0.1
+        );
+
+        // without spreading, the tone masks everything completely
+        let threshold = spectrum.masking_threshold(0.0);
+        assert_eq!(threshold.max().1.val(), 1.0);
+        assert_eq!(threshold.min().1.val(), 0.001);
+        assert_eq!(threshold.data()[tone_index].1.val(), 0.001);
+    }
+
+    #[test]
+    fn test_aggregation_mode() {
+        let spectrum = FrequencySpectrum::from_iter_sorted(
+            [(0.0, 1.0), (100.0, 2.0), (200.0, 2.0), (300.0, 1.0)]
+                .into_iter()
+                .map(|(fr, val): (f32, f32)| (fr.into(), val.into())),
+            100.0,
+            6,
+        );
+        // a band with two equal bins
+        let band = |mode| spectrum.band_value(50.0, 250.0, mode);
+        assert_eq!(band(AggregationMode::SumPower), 2.0 * 4.0);
+        assert_eq!(band(AggregationMode::SumAmplitude), 2.0 * 2.0);
+        assert_eq!(band(AggregationMode::Average), 2.0);
+        assert_eq!(band(AggregationMode::Rms), 2.0);
+        assert_eq!(band(AggregationMode::Max), 2.0);
+        float_cmp::assert_approx_eq!(
+            f32,
+            spectrum.band_value(0.0, 300.0, AggregationMode::Rms),
+            libm::sqrtf(10.0 / 4.0),
+            ulps = 2
+        );
+        assert_eq!(
+            band(AggregationMode::SumPower),
+            spectrum.band_energy(50.0, 250.0)
+        );
+
+        // empty band
+        for mode in [
+            AggregationMode::SumPower,
+            AggregationMode::SumAmplitude,
+            AggregationMode::Average,
+            AggregationMode::Rms,
+            AggregationMode::Max,
+        ] {
+            assert_eq!(spectrum.band_value(110.0, 190.0, mode), 0.0);
+        }
+
+        // the ERB bands use the same aggregation
+        let full = spectrum.band_value(0.0, 300.0, AggregationMode::Max);
+        assert_eq!(
+            spectrum.to_erb_bands_with(1, AggregationMode::Max),
+            [(spectrum.to_erb_bands(1)[0].0, full.into())]
+        );
+        assert_eq!(
+            spectrum.to_erb_bands_with(3, AggregationMode::SumPower),
+            spectrum.to_erb_bands(3)
+        );
+
+        // and the smoothing; the value at 0 Hz stays unchanged
+        assert_eq!(
+            spectrum
+                .smooth_log_frequency_with(2.0, AggregationMode::Max)
+                .magnitudes(),
+            [1.0, 2.0, 2.0, 2.0]
+        );
+        assert_eq!(
+            spectrum
+                .smooth_log_frequency_with(2.0, AggregationMode::Average)
+                .data(),
+            spectrum.smooth_log_frequency(2.0).data()
+        );
+        assert_eq!(
+            spectrum
+                .fractional_octave_smoothed_with(0.5, AggregationMode::Average)
+                .data(),
+            spectrum.smooth_log_frequency(2.0).data()
+        );
+        assert_eq!(
+            spectrum
+                .fractional_octave_smoothed_with(0.5, AggregationMode::Rms)
+                .data(),
+            spectrum.fractional_octave_smoothed(0.5).data()
+        );
+    }
 }
@@ -0,0 +1,73 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for assertion helpers that check the peaks of a spectrum.
+
+use crate::peaks::PeakConfig;
+use crate::FrequencySpectrum;
+
+/// Asserts that the spectrum has a peak (local maximum) within `tolerance_hz`
+/// of `expected_hz`.
+///
+/// ## Return value
+/// The value of the highest peak within the tolerance.
+#[track_caller]
+pub fn assert_peak_near(spectrum: &FrequencySpectrum, expected_hz: f32, tolerance_hz: f32) -> f32 {
+    spectrum
+        .peaks(&PeakConfig::default())
+        .iter()
+        .filter(|(fr, _val)| (fr.val() - expected_hz).abs() <= tolerance_hz)
+        .map(|(_fr, val)| val.val())
+        .fold(None, |max: Option<f32>, val| {
+            Some(max.map_or(val, |max| max.max(val)))
+        })
+        .unwrap_or_else(|| {
+            panic!(
+                "Expected a peak at {}Hz (+-{}Hz) but there is none!",
+                expected_hz, tolerance_hz
+            )
+        })
+}
+
+/// Asserts that the spectrum has no peak (local maximum) with a value above
+/// `threshold`, except for peaks in one of the `excluded_ranges`
+/// (`(low, high)` in Hertz, inclusive).
+#[track_caller]
+pub fn assert_no_peak_above(
+    spectrum: &FrequencySpectrum,
+    threshold: f32,
+    excluded_ranges: &[(f32, f32)],
+) {
+    for (fr, val) in spectrum.peaks(&PeakConfig::default()) {
+        let is_excluded = excluded_ranges
+            .iter()
+            .any(|(low, high)| fr.val() >= *low && fr.val() <= *high);
+        assert!(
+            is_excluded || val.val() <= threshold,
+            "Unexpected peak at {}Hz with value {} > {}!",
+            fr,
+            val,
+            threshold
+        );
+    }
+}
@@ -0,0 +1,40 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for fixtures that the unit tests of several modules share.
+
+use crate::FrequencySpectrum;
+
+/// Creates a spectrum from `(frequency, value)`-pairs that are sorted by
+/// frequency, e.g., a synthetic resonance or a spectrum with outliers.
+pub fn spectrum_from_pairs(
+    pairs: impl IntoIterator<Item = (f32, f32)>,
+    frequency_resolution: f32,
+    samples_len: u32,
+) -> FrequencySpectrum {
+    FrequencySpectrum::from_iter_sorted(
+        pairs.into_iter().map(|(fr, val)| (fr.into(), val.into())),
+        frequency_resolution,
+        samples_len,
+    )
+}
@@ -24,10 +24,27 @@ SOFTWARE.
 //! Test module for "integration"-like tests. No small unit tests of simple functions.
 
 use crate::error::SpectrumAnalyzerError;
-use crate::scaling::{divide_by_N, scale_to_zero_to_one};
-use crate::tests::sine::sine_wave_audio_data_multiple;
-use crate::windows::{hamming_window, hann_window};
-use crate::{samples_fft_to_spectrum, FrequencyLimit};
+use crate::minimal::{dominant_frequency, dominant_frequency_with_scratch};
+use crate::scaling::{
+    divide_by_N, scale_20_times_log10, scale_to_zero_to_one, SpectrumDataStats,
+    SpectrumScalingFunction,
+};
+use crate::spectrogram::{
+    analyze_chunks, analyze_file_samples, analyze_file_samples_timed, analyze_slice,
+    average_segments, average_timed_spectra, detect_onsets, frame_time_seconds,
+    long_term_average_spectrum, modulation_energy, reassigned_spectrogram, segment_active_regions,
+    spectrogram_peaks, window_duration_seconds, AnalysisMetadata, ChunkConfig, Remainder,
+};
+use crate::tests::allocations::count_allocations;
+use crate::tests::assertions::{assert_no_peak_above, assert_peak_near};
+use crate::tests::sine::{sine_wave, sine_wave_audio_data_multiple};
+use crate::windows::{coherent_gain, hamming_window, hann_window, WindowType};
+use crate::{
+    difference_spectrum, samples_fft_magnitudes_only, samples_fft_to_mag_phase,
+    samples_fft_to_spectrum, samples_fft_to_spectrum_dual, samples_fft_to_spectrum_with_config,
+    samples_interleaved_stereo_to_mono_spectrum, BinValue, FrequencyLimit, FrequencySpectrum,
+    Interpolation, LimitStage, SpectrumConfig, DEFAULT_SINC_KERNEL_BINS, MAX_SAMPLES_LEN,
+};
 use alloc::vec::Vec;
 use audio_visualizer::spectrum::plotters_png_file::spectrum_static_plotters_png_visualize;
 use audio_visualizer::waveform::plotters_png_file::waveform_static_plotters_png_visualize;
@@ -39,6 +56,9 @@ use core::cmp::max;
 /// If tests create files, they should be stored here.
 const TEST_OUT_DIR: &str = "test/out";
 
+mod allocations;
+mod assertions;
+pub(crate) mod fixtures;
 mod sine;
 
 #[test]
@@ -126,13 +146,19 @@ fn test_spectrum_and_visualize_sine_waves_50_1000_3777hz() {
     assert!(spectrum_hann_window.freq_val_exact(500.0).val() < 0.00001);
     assert!(spectrum_hann_window.freq_val_closest(500.0).1.val() < 0.00001);
 
-    /*for (fr, vol) in spectrum.iter() {
-        // you will experience inaccuracies here
-        // TODO add further smoothing / noise reduction
-        if *fr > 45.0.into() && *fr < 55.0.into() {
-            println!("{}Hz => {}", fr, vol);
-        }
-    }*/
+    // frequency resolution is 44100/4096 = ~10.8 Hz
+    let tolerance_hz = 11.0;
+    let excluded_ranges = [(30.0, 70.0), (980.0, 1020.0), (3757.0, 3797.0)];
+    for spectrum in [&spectrum_no_window, &spectrum_hann_window] {
+        let smallest_peak = [50.0, 1000.0, 3777.0]
+            .into_iter()
+            .map(|fr| assert_peak_near(spectrum, fr, tolerance_hz))
+            .fold(f32::MAX, f32::min);
+        // the three sine waves are the top peaks
+        assert_no_peak_above(spectrum, smallest_peak, &excluded_ranges);
+    }
+    // no spurious peaks at all with a window
+    assert_no_peak_above(&spectrum_hann_window, 0.01, &excluded_ranges);
 }
 
 /// This test is primarily for my personal understanding. It analyzes a specific constant
@@ -194,6 +220,12 @@ fn test_spectrum_power() {
         "test_spectrum_power__very_long_window.png",
     );*/
 
+    // frequency resolution is 44100/2048 = ~21.5 Hz
+    for spectrum in [&spectrum_short_window, &spectrum_long_window] {
+        let peak = assert_peak_near(spectrum, interesting_frequency, 22.0);
+        assert_no_peak_above(spectrum, peak, &[(2000.0, 2100.0)]);
+    }
+
     let a = spectrum_short_window.freq_val_exact(interesting_frequency);
     let b = spectrum_long_window.freq_val_exact(interesting_frequency);
     //let c = spectrum_very_long_window.freq_val_exact(interesting_frequency);
@@ -366,6 +398,9 @@ fn test_spectrum_nyquist_theorem2() {
         spectrum.freq_val_exact(21500.0).val() <= 0.01,
         "Other frequencies must not be part of the spectrum!"
     );
+    // the peak is the last bin; all other peaks are side lobes
+    assert_eq!(spectrum.max().0.val(), 44100.0 / 2.0);
+    assert_no_peak_above(&spectrum, 0.3, &[]);
 }
 
 #[test]
@@ -465,3 +500,1715 @@ fn test_divide_by_n_has_effect() {
         );
     }
 }
+
+/// Tests that the [`LimitStage`] decides whether the scaling function sees the
+/// statistics of the full spectrum or only of the bins inside the limit.
+#[test]
+fn test_limit_stage() {
+    let sampling_rate = 1024;
+    // strong peak at 400 Hz, weak peak at 100 Hz
+    let strong = sine_wave(400.0);
+    let weak = sine_wave(100.0);
+    let samples = (0..1024)
+        .map(|i| i as f32 / sampling_rate as f32)
+        .map(|t| strong(t) + 0.25 * weak(t))
+        .collect::<Vec<_>>();
+    let samples = hann_window(&samples);
+    // the global maximum at 400 Hz lies outside the limit
+    let limit = FrequencyLimit::Max(200.0);
+
+    let before = samples_fft_to_spectrum_with_config(
+        &samples,
+        sampling_rate,
+        limit,
+        Some(&scale_to_zero_to_one),
+        &SpectrumConfig {
+            limit_stage: LimitStage::BeforeScaling,
+            ..SpectrumConfig::default()
+        },
+    )
+    .unwrap();
+    let after = samples_fft_to_spectrum_with_config(
+        &samples,
+        sampling_rate,
+        limit,
+        Some(&scale_to_zero_to_one),
+        &SpectrumConfig {
+            limit_stage: LimitStage::AfterScaling,
+            ..SpectrumConfig::default()
+        },
+    )
+    .unwrap();
+
+    // both spectra cover the same bins
+    assert_eq!(before.data().len(), after.data().len());
+    assert_eq!(before.max_fr().val(), 200.0);
+    assert_eq!(after.max_fr().val(), 200.0);
+
+    // normalized against the bins <= 200 Hz: the weak peak is the maximum
+    assert_eq!(before.max().0.val(), 100.0);
+    float_cmp::assert_approx_eq!(f32, before.max().1.val(), 1.0, ulps = 3);
+
+    // normalized against the full spectrum: the weak peak is a quarter of the
+    // strong peak
+    assert_eq!(after.max().0.val(), 100.0);
+    float_cmp::assert_approx_eq!(f32, after.max().1.val(), 0.25, epsilon = 0.01);
+
+    // the strong peak is the top peak of the full spectrum
+    let full_spectrum =
+        samples_fft_to_spectrum(&samples, sampling_rate, FrequencyLimit::All, None).unwrap();
+    let strong_peak = assert_peak_near(&full_spectrum, 400.0, 1.0);
+    let weak_peak = assert_peak_near(&full_spectrum, 100.0, 1.0);
+    float_cmp::assert_approx_eq!(f32, weak_peak / strong_peak, 0.25, epsilon = 0.01);
+    assert_no_peak_above(
+        &full_spectrum,
+        0.01 * strong_peak,
+        &[(99.0, 101.0), (399.0, 401.0)],
+    );
+
+    // default is the same as `samples_fft_to_spectrum`
+    let default =
+        samples_fft_to_spectrum(&samples, sampling_rate, limit, Some(&scale_to_zero_to_one))
+            .unwrap();
+    assert_eq!(default.data(), before.data());
+}
+
+/// Analyzes a 30 second recording chunk by chunk and checks the reported
+/// progress.
+#[test]
+fn test_analyze_file_samples() {
+    let sampling_rate = 8000;
+    let sine_audio = sine_wave_audio_data_multiple(&[1000.0], sampling_rate, 30_000)
+        .into_iter()
+        .map(|x| x as f32)
+        .collect::<Vec<f32>>();
+    assert_eq!(sine_audio.len(), 240_000);
+
+    let config = ChunkConfig {
+        chunk_len: 1024,
+        hop_len: 1024,
+        window: WindowType::Hann,
+        frequency_limit: FrequencyLimit::All,
+    };
+    let mut progress_values = Vec::new();
+    let spectra = analyze_file_samples(
+        &sine_audio,
+        sampling_rate,
+        &config,
+        None,
+        Some(&mut |progress| progress_values.push(progress)),
+    )
+    .unwrap();
+
+    // 240000 / 1024 = 234.375 => the last chunk is padded
+    assert_eq!(spectra.len(), 235);
+    assert_eq!(progress_values.len(), spectra.len());
+    assert!(
+        progress_values.windows(2).all(|w| w[0] < w[1]),
+        "progress must be monotonically increasing"
+    );
+    assert_eq!(*progress_values.last().unwrap(), 1.0);
+
+    // each full chunk detects the tone
+    for spectrum in &spectra[..spectra.len() - 1] {
+        assert!((spectrum.max().0.val() - 1000.0).abs() <= spectrum.frequency_resolution());
+    }
+}
+
+/// Tests that the maximum number of samples is enforced with an error and not
+/// with a panic or an allocator abort.
+#[test]
+fn test_max_samples_len() {
+    // the biggest supported length still works
+    let samples = vec![0.0; MAX_SAMPLES_LEN];
+    let _ = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+
+    let samples = vec![0.0; MAX_SAMPLES_LEN * 2];
+    let err = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap_err();
+    assert!(matches!(
+        err,
+        SpectrumAnalyzerError::SamplesLengthTooLarge(len, max) if len == MAX_SAMPLES_LEN * 2 && max == MAX_SAMPLES_LEN
+    ));
+
+    // custom limit
+    let config = SpectrumConfig {
+        max_samples_len: 1024,
+        ..SpectrumConfig::default()
+    };
+    let samples = vec![0.0; 1024];
+    let _ =
+        samples_fft_to_spectrum_with_config(&samples, 44100, FrequencyLimit::All, None, &config)
+            .unwrap();
+    let samples = vec![0.0; 2048];
+    let err =
+        samples_fft_to_spectrum_with_config(&samples, 44100, FrequencyLimit::All, None, &config)
+            .unwrap_err();
+    assert!(matches!(
+        err,
+        SpectrumAnalyzerError::SamplesLengthTooLarge(2048, 1024)
+    ));
+}
+
+#[test]
+fn test_frequency_limit_excludes_all_bins() {
+    let sine = sine_wave(100.0);
+    let samples = (0..1024)
+        .map(|i| sine(i as f32 / 44100.0))
+        .collect::<Vec<_>>();
+    // frequency resolution is ~43 Hz: no bin in this range
+    let err = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::Range(100.0, 101.0), None)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        SpectrumAnalyzerError::FrequencyLimitExcludesAllBins
+    ));
+    // only the DC bin remains
+    let err = samples_fft_to_spectrum_with_config(
+        &samples,
+        44100,
+        FrequencyLimit::Max(10.0),
+        None,
+        &SpectrumConfig {
+            limit_stage: LimitStage::AfterScaling,
+            ..SpectrumConfig::default()
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        SpectrumAnalyzerError::FrequencyLimitExcludesAllBins
+    ));
+}
+
+#[test]
+fn test_samples_interleaved_stereo_to_mono_spectrum() {
+    let left = sine_wave(1000.0);
+    let right = sine_wave(3000.0);
+    let interleaved = (0..1024)
+        .map(|i| i as f32 / 44100.0)
+        .flat_map(|t| [left(t), right(t)])
+        .collect::<Vec<_>>();
+    let mono_samples = interleaved
+        .chunks_exact(2)
+        .map(|frame| (frame[0] + frame[1]) / 2.0)
+        .collect::<Vec<_>>();
+
+    let spectrum =
+        samples_interleaved_stereo_to_mono_spectrum(&interleaved, 44100, FrequencyLimit::All, None)
+            .unwrap();
+    let expected =
+        samples_fft_to_spectrum(&mono_samples, 44100, FrequencyLimit::All, None).unwrap();
+    assert_eq!(spectrum.data(), expected.data());
+    assert_eq!(spectrum.samples_len(), 1024);
+
+    let err = samples_interleaved_stereo_to_mono_spectrum(
+        &interleaved[1..],
+        44100,
+        FrequencyLimit::All,
+        None,
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        SpectrumAnalyzerError::OddInterleavedSamplesLength
+    ));
+}
+
+#[test]
+fn test_segment_active_regions() {
+    let sampling_rate = 8000;
+    let tone = sine_wave(1000.0);
+    let bursts = [(0.5, 1.0), (2.0, 2.5), (3.5, 4.5)];
+    let samples = (0..5 * sampling_rate)
+        .map(|i| i as f32 / sampling_rate as f32)
+        .map(|t| {
+            if bursts.iter().any(|(start, end)| t >= *start && t < *end) {
+                tone(t)
+            } else {
+                0.0
+            }
+        })
+        .collect::<Vec<_>>();
+    let config = ChunkConfig {
+        chunk_len: 256,
+        hop_len: 256,
+        window: WindowType::Hann,
+        frequency_limit: FrequencyLimit::All,
+    };
+    let frames = analyze_file_samples(&samples, sampling_rate, &config, None, None).unwrap();
+    let hop_seconds = config.hop_len as f32 / sampling_rate as f32;
+
+    let segments = segment_active_regions(&frames, hop_seconds, 30.0, 0.1, 0.1);
+    assert_eq!(segments.len(), 3);
+    for ((start, end), (expected_start, expected_end)) in segments.iter().zip(bursts) {
+        assert!((start - expected_start).abs() <= hop_seconds);
+        assert!((end - expected_end).abs() <= hop_seconds);
+    }
+
+    // gaps of 1s are closed
+    let segments = segment_active_regions(&frames, hop_seconds, 30.0, 0.1, 1.5);
+    assert_eq!(segments.len(), 1);
+    // all segments are too short
+    let segments = segment_active_regions(&frames, hop_seconds, 30.0, 2.0, 0.1);
+    assert!(segments.is_empty());
+}
+
+#[test]
+fn test_cumulative_energy_and_occupied_bandwidth() {
+    let samples = sine_wave_audio_data_multiple(&[1000.0], 44100, 1000)
+        .into_iter()
+        .map(|x| x as f32)
+        .take(4096)
+        .collect::<Vec<_>>();
+    let spectrum =
+        samples_fft_to_spectrum(&hann_window(&samples), 44100, FrequencyLimit::All, None).unwrap();
+
+    let cumulative = spectrum.cumulative_energy();
+    assert_eq!(cumulative.len(), spectrum.data().len());
+    assert!(cumulative.windows(2).all(|w| w[0].1 <= w[1].1));
+    assert_eq!(cumulative.last().unwrap().1, 1.0);
+    // half of the energy is below the tone
+    let half = cumulative.iter().find(|(_fr, e)| *e >= 0.5).unwrap().0;
+    assert!((half.val() - 1000.0).abs() < 15.0, "{}", half);
+
+    let (low, high) = spectrum.occupied_bandwidth(0.99).unwrap();
+    assert!(low.val() < 1000.0 && high.val() > 1000.0);
+    assert!(high.val() - low.val() < 100.0, "{}..{}", low, high);
+
+    // two widely separated tones with the same amplitude: the band spans both
+    let samples = sine_wave_audio_data_multiple(&[500.0, 8000.0], 44100, 1000)
+        .into_iter()
+        .map(|x| x as f32)
+        .take(4096)
+        .collect::<Vec<_>>();
+    let spectrum =
+        samples_fft_to_spectrum(&hann_window(&samples), 44100, FrequencyLimit::All, None).unwrap();
+
+    let cumulative = spectrum.cumulative_energy();
+    assert!(cumulative.windows(2).all(|w| w[0].1 <= w[1].1));
+    assert_eq!(cumulative.last().unwrap().1, 1.0);
+    // in between the tones, roughly half of the energy is reached
+    let between = cumulative
+        .iter()
+        .find(|(fr, _e)| fr.val() >= 4000.0)
+        .unwrap()
+        .1;
+    float_cmp::assert_approx_eq!(f32, between, 0.5, epsilon = 0.05);
+
+    let (low, high) = spectrum.occupied_bandwidth(0.99).unwrap();
+    assert!((low.val() - 500.0).abs() < 50.0, "{}", low);
+    assert!((high.val() - 8000.0).abs() < 50.0, "{}", high);
+    let (low, high) = spectrum.occupied_bandwidth(0.5).unwrap();
+    assert!((low.val() - 500.0).abs() < 50.0, "{}", low);
+    assert!((high.val() - 8000.0).abs() < 50.0, "{}", high);
+
+    // no energy at all
+    let spectrum = samples_fft_to_spectrum(&[0.0; 1024], 44100, FrequencyLimit::All, None).unwrap();
+    assert!(spectrum
+        .cumulative_energy()
+        .iter()
+        .all(|(_fr, e)| *e == 0.0));
+    assert_eq!(
+        spectrum.occupied_bandwidth(0.99),
+        Some((spectrum.min_fr(), spectrum.max_fr()))
+    );
+
+    // no bins at all
+    let spectrum = FrequencySpectrum::empty();
+    assert!(spectrum.cumulative_energy().is_empty());
+    assert_eq!(spectrum.occupied_bandwidth(0.99), None);
+}
+
+#[test]
+fn test_spectrogram_peaks() {
+    let sampling_rate = 8000;
+    let tone_a = sine_wave(1000.0);
+    let tone_b = sine_wave(2500.0);
+    // bursts with a smooth envelope, so that there is a single loudest frame
+    let burst = |t: f32, start: f32, duration: f32| {
+        if t >= start && t < start + duration {
+            libm::powf(
+                libm::sinf(core::f32::consts::PI * (t - start) / duration),
+                2.0,
+            )
+        } else {
+            0.0
+        }
+    };
+    let samples = (0..2 * sampling_rate)
+        .map(|i| i as f32 / sampling_rate as f32)
+        .map(|t| burst(t, 0.3, 0.2) * tone_a(t) + burst(t, 1.2, 0.2) * tone_b(t))
+        .collect::<Vec<_>>();
+    let config = ChunkConfig {
+        chunk_len: 256,
+        hop_len: 256,
+        window: WindowType::Hann,
+        frequency_limit: FrequencyLimit::All,
+    };
+    let frames = analyze_file_samples(&samples, sampling_rate, &config, None, None).unwrap();
+
+    let peaks = spectrogram_peaks(&frames, (10, 20), 1.0);
+    assert_eq!(peaks.len(), 2);
+    // frames are 32ms long: the loudest frames are at 0.4s and 1.3s
+    assert_eq!(peaks[0].frame_idx, 12);
+    assert_eq!(peaks[0].frequency, 1000.0);
+    assert_eq!(peaks[1].frame_idx, 40);
+    assert_eq!(peaks[1].frequency, 2500.0);
+
+    assert!(spectrogram_peaks(&[], (3, 3), 0.0).is_empty());
+    assert!(spectrogram_peaks(&[FrequencySpectrum::empty()], (3, 3), 0.0).is_empty());
+}
+
+/// The spectrum covers all frequencies up to and including the Nyquist
+/// frequency.
+#[test]
+fn test_nyquist_bin_is_included() {
+    for (sampling_rate, fft_len) in [(44100, 1024), (8000, 256), (48000, 16)] {
+        let samples = (0..fft_len)
+            .map(|i| if i % 2 == 0 { 1.0 } else { -1.0 })
+            .collect::<Vec<_>>();
+        let spectrum =
+            samples_fft_to_spectrum(&samples, sampling_rate, FrequencyLimit::All, None).unwrap();
+        assert_eq!(spectrum.data().len(), fft_len / 2 + 1);
+        assert_eq!(spectrum.min_fr().val(), 0.0);
+        assert_eq!(spectrum.max_fr().val(), sampling_rate as f32 / 2.0);
+        // an alternating signal has all its energy at the Nyquist frequency
+        assert_eq!(spectrum.max().0.val(), sampling_rate as f32 / 2.0);
+        assert_eq!(spectrum.max().1.val(), fft_len as f32);
+    }
+}
+
+#[test]
+fn test_samples_fft_to_mag_phase() {
+    // 8 Hz frequency resolution: both frequencies are exactly on a bin
+    let sampling_rate = 1024;
+    let cosine = |fr: f32, t: f32| libm::cosf(2.0 * core::f32::consts::PI * fr * t);
+    let samples = (0..128)
+        .map(|i| i as f32 / sampling_rate as f32)
+        // phase 0 at 64 Hz and phase -PI/2 (sine) at 128 Hz
+        .map(|t| cosine(64.0, t) + sine_wave(128.0)(t))
+        .collect::<Vec<_>>();
+
+    let mag_phase = samples_fft_to_mag_phase(&samples, sampling_rate, FrequencyLimit::All).unwrap();
+    let spectrum =
+        samples_fft_to_spectrum(&samples, sampling_rate, FrequencyLimit::All, None).unwrap();
+    assert_eq!(mag_phase.len(), spectrum.data().len());
+    for ((fr, mag, _phase), (expected_fr, expected_mag)) in mag_phase.iter().zip(spectrum.data()) {
+        assert_eq!(fr, expected_fr);
+        assert_eq!(mag, expected_mag);
+    }
+
+    let (_, _, phase_64) = mag_phase[8];
+    let (_, _, phase_128) = mag_phase[16];
+    float_cmp::assert_approx_eq!(f32, phase_64, 0.0, epsilon = 1e-3);
+    float_cmp::assert_approx_eq!(
+        f32,
+        phase_128,
+        -core::f32::consts::FRAC_PI_2,
+        epsilon = 1e-3
+    );
+
+    let mag_phase =
+        samples_fft_to_mag_phase(&samples, sampling_rate, FrequencyLimit::Range(60.0, 130.0))
+            .unwrap();
+    assert_eq!(mag_phase.first().unwrap().0.val(), 64.0);
+    assert_eq!(mag_phase.last().unwrap().0.val(), 128.0);
+
+    // the same input is rejected with the same error as by the spectrum
+    let mut nan_samples = samples.clone();
+    nan_samples[3] = f32::NAN;
+    let too_many_samples = vec![0.0; MAX_SAMPLES_LEN * 2];
+    let invalid_inputs: [(&[f32], u32, FrequencyLimit); 6] = [
+        (&samples, 0, FrequencyLimit::All),
+        (&nan_samples, 0, FrequencyLimit::All),
+        (&too_many_samples, 0, FrequencyLimit::All),
+        (&samples[..100], sampling_rate, FrequencyLimit::All),
+        (&samples, sampling_rate, FrequencyLimit::Min(600.0)),
+        // only a single bin
+        (&samples, sampling_rate, FrequencyLimit::Range(63.0, 65.0)),
+    ];
+    for (samples, sampling_rate, frequency_limit) in invalid_inputs {
+        let mag_phase_err =
+            samples_fft_to_mag_phase(samples, sampling_rate, frequency_limit).unwrap_err();
+        let spectrum_err =
+            samples_fft_to_spectrum(samples, sampling_rate, frequency_limit, None).unwrap_err();
+        assert_eq!(
+            format!("{:?}", mag_phase_err),
+            format!("{:?}", spectrum_err)
+        );
+    }
+}
+
+/// Sub-Hertz frequency bins keep their precision.
+#[test]
+fn test_to_precise_map() {
+    // frequency resolution: 100 / 1024 = ~0.1 Hz
+    let sine = sine_wave(10.0);
+    let samples = (0..1024)
+        .map(|i| sine(i as f32 / 100.0))
+        .collect::<Vec<_>>();
+    let spectrum = samples_fft_to_spectrum(&samples, 100, FrequencyLimit::All, None).unwrap();
+
+    // the lossy map collides
+    assert_eq!(spectrum.to_map().len(), 51);
+    let map = spectrum.to_precise_map();
+    assert_eq!(map.len(), spectrum.data().len());
+    for ((map_fr, map_val), (fr, val)) in map.iter().zip(spectrum.data()) {
+        assert_eq!(map_fr, fr);
+        assert_eq!(map_val, val);
+    }
+    assert_eq!(map[&spectrum.data()[1].0].val(), spectrum.data()[1].1.val());
+}
+
+/// A tone with an amplitude modulation of 4 Hz, as typical for speech, has a
+/// much higher modulation energy than a constant tone.
+#[test]
+fn test_modulation_energy() {
+    let sampling_rate = 8000;
+    let tone = sine_wave(1000.0);
+    let modulation = sine_wave(4.0);
+    let config = ChunkConfig {
+        chunk_len: 256,
+        hop_len: 128,
+        window: WindowType::Hann,
+        frequency_limit: FrequencyLimit::All,
+    };
+    let hop_seconds = config.hop_len as f32 / sampling_rate as f32;
+    let modulation_energy_of = |signal: &dyn Fn(f32) -> f32| {
+        let samples = (0..4 * sampling_rate)
+            .map(|i| signal(i as f32 / sampling_rate as f32))
+            .collect::<Vec<_>>();
+        let frames = analyze_file_samples(&samples, sampling_rate, &config, None, None).unwrap();
+        modulation_energy(&frames, (900.0, 1100.0), hop_seconds, (2.0, 8.0)).unwrap()
+    };
+
+    let modulated = modulation_energy_of(&|t| (1.0 + 0.8 * modulation(t)) * tone(t) / 1.8);
+    let constant = modulation_energy_of(&|t| tone(t));
+    assert!(modulated > 10.0 * constant, "{} vs {}", modulated, constant);
+}
+
+#[test]
+fn test_samples_fft_magnitudes_only() {
+    let sine = sine_wave(1000.0);
+    let samples = (0..1024)
+        .map(|i| sine(i as f32 / 44100.0))
+        .collect::<Vec<_>>();
+    let spectrum = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+
+    let magnitudes = samples_fft_magnitudes_only(&samples, None).unwrap();
+    assert_eq!(magnitudes.len(), 513);
+    assert_eq!(magnitudes, spectrum.magnitudes());
+
+    let scaled = samples_fft_magnitudes_only(&samples, Some(&|val| val / 1024.0)).unwrap();
+    for (scaled, magnitude) in scaled.iter().zip(&magnitudes) {
+        assert_eq!(*scaled, magnitude / 1024.0);
+    }
+
+    assert!(matches!(
+        samples_fft_magnitudes_only(&samples, Some(&|val| val / 0.0)),
+        Err(SpectrumAnalyzerError::ScalingError(..))
+    ));
+    assert!(matches!(
+        samples_fft_magnitudes_only(&samples[..1000], None),
+        Err(SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo)
+    ));
+}
+
+/// Silence with a raw log scaling function results in `-inf`, unless the
+/// magnitudes are clamped to a floor.
+#[test]
+fn test_magnitude_floor() {
+    let samples = vec![0.0; 1024];
+    let log_scaling = |val: f32, _stats: &SpectrumDataStats| 20.0 * libm::log10f(val);
+
+    let err = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, Some(&log_scaling))
+        .unwrap_err();
+    assert!(matches!(err, SpectrumAnalyzerError::ScalingError(..)));
+
+    let spectrum = samples_fft_to_spectrum_with_config(
+        &samples,
+        44100,
+        FrequencyLimit::All,
+        Some(&log_scaling),
+        &SpectrumConfig {
+            magnitude_floor: Some(1e-5),
+            ..SpectrumConfig::default()
+        },
+    )
+    .unwrap();
+    float_cmp::assert_approx_eq!(f32, spectrum.min().1.val(), -100.0, epsilon = 1e-3);
+    float_cmp::assert_approx_eq!(f32, spectrum.max().1.val(), -100.0, epsilon = 1e-3);
+    for value in [
+        spectrum.average().val(),
+        spectrum.median().val(),
+        spectrum.range().val(),
+    ] {
+        assert!(value.is_finite());
+    }
+
+    // magnitudes above the floor are unchanged
+    let sine = sine_wave(1000.0);
+    let samples = (0..1024)
+        .map(|i| sine(i as f32 / 44100.0))
+        .collect::<Vec<_>>();
+    let spectrum = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+    let floored_spectrum = samples_fft_to_spectrum_with_config(
+        &samples,
+        44100,
+        FrequencyLimit::All,
+        None,
+        &SpectrumConfig {
+            magnitude_floor: Some(1e-5),
+            ..SpectrumConfig::default()
+        },
+    )
+    .unwrap();
+    for ((_, val), (_, floored_val)) in spectrum.data().iter().zip(floored_spectrum.data()) {
+        assert_eq!(floored_val.val(), val.val().max(1e-5));
+    }
+
+    // a floor of zero or below doesn't protect the log-based scaling
+    for floor in [0.0, -1e-5, f32::NAN] {
+        let err = samples_fft_to_spectrum_with_config(
+            &samples,
+            44100,
+            FrequencyLimit::All,
+            Some(&log_scaling),
+            &SpectrumConfig {
+                magnitude_floor: Some(floor),
+                ..SpectrumConfig::default()
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            SpectrumAnalyzerError::InvalidMagnitudeFloor(_)
+        ));
+    }
+}
+
+/// The level of the frame is independent of the scaling function.
+#[test]
+fn test_frame_level() {
+    let sine = sine_wave(1000.0);
+    let samples = (0..4096)
+        .map(|i| 0.5 * sine(i as f32 / 44100.0))
+        .collect::<Vec<_>>();
+
+    let scaling_fns: [Option<&SpectrumScalingFunction>; 3] =
+        [None, Some(&scale_to_zero_to_one), Some(&divide_by_N)];
+    for scaling_fn in scaling_fns {
+        let spectrum =
+            samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, scaling_fn).unwrap();
+        float_cmp::assert_approx_eq!(f32, spectrum.frame_rms(), 0.354, epsilon = 1e-3);
+        float_cmp::assert_approx_eq!(f32, spectrum.frame_peak(), 0.5, epsilon = 1e-3);
+    }
+
+    // the spectrogram reports the level before the window is applied
+    let config = ChunkConfig {
+        chunk_len: 1024,
+        hop_len: 1024,
+        window: WindowType::Hann,
+        frequency_limit: FrequencyLimit::All,
+    };
+    let frames = analyze_file_samples(&samples, 44100, &config, None, None).unwrap();
+    for frame in &frames {
+        float_cmp::assert_approx_eq!(f32, frame.frame_rms(), 0.354, epsilon = 1e-2);
+        float_cmp::assert_approx_eq!(f32, frame.frame_peak(), 0.5, epsilon = 1e-2);
+    }
+}
+
+/// A scaling function that produces `NaN`, here `0 * log10(0)` for silence,
+/// results in an error instead of an arbitrary panic while sorting.
+#[test]
+fn test_scaling_fn_nan_policy() {
+    let samples = vec![0.0; 1024];
+    let err = samples_fft_to_spectrum(
+        &samples,
+        44100,
+        FrequencyLimit::All,
+        Some(&|val, _stats| val * libm::log10f(val)),
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        SpectrumAnalyzerError::ScalingError(val, scaled_val) if val == 0.0 && scaled_val.is_nan()
+    ));
+}
+
+/// The sinc interpolated peak of a sine between two bins is closer to the
+/// true frequency and amplitude than the linearly interpolated peak.
+#[test]
+fn test_interpolate_range_sinc() {
+    // 1 Hz frequency resolution; the sine lies between two bins
+    let sine = sine_wave(100.3);
+    let samples = (0..1024)
+        .map(|i| sine(i as f32 / 1024.0))
+        .collect::<Vec<_>>();
+    let spectrum = samples_fft_to_spectrum(&samples, 1024, FrequencyLimit::All, None).unwrap();
+    // N/2 for a sine with amplitude 1
+    let true_amplitude = 512.0;
+
+    let peak_of = |interpolation| {
+        spectrum
+            .interpolate_range(95.0, 106.0, 1101, interpolation)
+            .into_iter()
+            .fold(
+                (0.0, 0.0),
+                |max: (f32, f32), point| {
+                    if point.1 > max.1 {
+                        point
+                    } else {
+                        max
+                    }
+                },
+            )
+    };
+    let (linear_fr, linear_val) = peak_of(Interpolation::Linear);
+    let (sinc_fr, sinc_val) = peak_of(Interpolation::Sinc {
+        kernel_bins: DEFAULT_SINC_KERNEL_BINS,
+    });
+    assert!(
+        (sinc_fr - 100.3).abs() < (linear_fr - 100.3).abs(),
+        "{} vs {}",
+        sinc_fr,
+        linear_fr
+    );
+    assert!(
+        (sinc_val - true_amplitude).abs() < (linear_val - true_amplitude).abs(),
+        "{} vs {}",
+        sinc_val,
+        linear_val
+    );
+
+    // the values at the bins are unchanged
+    for (fr, val) in
+        spectrum.interpolate_range(95.0, 105.0, 11, Interpolation::Sinc { kernel_bins: 4 })
+    {
+        float_cmp::assert_approx_eq!(f32, val, spectrum.freq_val_exact(fr).val(), epsilon = 1e-2);
+    }
+}
+
+/// The statistics of a range equal the ones of a spectrum with the
+/// corresponding frequency limit.
+#[test]
+fn test_stats_in_range() {
+    let samples = sine_wave_audio_data_multiple(&[440.0, 1000.0, 5000.0], 44100, 1000)
+        .into_iter()
+        .take(4096)
+        .map(|x| x as f32)
+        .collect::<Vec<_>>();
+    let spectrum = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+
+    for (low_hz, high_hz) in [(300.0, 3400.0), (0.0, 22050.0), (985.0, 1015.0)] {
+        let stats = spectrum.stats_in_range(low_hz, high_hz).unwrap();
+        let sub_spectrum = samples_fft_to_spectrum(
+            &samples,
+            44100,
+            FrequencyLimit::Range(low_hz, high_hz),
+            None,
+        )
+        .unwrap();
+        let expected = SpectrumDataStats::from(&sub_spectrum);
+        assert_eq!(stats.min, expected.min);
+        assert_eq!(stats.max, expected.max);
+        assert_eq!(stats.median, expected.median);
+        assert_eq!(stats.n, expected.n);
+        float_cmp::assert_approx_eq!(f32, stats.average, expected.average, ulps = 8);
+    }
+
+    // a single bin
+    let stats = spectrum.stats_in_range(1000.0, 1010.0).unwrap();
+    assert_eq!(stats.min, stats.max);
+    assert_eq!(stats.median, stats.max);
+    // no bin
+    assert!(spectrum.stats_in_range(1002.0, 1010.0).is_none());
+    assert!(spectrum.stats_in_range(30000.0, 40000.0).is_none());
+    assert!(spectrum.stats_in_range(3400.0, 300.0).is_none());
+}
+
+/// A short window can't resolve a low tone, a long window can.
+#[test]
+fn test_analysis_diagnostics() {
+    let sine = sine_wave(100.0);
+    let diagnostics_for = |samples_len: usize| {
+        let samples = (0..samples_len)
+            .map(|i| sine(i as f32 / 44100.0))
+            .collect::<Vec<_>>();
+        samples_fft_to_spectrum(&hann_window(&samples), 44100, FrequencyLimit::All, None)
+            .unwrap()
+            .analysis_diagnostics()
+    };
+
+    // 43 Hz resolution
+    let diagnostics = diagnostics_for(1024);
+    float_cmp::assert_approx_eq!(
+        f32,
+        diagnostics.frequency_resolution,
+        43.066,
+        epsilon = 1e-3
+    );
+    float_cmp::assert_approx_eq!(f32, diagnostics.window_duration_s, 0.02322, epsilon = 1e-5);
+    assert!(diagnostics.lowest_frequency.unwrap() < 100.0);
+    assert!(diagnostics.likely_under_resolved);
+
+    // 2.7 Hz resolution; the main lobe of the window is two bins wide
+    let diagnostics = diagnostics_for(16384);
+    float_cmp::assert_approx_eq!(f32, diagnostics.window_duration_s, 0.3715, epsilon = 1e-4);
+    float_cmp::assert_approx_eq!(
+        f32,
+        diagnostics.lowest_frequency.unwrap(),
+        100.0,
+        epsilon = 6.0
+    );
+    assert!(!diagnostics.likely_under_resolved);
+
+    // silence
+    let silence = samples_fft_to_spectrum(&[0.0; 64], 44100, FrequencyLimit::All, None).unwrap();
+    let diagnostics = silence.analysis_diagnostics();
+    assert_eq!(diagnostics.lowest_frequency, None);
+    assert!(!diagnostics.likely_under_resolved);
+}
+
+/// A filter that halves the amplitude has a frequency response of -6 dB.
+#[test]
+fn test_difference_spectrum() {
+    let input = sine_wave_audio_data_multiple(&[1000.0], 44100, 1000)
+        .into_iter()
+        .take(2048)
+        .map(|x| x as f32)
+        .collect::<Vec<_>>();
+    let output = input.iter().map(|x| x * 0.5).collect::<Vec<_>>();
+
+    let response = difference_spectrum(
+        &output,
+        &input,
+        44100,
+        WindowType::Hann,
+        FrequencyLimit::All,
+        Some(&scale_20_times_log10),
+    )
+    .unwrap();
+    assert_eq!(response.data().len(), 1025);
+    float_cmp::assert_approx_eq!(
+        f32,
+        response.freq_val_closest(1000.0).1.val(),
+        -6.0206,
+        epsilon = 1e-3
+    );
+
+    // without scaling, it is the difference of the magnitudes
+    let difference = difference_spectrum(
+        &input,
+        &output,
+        44100,
+        WindowType::Hann,
+        FrequencyLimit::All,
+        None,
+    )
+    .unwrap();
+    let expected =
+        samples_fft_to_spectrum(&hann_window(&output), 44100, FrequencyLimit::All, None).unwrap();
+    for ((_fr, diff), (_fr_expected, expected)) in difference.data().iter().zip(expected.data()) {
+        float_cmp::assert_approx_eq!(f32, diff.val(), expected.val(), epsilon = 1e-2);
+    }
+
+    assert!(matches!(
+        difference_spectrum(
+            &input,
+            &output[..1024],
+            44100,
+            WindowType::Hann,
+            FrequencyLimit::All,
+            None,
+        ),
+        Err(SpectrumAnalyzerError::SamplesLengthMismatch(2048, 1024))
+    ));
+}
+
+/// A file with 440 Hz in the first half and 1000 Hz in the second half.
+#[test]
+fn test_analyze_chunks() {
+    let sampling_rate = 8000;
+    let low_tone = sine_wave(440.0);
+    let high_tone = sine_wave(1000.0);
+    // 8.1 chunks of 1024 samples
+    let samples = (0..8 * 1024 + 100)
+        .map(|i| {
+            let t = i as f32 / sampling_rate as f32;
+            if i < 4 * 1024 {
+                low_tone(t)
+            } else {
+                high_tone(t)
+            }
+        })
+        .collect::<Vec<_>>();
+    let analyze = |remainder| {
+        analyze_chunks(
+            &samples,
+            sampling_rate,
+            1024,
+            WindowType::Hann,
+            FrequencyLimit::All,
+            None,
+            remainder,
+        )
+        .unwrap()
+    };
+
+    let analysis = analyze(Remainder::Drop);
+    assert_eq!(analysis.peak_frequencies.len(), 8);
+    for (i, peak_fr) in analysis.peak_frequencies.iter().enumerate() {
+        let expected = if i < 4 { 440.0 } else { 1000.0 };
+        assert!((peak_fr.val() - expected).abs() <= 8.0, "{}", peak_fr);
+    }
+    // both tones are part of the aggregates
+    for aggregate in [&analysis.average, &analysis.max_hold] {
+        for tone in [440.0, 1000.0] {
+            assert!(aggregate.freq_val_closest(tone).1.val() > 100.0 * aggregate.median().val());
+        }
+    }
+    // each tone is present in half of the chunks
+    float_cmp::assert_approx_eq!(
+        f32,
+        analysis.average.freq_val_closest(1000.0).1.val(),
+        analysis.max_hold.freq_val_closest(1000.0).1.val() / 2.0,
+        epsilon = 0.1
+    );
+
+    let analysis = analyze(Remainder::Pad);
+    assert_eq!(analysis.peak_frequencies.len(), 9);
+    assert_eq!(analysis.average.data().len(), 513);
+
+    assert!(matches!(
+        analyze_chunks(
+            &samples[..1000],
+            sampling_rate,
+            1024,
+            WindowType::Hann,
+            FrequencyLimit::All,
+            None,
+            Remainder::Drop,
+        ),
+        Err(SpectrumAnalyzerError::TooFewSamples)
+    ));
+}
+
+#[test]
+fn test_spectrogram_time_axis() {
+    assert_eq!(window_duration_seconds(1024, 8000), 0.128);
+    float_cmp::assert_approx_eq!(
+        f32,
+        window_duration_seconds(4096, 44100),
+        0.092_88,
+        epsilon = 1e-5
+    );
+    assert_eq!(frame_time_seconds(0, 512, 8000), 0.0);
+    assert_eq!(frame_time_seconds(10, 400, 8000), 0.5);
+
+    let config = ChunkConfig {
+        chunk_len: 1024,
+        hop_len: 256,
+        window: WindowType::Hann,
+        frequency_limit: FrequencyLimit::All,
+    };
+    let frames = analyze_file_samples_timed(&[0.0; 4096], 8000, &config, None, None).unwrap();
+    for (i, frame) in frames.iter().enumerate() {
+        assert_eq!(frame.start_seconds, frame_time_seconds(i, 256, 8000));
+        assert_eq!(
+            frame.duration_seconds(),
+            window_duration_seconds(1024, 8000)
+        );
+    }
+}
+
+#[test]
+fn test_average_segments() {
+    let sampling_rate = 8000;
+    let config = ChunkConfig {
+        chunk_len: 256,
+        hop_len: 256,
+        window: WindowType::Hann,
+        frequency_limit: FrequencyLimit::All,
+    };
+    // 64 segments of deterministic white noise
+    let noise = crate::signal_gen::white_noise(1.0, 256 * 64, 1000, 0x1234_5678);
+    let mean_std_dev = |num_segments: usize| {
+        let averaged =
+            average_segments(&noise[..256 * num_segments], sampling_rate, &config, None).unwrap();
+        assert_eq!(averaged.num_segments, num_segments);
+        assert_eq!(averaged.mean.data().len(), averaged.std_dev.data().len());
+        averaged.std_dev.average().val()
+    };
+    let (few, many) = (mean_std_dev(4), mean_std_dev(64));
+    assert!(few > 0.0);
+    // the spread of the noise doesn't depend on the number of segments, but
+    // the standard error of the mean, i.e., `std_dev / sqrt(num_segments)`,
+    // decreases
+    assert!(many > few / 2.0 && many < few * 2.0, "{} vs {}", many, few);
+    assert!(many / 8.0 < few / 2.0 / 2.0, "{} vs {}", many, few);
+
+    // every segment of the sine is identical
+    let sine = sine_wave(1000.0);
+    let samples = (0..256 * 8)
+        .map(|i| sine(i as f32 / sampling_rate as f32))
+        .collect::<Vec<_>>();
+    let averaged = average_segments(&samples, sampling_rate, &config, None).unwrap();
+    let (peak_fr, peak_val) = averaged.mean.max();
+    assert_eq!(peak_fr.val(), 1000.0);
+    assert!(averaged.std_dev.freq_val_exact(1000.0).val() < 1e-3 * peak_val.val());
+
+    // a single segment
+    let averaged = average_segments(&samples[..256], sampling_rate, &config, None).unwrap();
+    assert_eq!(averaged.num_segments, 1);
+    assert_eq!(averaged.std_dev.max().1.val(), 0.0);
+}
+
+/// The reassigned points of a linear chirp are much closer to its
+/// instantaneous frequency than the centers of the bins and frames.
+#[test]
+fn test_reassigned_spectrogram() {
+    let sampling_rate = 8000;
+    // 500 Hz to 2500 Hz in one second
+    let (f0, slope) = (500.0, 2000.0);
+    let instantaneous_frequency = |t: f32| f0 + slope * t;
+    let samples = (0..sampling_rate)
+        .map(|i| {
+            let t = i as f32 / sampling_rate as f32;
+            libm::sinf(2.0 * core::f32::consts::PI * (f0 * t + slope * t * t / 2.0))
+        })
+        .collect::<Vec<_>>();
+    let config = ChunkConfig {
+        chunk_len: 256,
+        hop_len: 64,
+        window: WindowType::Hann,
+        frequency_limit: FrequencyLimit::All,
+    };
+
+    let frames = reassigned_spectrogram(&samples, sampling_rate, &config).unwrap();
+    let spectra = analyze_file_samples(&samples, sampling_rate, &config, None, None).unwrap();
+    assert_eq!(frames.len(), spectra.len());
+
+    // mean absolute deviation of the strong points of the full frames
+    let mean_deviation = |points: &mut dyn Iterator<Item = (f32, f32, f32)>| {
+        let points = points.collect::<Vec<_>>();
+        let max = points.iter().map(|(_t, _fr, val)| *val).fold(0.0, f32::max);
+        let strong_points = points
+            .iter()
+            .filter(|(_t, _fr, val)| *val > 0.1 * max)
+            .collect::<Vec<_>>();
+        strong_points
+            .iter()
+            .map(|(t, fr, _val)| (fr - instantaneous_frequency(*t)).abs())
+            .sum::<f32>()
+            / strong_points.len() as f32
+    };
+    let full_frames = 120;
+    let reassigned_deviation = mean_deviation(&mut frames[..full_frames].iter().flatten().copied());
+    let plain_deviation = mean_deviation(&mut spectra[..full_frames].iter().enumerate().flat_map(
+        |(i, spectrum)| {
+            // the center of the frame and of each bin
+            let t = (i * config.hop_len + config.chunk_len / 2) as f32 / sampling_rate as f32;
+            spectrum
+                .data()
+                .iter()
+                .map(move |(fr, val)| (t, fr.val(), val.val()))
+        },
+    ));
+    assert!(
+        reassigned_deviation < plain_deviation / 4.0,
+        "{} Hz vs {} Hz",
+        reassigned_deviation,
+        plain_deviation
+    );
+}
+
+#[test]
+fn test_bin_value_power() {
+    let sine = sine_wave(1000.0);
+    let samples = (0..1024)
+        .map(|i| sine(i as f32 / 44100.0))
+        .collect::<Vec<_>>();
+    let samples = hann_window(&samples);
+    let magnitudes = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+    let powers = samples_fft_to_spectrum_with_config(
+        &samples,
+        44100,
+        FrequencyLimit::All,
+        None,
+        &SpectrumConfig {
+            bin_value: BinValue::Power,
+            ..SpectrumConfig::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(powers.data().len(), magnitudes.data().len());
+    for ((fr, power), (fr_magnitude, magnitude)) in powers.data().iter().zip(magnitudes.data()) {
+        assert_eq!(fr, fr_magnitude);
+        float_cmp::assert_approx_eq!(
+            f32,
+            power.val(),
+            magnitude.val() * magnitude.val(),
+            epsilon = 1e-3 * magnitude.val() * magnitude.val() + 1e-9
+        );
+    }
+    assert_eq!(powers.max().0, magnitudes.max().0);
+}
+
+#[test]
+fn test_min_magnitude() {
+    let samples = sine_wave_audio_data_multiple(&[1000.0, 5000.0], 44100, 1000)
+        .into_iter()
+        .take(2048)
+        .map(|x| x as f32)
+        .collect::<Vec<_>>();
+    let samples = hann_window(&samples);
+    let full = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+    let threshold = 0.01 * full.max().1.val();
+    let config = SpectrumConfig {
+        min_magnitude: Some(threshold),
+        ..SpectrumConfig::default()
+    };
+    let pruned =
+        samples_fft_to_spectrum_with_config(&samples, 44100, FrequencyLimit::All, None, &config)
+            .unwrap();
+
+    // essentially only the main lobes of both tones remain
+    assert!(pruned.data().len() < 20, "{}", pruned.data().len());
+    assert!(pruned
+        .data()
+        .iter()
+        .all(|(_fr, val)| val.val() >= threshold));
+    assert_eq!(
+        pruned.data(),
+        full.data()
+            .iter()
+            .filter(|(_fr, val)| val.val() >= threshold)
+            .copied()
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(pruned.max(), full.max());
+    // the statistics only cover the retained bins
+    assert!(pruned.average() > full.average());
+    assert!(pruned.min().1.val() >= threshold);
+    assert_peak_near(&pruned, 5000.0, 25.0);
+
+    // silence
+    assert!(matches!(
+        samples_fft_to_spectrum_with_config(&[0.0; 64], 44100, FrequencyLimit::All, None, &config),
+        Err(SpectrumAnalyzerError::TooFewFrequencyBins)
+    ));
+}
+
+/// Plucked notes with an abrupt attack and an exponential decay.
+#[test]
+fn test_detect_onsets() {
+    let sampling_rate = 8000;
+    let note_starts = [0.25, 0.8, 1.3];
+    let tone = sine_wave(1000.0);
+    let samples = (0..2 * sampling_rate)
+        .map(|i| {
+            let t = i as f32 / sampling_rate as f32;
+            note_starts
+                .iter()
+                .filter(|start| t >= **start)
+                .map(|start| libm::expf(-(t - start) / 0.05) * tone(t))
+                .sum()
+        })
+        .collect::<Vec<_>>();
+
+    let onsets = detect_onsets(&samples, sampling_rate, 512, 128, 0.1).unwrap();
+    assert_eq!(onsets.len(), note_starts.len(), "{:?}", onsets);
+    // within the duration of a frame
+    for (onset, start) in onsets.iter().zip(note_starts) {
+        assert!((onset - start).abs() < 0.064, "{} vs {}", onset, start);
+    }
+
+    assert!(detect_onsets(&[0.0; 4096], sampling_rate, 512, 128, 0.1)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_long_term_average_spectrum() {
+    let sampling_rate = 8000;
+    // full scale sine
+    let sine = sine_wave(1000.0);
+    let samples = (0..sampling_rate)
+        .map(|i| sine(i as f32 / sampling_rate as f32))
+        .collect::<Vec<_>>();
+    let ltas = long_term_average_spectrum(&samples, sampling_rate, 512).unwrap();
+    assert_eq!(ltas.data().len(), 257);
+    assert_eq!(ltas.max().0.val(), 1000.0);
+    float_cmp::assert_approx_eq!(f32, ltas.max().1.val(), 0.0, epsilon = 0.1);
+
+    // eight seconds of deterministic white noise
+    let noise = crate::signal_gen::white_noise(0.5, sampling_rate, 8000, 0x1234_5678);
+    let ltas = long_term_average_spectrum(&noise, sampling_rate, 512).unwrap();
+    // the spectrum of a single frame in dBFS
+    let single_frame = samples_fft_to_spectrum(
+        &hann_window(&noise[..512]),
+        sampling_rate,
+        FrequencyLimit::All,
+        Some(&|val, _stats| 20.0 * libm::log10f(val / 128.0)),
+    )
+    .unwrap();
+    // without the DC component and the Nyquist frequency
+    let std_dev = |spectrum: &crate::FrequencySpectrum| {
+        let vals = spectrum.data()[1..256]
+            .iter()
+            .map(|(_fr, val)| val.val())
+            .collect::<Vec<_>>();
+        let mean = vals.iter().sum::<f32>() / vals.len() as f32;
+        let variance = vals
+            .iter()
+            .map(|val| (val - mean) * (val - mean))
+            .sum::<f32>()
+            / vals.len() as f32;
+        (mean, libm::sqrtf(variance))
+    };
+    let (ltas_mean, ltas_std_dev) = std_dev(&ltas);
+    let (single_mean, single_std_dev) = std_dev(&single_frame);
+    // the same flat shape and level; the log of a single frame is biased
+    // slightly downwards
+    assert!(
+        (ltas_mean - single_mean).abs() < 4.0,
+        "{} vs {}",
+        ltas_mean,
+        single_mean
+    );
+    assert!(
+        ltas_std_dev < single_std_dev / 4.0,
+        "{} vs {}",
+        ltas_std_dev,
+        single_std_dev
+    );
+}
+
+#[test]
+fn test_sampling_rate_guard_rails() {
+    let samples = vec![0.5; 4096];
+    // zero
+    let err = samples_fft_to_spectrum(&samples, 0, FrequencyLimit::All, None).unwrap_err();
+    assert!(matches!(err, SpectrumAnalyzerError::ZeroSamplingRate));
+    let err = samples_fft_to_mag_phase(&samples, 0, FrequencyLimit::All).unwrap_err();
+    assert!(matches!(err, SpectrumAnalyzerError::ZeroSamplingRate));
+
+    // one: accepted by default but rejected if the window duration is limited
+    let config = SpectrumConfig {
+        max_window_duration: Some(10.0),
+        ..SpectrumConfig::default()
+    };
+    let _ = samples_fft_to_spectrum(&samples, 1, FrequencyLimit::All, None).unwrap();
+    let err = samples_fft_to_spectrum_with_config(&samples, 1, FrequencyLimit::All, None, &config)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        SpectrumAnalyzerError::WindowDurationTooLong(duration, max)
+            if duration == 4096.0 && max == 10.0
+    ));
+
+    // normal
+    let spectrum =
+        samples_fft_to_spectrum_with_config(&samples, 44100, FrequencyLimit::All, None, &config)
+            .unwrap();
+    assert_eq!(spectrum.max_fr().val(), 22050.0);
+}
+
+#[test]
+fn test_include_nyquist() {
+    // a sine at the Nyquist frequency, i.e., alternating samples
+    let samples = (0..1024)
+        .map(|i| if i % 2 == 0 { 1.0 } else { -1.0 })
+        .collect::<Vec<f32>>();
+
+    let spectrum = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+    assert_eq!(spectrum.data().len(), 513);
+    assert_eq!(spectrum.max_fr().val(), 22050.0);
+    assert_eq!(spectrum.max().0.val(), 22050.0);
+    float_cmp::assert_approx_eq!(f32, spectrum.max().1.val(), 1024.0, epsilon = 0.1);
+
+    let config = SpectrumConfig {
+        include_nyquist: false,
+        ..SpectrumConfig::default()
+    };
+    let spectrum =
+        samples_fft_to_spectrum_with_config(&samples, 44100, FrequencyLimit::All, None, &config)
+            .unwrap();
+    assert_eq!(spectrum.data().len(), 512);
+    assert_eq!(spectrum.max_fr().val(), 22050.0 - 44100.0 / 1024.0);
+    // the signal is gone
+    assert!(spectrum.max().1.val() < 1e-3, "{}", spectrum.max().1.val());
+}
+
+#[test]
+fn test_analysis_metadata() {
+    let config = ChunkConfig {
+        chunk_len: 1024,
+        hop_len: 512,
+        window: WindowType::Hann,
+        frequency_limit: FrequencyLimit::All,
+    };
+    let samples = sine_wave_audio_data_multiple(&[1000.0], 8000, 500)
+        .into_iter()
+        .map(f32::from)
+        .collect::<Vec<_>>();
+    let frames = analyze_file_samples_timed(&samples, 8000, &config, None, None).unwrap();
+    let metadata = frames[0].metadata.clone();
+    assert_eq!(metadata, AnalysisMetadata::new(8000, &config));
+    assert_eq!(
+        format!("{}", metadata),
+        format!(
+            "window=Hann fft_len=1024 hop_len=512 sampling_rate=8000 frequency_limit=All scaling=none version={}",
+            env!("CARGO_PKG_VERSION")
+        )
+    );
+
+    let average = average_timed_spectra(&frames).unwrap();
+    assert_eq!(average.data().len(), 513);
+    assert_eq!(average.max().0.val(), 1000.0);
+
+    // the hop length doesn't matter
+    let other_config = ChunkConfig {
+        hop_len: 1024,
+        ..config
+    };
+    let mut other_frames =
+        analyze_file_samples_timed(&samples, 8000, &other_config, None, None).unwrap();
+    assert!(metadata.compatible_with(&other_frames[0].metadata));
+
+    // but the window does
+    other_frames[0].metadata.window = WindowType::Hamming;
+    assert!(!metadata.compatible_with(&other_frames[0].metadata));
+    let mixed = frames.into_iter().chain(other_frames).collect::<Vec<_>>();
+    assert!(matches!(
+        average_timed_spectra(&mixed),
+        Err(SpectrumAnalyzerError::IncompatibleMetadata)
+    ));
+
+    // and the scaling
+    let scaled = AnalysisMetadata::new(8000, &config).with_scaling("20log10");
+    assert!(!metadata.compatible_with(&scaled));
+    assert!(scaled.compatible_with(&scaled.clone()));
+
+    // spectra with and without a scaling function aren't averaged together
+    let scaled_frames =
+        analyze_file_samples_timed(&samples, 8000, &config, Some(&scale_20_times_log10), None)
+            .unwrap();
+    assert_eq!(
+        scaled_frames[0].metadata.scaling.as_deref(),
+        Some(AnalysisMetadata::UNNAMED_SCALING)
+    );
+    assert!(!metadata.compatible_with(&scaled_frames[0].metadata));
+    let mixed = scaled_frames
+        .into_iter()
+        .chain(analyze_file_samples_timed(&samples, 8000, &config, None, None).unwrap())
+        .collect::<Vec<_>>();
+    assert!(matches!(
+        average_timed_spectra(&mixed),
+        Err(SpectrumAnalyzerError::IncompatibleMetadata)
+    ));
+    let limited = AnalysisMetadata {
+        window: WindowType::BlackmanHarris4Term,
+        frequency_limit: FrequencyLimit::Range(20.0, 2000.5),
+        ..scaled
+    };
+    assert_eq!(
+        format!("{}", limited),
+        format!(
+            "window=BlackmanHarris4Term fft_len=1024 hop_len=512 sampling_rate=8000 frequency_limit=Range(20,2000.5) scaling=20log10 version={}",
+            env!("CARGO_PKG_VERSION")
+        )
+    );
+
+    assert!(matches!(
+        average_timed_spectra(&[]),
+        Err(SpectrumAnalyzerError::TooFewSamples)
+    ));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_analysis_metadata_serde() {
+    let config = ChunkConfig {
+        chunk_len: 1024,
+        hop_len: 512,
+        window: WindowType::BlackmanHarris4Term,
+        frequency_limit: FrequencyLimit::Range(20.0, 2000.0),
+    };
+    let metadata = AnalysisMetadata::new(8000, &config).with_scaling("20log10");
+    let json = serde_json::to_string(&metadata).unwrap();
+    let deserialized = serde_json::from_str::<AnalysisMetadata>(&json).unwrap();
+    assert_eq!(deserialized, metadata);
+}
+
+#[test]
+fn test_coherent_gain_correction() {
+    // a sine with amplitude 1.0 exactly on bin 64
+    let samples = (0..1024)
+        .map(|i| libm::sinf(2.0 * core::f32::consts::PI * 64.0 * i as f32 / 1024.0))
+        .collect::<Vec<_>>();
+
+    let peak_magnitude = |window: WindowType, bin_value: BinValue| {
+        let coefficients = window.apply(&[1.0; 1024]);
+        let config = SpectrumConfig {
+            coherent_gain: Some(coherent_gain(&coefficients)),
+            bin_value,
+            ..SpectrumConfig::default()
+        };
+        let spectrum = samples_fft_to_spectrum_with_config(
+            &window.apply(&samples),
+            1024,
+            FrequencyLimit::All,
+            None,
+            &config,
+        )
+        .unwrap();
+        assert_eq!(spectrum.max().0.val(), 64.0);
+        spectrum.max().1.val()
+    };
+
+    // without correction, the windows attenuate the sine differently; with it,
+    // all have the magnitude N/2 of a rectangular window
+    for window in [WindowType::Rectangular, WindowType::Hann, WindowType::Welch] {
+        let magnitude = peak_magnitude(window, BinValue::Magnitude);
+        assert!(
+            (magnitude - 512.0).abs() < 1.0,
+            "{:?}: {}",
+            window,
+            magnitude
+        );
+        let power = peak_magnitude(window, BinValue::Power);
+        assert!(
+            (power - 512.0 * 512.0).abs() < 1000.0,
+            "{:?}: {}",
+            window,
+            power
+        );
+    }
+
+    // a gain of zero or below would result in infinite or negative values
+    for gain in [0.0, -0.5, f32::NAN] {
+        let err = samples_fft_to_spectrum_with_config(
+            &samples,
+            1024,
+            FrequencyLimit::All,
+            None,
+            &SpectrumConfig {
+                coherent_gain: Some(gain),
+                ..SpectrumConfig::default()
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, SpectrumAnalyzerError::InvalidCoherentGain(_)));
+    }
+
+    assert_eq!(coherent_gain(&[1.0; 16]), 1.0);
+    assert_eq!(coherent_gain(&[]), 1.0);
+    float_cmp::assert_approx_eq!(
+        f32,
+        coherent_gain(&WindowType::Hann.apply(&[1.0; 4096])),
+        WindowType::Hann.coherent_gain(),
+        epsilon = 0.001
+    );
+}
+
+#[test]
+fn test_minimal_dominant_frequency() {
+    let samples = sine_wave_audio_data_multiple(&[1000.0], 44100, 1000)
+        .into_iter()
+        .map(|x| x as f32)
+        .take(1024)
+        .collect::<Vec<_>>();
+    let samples: &[f32; 1024] = samples.as_slice().try_into().unwrap();
+    let resolution = 44100.0 / 1024.0;
+
+    let (frequency, allocations) = count_allocations(|| dominant_frequency(samples, 44100));
+    assert!((frequency.unwrap() - 1000.0).abs() <= resolution / 2.0);
+    assert_eq!(allocations, 1);
+
+    // one-time setup
+    let mut scratch = [0.0; 1024];
+    for _ in 0..3 {
+        let (frequency, allocations) =
+            count_allocations(|| dominant_frequency_with_scratch(samples, 44100, &mut scratch));
+        assert!((frequency.unwrap() - 1000.0).abs() <= resolution / 2.0);
+        assert_eq!(allocations, 0);
+    }
+
+    // same result as the full analysis
+    let spectrum =
+        samples_fft_to_spectrum(&hann_window(samples), 44100, FrequencyLimit::Min(1.0), None)
+            .unwrap();
+    assert_eq!(
+        dominant_frequency(samples, 44100).unwrap(),
+        spectrum.max().0.val()
+    );
+
+    assert_eq!(dominant_frequency(&[0.0; 64], 44100).unwrap(), 0.0);
+    // alternating signal: Nyquist frequency
+    let alternating = (0..64)
+        .map(|i| if i % 2 == 0 { 1.0 } else { -1.0 })
+        .collect::<Vec<_>>();
+    assert_eq!(dominant_frequency(&alternating, 44100).unwrap(), 22050.0);
+    assert!(matches!(
+        dominant_frequency_with_scratch(samples, 44100, &mut [0.0; 512]),
+        Err(SpectrumAnalyzerError::ScratchBufferTooSmall(512, 1024))
+    ));
+    assert!(matches!(
+        dominant_frequency(&[0.0; 1000], 44100),
+        Err(SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo)
+    ));
+}
+
+#[test]
+fn test_spectrum_dual() {
+    let samples = sine_wave_audio_data_multiple(&[440.0, 3000.0], 44100, 1000)
+        .into_iter()
+        .map(|x| x as f32)
+        .take(2048)
+        .collect::<Vec<_>>();
+    let samples = hann_window(&samples);
+
+    for scaling_fn in [
+        &scale_20_times_log10 as &SpectrumScalingFunction,
+        &scale_to_zero_to_one,
+    ] {
+        let (raw, scaled) = samples_fft_to_spectrum_dual(
+            &samples,
+            44100,
+            FrequencyLimit::All,
+            scaling_fn,
+            &SpectrumConfig::default(),
+        )
+        .unwrap();
+        let expected_raw =
+            samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+        assert_eq!(raw.data(), expected_raw.data());
+        assert_eq!(raw.frame_rms(), expected_raw.frame_rms());
+
+        // scaling the raw spectrum after the fact gives the same values
+        let mut scaled_raw =
+            samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+        scaled_raw.apply_scaling_fn(scaling_fn, &mut []).unwrap();
+        assert_eq!(scaled.data(), scaled_raw.data());
+        assert_eq!(scaled.median(), scaled_raw.median());
+        assert_eq!(scaled.max(), scaled_raw.max());
+    }
+
+    // the limit applies to both, the scaling sees the full spectrum
+    let config = SpectrumConfig {
+        limit_stage: LimitStage::AfterScaling,
+        ..SpectrumConfig::default()
+    };
+    let (raw, scaled) = samples_fft_to_spectrum_dual(
+        &samples,
+        44100,
+        FrequencyLimit::Min(1000.0),
+        &scale_to_zero_to_one,
+        &config,
+    )
+    .unwrap();
+    let expected_scaled = samples_fft_to_spectrum_with_config(
+        &samples,
+        44100,
+        FrequencyLimit::Min(1000.0),
+        Some(&scale_to_zero_to_one),
+        &config,
+    )
+    .unwrap();
+    assert_eq!(scaled.data(), expected_scaled.data());
+    assert_eq!(raw.frequencies(), scaled.frequencies());
+    assert!(raw.min_fr().val() >= 1000.0);
+}
+
+#[test]
+fn test_frequency_limit_bins() {
+    let samples = sine_wave_audio_data_multiple(&[50.0, 1000.0, 3777.0], 44100, 1000)
+        .into_iter()
+        .map(|x| x as f32)
+        .take(1024)
+        .collect::<Vec<_>>();
+    let frequency_resolution = 44100.0 / 1024.0;
+
+    let spectrum =
+        samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::Bins(1, 129), None).unwrap();
+    assert_eq!(spectrum.data().len(), 128);
+    assert!(spectrum.dc_component().is_none());
+    assert_eq!(spectrum.min_fr().val(), frequency_resolution);
+    assert_eq!(spectrum.max_fr().val(), 128.0 * frequency_resolution);
+
+    // equivalent to the range with the exact frequencies of the bins
+    let range_spectrum = samples_fft_to_spectrum(
+        &samples,
+        44100,
+        FrequencyLimit::Range(frequency_resolution, 128.0 * frequency_resolution),
+        None,
+    )
+    .unwrap();
+    assert_eq!(spectrum.data(), range_spectrum.data());
+
+    // also after scaling
+    let config = SpectrumConfig {
+        limit_stage: LimitStage::AfterScaling,
+        ..SpectrumConfig::default()
+    };
+    let scaled_spectrum = samples_fft_to_spectrum_with_config(
+        &samples,
+        44100,
+        FrequencyLimit::Bins(1, 129),
+        Some(&scale_to_zero_to_one),
+        &config,
+    )
+    .unwrap();
+    assert_eq!(scaled_spectrum.frequencies(), spectrum.frequencies());
+
+    let mag_phase =
+        samples_fft_to_mag_phase(&samples, 44100, FrequencyLimit::Bins(1, 129)).unwrap();
+    assert_eq!(mag_phase.len(), 128);
+
+    // all bins up to and including the Nyquist frequency
+    let spectrum =
+        samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::Bins(0, 513), None).unwrap();
+    assert_eq!(spectrum.data().len(), 513);
+    for limit in [FrequencyLimit::Bins(0, 514), FrequencyLimit::Bins(5, 5)] {
+        assert!(matches!(
+            samples_fft_to_spectrum(&samples, 44100, limit, None),
+            Err(SpectrumAnalyzerError::InvalidFrequencyLimit(_))
+        ));
+    }
+    // without the Nyquist frequency, there is one bin less
+    let config = SpectrumConfig {
+        include_nyquist: false,
+        ..SpectrumConfig::default()
+    };
+    assert!(matches!(
+        samples_fft_to_spectrum_with_config(
+            &samples,
+            44100,
+            FrequencyLimit::Bins(0, 513),
+            None,
+            &config
+        ),
+        Err(SpectrumAnalyzerError::InvalidFrequencyLimit(_))
+    ));
+}
+
+#[test]
+fn test_analyze_slice() {
+    let full = sine_wave_audio_data_multiple(&[440.0, 1000.0], 44100, 1000)
+        .into_iter()
+        .map(|x| x as f32)
+        .collect::<Vec<_>>();
+    let len = 2048;
+    let window = WindowType::Hann.apply(&[1.0; 2048]);
+
+    let mut buffer = Vec::new();
+    let mut buffer_ptr = None;
+    for offset in [0, 1, 1000, full.len() - len] {
+        let spectrum = analyze_slice(
+            &full,
+            offset,
+            len,
+            &window,
+            44100,
+            FrequencyLimit::All,
+            Some(&scale_to_zero_to_one),
+            &mut buffer,
+        )
+        .unwrap();
+        let expected = samples_fft_to_spectrum(
+            &hann_window(&full[offset..offset + len]),
+            44100,
+            FrequencyLimit::All,
+            Some(&scale_to_zero_to_one),
+        )
+        .unwrap();
+        assert_eq!(spectrum.data(), expected.data());
+        // level of the slice without the window
+        let slice = &full[offset..offset + len];
+        let peak = slice.iter().fold(0.0_f32, |peak, x| peak.max(x.abs()));
+        assert_eq!(spectrum.frame_peak(), peak);
+
+        // the buffer is reused
+        assert!(buffer_ptr.map_or(true, |ptr| ptr == buffer.as_ptr()));
+        buffer_ptr = Some(buffer.as_ptr());
+    }
+
+    assert!(matches!(
+        analyze_slice(
+            &full,
+            full.len() - len + 1,
+            len,
+            &window,
+            44100,
+            FrequencyLimit::All,
+            None,
+            &mut buffer
+        ),
+        Err(SpectrumAnalyzerError::SliceOutOfBounds(_, _))
+    ));
+    assert!(matches!(
+        analyze_slice(
+            &full,
+            0,
+            1024,
+            &window,
+            44100,
+            FrequencyLimit::All,
+            None,
+            &mut buffer
+        ),
+        Err(SpectrumAnalyzerError::WindowLengthMismatch(2048, 1024))
+    ));
+}
+
+#[test]
+fn test_unit_energy_normalization() {
+    let samples = sine_wave_audio_data_multiple(&[440.0, 2000.0], 44100, 1000)
+        .into_iter()
+        .map(|x| x as f32)
+        .take(2048)
+        .collect::<Vec<_>>();
+    let samples = hann_window(&samples);
+
+    let raw = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+    let stats = SpectrumDataStats::from(&raw);
+    assert_eq!(stats.sum, raw.average().val() * raw.data().len() as f32);
+    let energy = raw
+        .data()
+        .iter()
+        .map(|(_fr, val)| val.val() * val.val())
+        .sum::<f32>();
+    assert_eq!(stats.sum_of_squares, energy);
+    assert_eq!(raw.sum_of_squares(), energy);
+
+    // unit energy, purely as scaling function
+    let unit_energy: &SpectrumScalingFunction =
+        &|val, stats| val / libm::sqrtf(stats.sum_of_squares);
+    let normalized =
+        samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, Some(unit_energy)).unwrap();
+    float_cmp::assert_approx_eq!(f32, normalized.sum_of_squares(), 1.0, epsilon = 1e-5);
+    float_cmp::assert_approx_eq!(
+        f32,
+        normalized.band_energy(0.0, 22050.0),
+        1.0,
+        epsilon = 1e-5
+    );
+
+    // unit sum
+    let unit_sum: &SpectrumScalingFunction = &|val, stats| val / stats.sum;
+    let normalized =
+        samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, Some(unit_sum)).unwrap();
+    float_cmp::assert_approx_eq!(f32, normalized.sum(), 1.0, epsilon = 1e-5);
+
+    let range_stats = raw.stats_in_range(0.0, 22050.0).unwrap();
+    float_cmp::assert_approx_eq!(f32, range_stats.sum, stats.sum, epsilon = stats.sum * 1e-5);
+    float_cmp::assert_approx_eq!(
+        f32,
+        range_stats.sum_of_squares,
+        stats.sum_of_squares,
+        epsilon = stats.sum_of_squares * 1e-5
+    );
+}
@@ -26,10 +26,158 @@ SOFTWARE.
 //! - <https://en.wikipedia.org/wiki/Window_function>
 //! - <https://www.youtube.com/watch?v=dCeHOf4cJE0> (FFT and windowing by Texas Instruments)
 
+use crate::error::SpectrumAnalyzerError;
+use crate::Complex32;
 use alloc::vec::Vec;
 use core::f32::consts::PI;
 // replacement for std functions like sin and cos in no_std-environments
-use libm::cosf;
+use libm::{cosf, expf, powf};
+
+// constants come from here:
+// https://en.wikipedia.org/wiki/Window_function#Blackman%E2%80%93Harris_window
+const BLACKMAN_HARRIS_4TERM_ALPHA: [f32; 4] = [0.35875, -0.48829, 0.14128, -0.01168];
+
+// constants come from here:
+// https://dsp.stackexchange.com/questions/51095/seven-term-blackman-harris-window
+const BLACKMAN_HARRIS_7TERM_ALPHA: [f32; 7] = [
+    0.271_051_4,
+    -0.433_297_93,
+    0.218_123,
+    -0.065_925_45,
+    0.010_811_742,
+    -0.000_776_584_84,
+    0.000_013_887_217,
+];
+
+/// Enumerates the window functions of this module.
+///
+/// This is useful, if the window is a parameter of an analysis, such as
+/// [`crate::spectrogram::ChunkConfig::window`], and for correction factors
+/// that depend on the window.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WindowType {
+    /// No window at all, i.e., all coefficients are `1.0`. See
+    /// [`rectangular_window`].
+    Rectangular,
+    /// See [`hann_window`].
+    Hann,
+    /// See [`hamming_window`].
+    Hamming,
+    /// See [`blackman_harris_4term`].
+    BlackmanHarris4Term,
+    /// See [`blackman_harris_7term`].
+    BlackmanHarris7Term,
+    /// See [`welch_window`].
+    Welch,
+}
+
+impl WindowType {
+    /// Applies the window function to the samples.
+    ///
+    /// ## Return value
+    /// New vector with the window applied to the values.
+    #[must_use]
+    pub fn apply(&self, samples: &[f32]) -> Vec<f32> {
+        match self {
+            Self::Rectangular => rectangular_window(samples),
+            Self::Hann => hann_window(samples),
+            Self::Hamming => hamming_window(samples),
+            Self::BlackmanHarris4Term => blackman_harris_4term(samples),
+            Self::BlackmanHarris7Term => blackman_harris_7term(samples),
+            Self::Welch => welch_window(samples),
+        }
+    }
+
+    /// Returns the coherent gain of the window, i.e., the average of all
+    /// window coefficients. The magnitude of a sinusoid in the spectrum is
+    /// scaled by this factor.
+    #[must_use]
+    pub const fn coherent_gain(&self) -> f32 {
+        match self {
+            Self::Rectangular => 1.0,
+            Self::Hann => 0.5,
+            Self::Hamming => 0.54,
+            Self::BlackmanHarris4Term => 0.35875,
+            Self::BlackmanHarris7Term => 0.271_051_4,
+            Self::Welch => 2.0 / 3.0,
+        }
+    }
+
+    /// Returns the half width of the main lobe of the window in bins, i.e.,
+    /// the distance from the center of the main lobe to its first zero,
+    /// rounded up. A sinusoid leaks into this many bins on each side.
+    #[must_use]
+    pub const fn main_lobe_half_width(&self) -> usize {
+        match self {
+            Self::Rectangular => 1,
+            Self::Hann | Self::Hamming | Self::Welch => 2,
+            Self::BlackmanHarris4Term => 4,
+            Self::BlackmanHarris7Term => 7,
+        }
+    }
+
+    /// Returns the equivalent noise bandwidth (ENBW) of the window in bins,
+    /// i.e., the width of a rectangular filter that passes the same amount of
+    /// white noise as the window.
+    ///
+    /// More information: <https://en.wikipedia.org/wiki/Window_function>
+    #[must_use]
+    pub const fn enbw(&self) -> f32 {
+        match self {
+            Self::Rectangular => 1.0,
+            Self::Hann => 1.5,
+            Self::Hamming => 1.3628,
+            Self::BlackmanHarris4Term => 2.0044,
+            Self::BlackmanHarris7Term => 2.6310,
+            Self::Welch => 1.2,
+        }
+    }
+
+    /// Returns the magnitude of the Fourier transform of the window
+    /// `offset_bins` bins away from the center of the main lobe, relative to
+    /// the center, i.e., `1.0` at `0.0`. This is the analytic transform for
+    /// many samples, hence, it doesn't depend on the number of samples and
+    /// needs no window coefficients.
+    pub(crate) fn kernel(&self, offset_bins: f32) -> f32 {
+        let x = f64::from(offset_bins);
+        let alphas: &[f32] = match self {
+            Self::Rectangular => &[1.0],
+            Self::Hann => &[0.5, -0.5],
+            Self::Hamming => &[0.54, -0.46],
+            Self::BlackmanHarris4Term => &BLACKMAN_HARRIS_4TERM_ALPHA,
+            Self::BlackmanHarris7Term => &BLACKMAN_HARRIS_7TERM_ALPHA,
+            Self::Welch => return welch_kernel(x) as f32,
+        };
+        // Cosine-sum window: the term `k` contributes two sinc kernels at
+        // `+-k` bins. The signs of the terms alternate, hence, all
+        // contributions are in phase at the center of the window.
+        let kernel = alphas
+            .iter()
+            .enumerate()
+            .map(|(k, alpha)| {
+                let alpha = f64::from(alpha.abs());
+                if k == 0 {
+                    alpha * sinc(x)
+                } else {
+                    alpha / 2.0 * (sinc(x - k as f64) + sinc(x + k as f64))
+                }
+            })
+            .sum::<f64>();
+        (kernel / f64::from(alphas[0])).abs() as f32
+    }
+}
+
+/// Applies a rectangular window, i.e., no window at all, to an array of
+/// samples. This exists for symmetry with the other window functions, e.g.,
+/// to show how spectral leakage looks like without a window.
+///
+/// ## Return value
+/// New vector with the same values as `samples`.
+#[must_use]
+pub fn rectangular_window(samples: &[f32]) -> Vec<f32> {
+    samples.to_vec()
+}
 
 /// Applies a Hann window (<https://en.wikipedia.org/wiki/Window_function#Hann_and_Hamming_windows>)
 /// to an array of samples.
@@ -53,13 +201,14 @@ pub fn hann_window(samples: &[f32]) -> Vec<f32> {
 /// to an array of samples.
 ///
 /// ## Return value
-/// New vector with Hann window applied to the values.
+/// New vector with Hamming window applied to the values.
 #[must_use]
 pub fn hamming_window(samples: &[f32]) -> Vec<f32> {
     let mut windowed_samples = Vec::with_capacity(samples.len());
-    let samples_len_f32 = samples.len() as f32;
+    // symmetric window: the first and the last coefficient are equal
+    let last_index_f32 = (samples.len().max(2) - 1) as f32;
     for (i, sample) in samples.iter().enumerate() {
-        let multiplier = 0.54 - (0.46 * (2.0 * PI * i as f32 / cosf(samples_len_f32 - 1.0)));
+        let multiplier = 0.54 - 0.46 * cosf(2.0 * PI * i as f32 / last_index_f32);
         windowed_samples.push(multiplier * sample)
     }
     windowed_samples
@@ -72,11 +221,7 @@ pub fn hamming_window(samples: &[f32]) -> Vec<f32> {
 /// New vector with Blackman-Harris 4-term window applied to the values.
 #[must_use]
 pub fn blackman_harris_4term(samples: &[f32]) -> Vec<f32> {
-    // constants come from here:
-    // https://en.wikipedia.org/wiki/Window_function#Blackman%E2%80%93Harris_window
-    const ALPHA: [f32; 4] = [0.35875, -0.48829, 0.14128, -0.01168];
-
-    blackman_harris_xterm(samples, &ALPHA)
+    blackman_harris_xterm(samples, &BLACKMAN_HARRIS_4TERM_ALPHA)
 }
 
 /// Applies a Blackman-Harris 7-term window to an array of samples.
@@ -90,19 +235,260 @@ pub fn blackman_harris_4term(samples: &[f32]) -> Vec<f32> {
 /// New vector with Blackman-Harris 7-term window applied to the values.
 #[must_use]
 pub fn blackman_harris_7term(samples: &[f32]) -> Vec<f32> {
-    // constants come from here:
-    // https://dsp.stackexchange.com/questions/51095/seven-term-blackman-harris-window
-    const ALPHA: [f32; 7] = [
-        0.271_051_4,
-        -0.433_297_93,
-        0.218_123,
-        -0.065_925_45,
-        0.010_811_742,
-        -0.000_776_584_84,
-        0.000_013_887_217,
-    ];
+    blackman_harris_xterm(samples, &BLACKMAN_HARRIS_7TERM_ALPHA)
+}
+
+/// Applies a Welch window
+/// (<https://en.wikipedia.org/wiki/Window_function#Welch_window>) to an array
+/// of samples. The window is a parabola that is `0.0` at the first and the
+/// last sample and `1.0` in the center. It is the classic window for the
+/// periodogram method of Welch.
+///
+/// ## Return value
+/// New vector with Welch window applied to the values.
+#[must_use]
+pub fn welch_window(samples: &[f32]) -> Vec<f32> {
+    let half_width = (samples.len().max(2) - 1) as f32 / 2.0;
+    samples
+        .iter()
+        .enumerate()
+        .map(|(i, sample)| {
+            let x = (i as f32 - half_width) / half_width;
+            (1.0 - x * x) * sample
+        })
+        .collect()
+}
+
+/// Applies a Planck-taper window
+/// (<https://en.wikipedia.org/wiki/Window_function#Planck-taper_window>) to an
+/// array of samples. The window is exactly `1.0` in the center and smoothly
+/// (infinitely differentiable) approaches `0.0` at both ends. It is popular
+/// for the analysis of short bursts, e.g., in gravitational-wave astronomy.
+///
+/// ## Parameters
+/// * `samples` Samples to apply the window to.
+/// * `epsilon` Fraction of the window that is tapered on each side. Must be
+///             in interval `(0.0; 0.5]`. `0.5` means there is no flat
+///             region.
+///
+/// ## Return value
+/// New vector with Planck-taper window applied to the values.
+///
+/// ## Panics
+/// If `epsilon` is not in interval `(0.0; 0.5]`.
+#[must_use]
+pub fn planck_taper_window(samples: &[f32], epsilon: f32) -> Vec<f32> {
+    assert!(
+        epsilon > 0.0 && epsilon <= 0.5,
+        "epsilon must be in interval (0.0; 0.5]"
+    );
+    let last_index = samples.len().max(1) - 1;
+    let taper_len = epsilon * last_index as f32;
+    samples
+        .iter()
+        .enumerate()
+        .map(|(i, sample)| {
+            // distance to the closer edge of the window
+            let distance = i.min(last_index - i) as f32;
+            let multiplier = if distance == 0.0 {
+                0.0
+            } else if distance < taper_len {
+                1.0 / (1.0 + expf(taper_len / distance - taper_len / (taper_len - distance)))
+            } else {
+                1.0
+            };
+            multiplier * sample
+        })
+        .collect()
+}
+
+/// Applies an exponential window to an array of samples. The window starts
+/// at `1.0` and decays exponentially to `end_fraction` at the last sample.
+/// This is the standard window for the response (ring-down) signal in
+/// impact/modal testing, as it forces the signal to (almost) zero at the end
+/// of the window and, thus, suppresses leakage caused by truncation.
+///
+/// The window adds artificial damping to the signal: a measured decay rate
+/// `sigma_measured` (in 1/s) is biased by `-ln(end_fraction) / T`, where `T`
+/// is the duration of the window in seconds. Subtract that value to get the
+/// real decay rate. Equivalently, for a mode at `f_n` Hertz, the measured
+/// damping ratio is biased by `-ln(end_fraction) / (2 * PI * f_n * T)`.
+///
+/// ## Parameters
+/// * `samples` Samples to apply the window to.
+/// * `end_fraction` Amplitude of the window at the last sample relative to
+///                  the first sample. Must be in interval `(0.0; 1.0]`.
+///
+/// ## Return value
+/// New vector with the exponential window applied to the values.
+///
+/// ## Panics
+/// If `end_fraction` is not in interval `(0.0; 1.0]`.
+#[must_use]
+pub fn exponential_window(samples: &[f32], end_fraction: f32) -> Vec<f32> {
+    assert!(
+        end_fraction > 0.0 && end_fraction <= 1.0,
+        "end_fraction must be in interval (0.0; 1.0]"
+    );
+    let last_index = (samples.len().max(2) - 1) as f32;
+    samples
+        .iter()
+        .enumerate()
+        .map(|(i, sample)| powf(end_fraction, i as f32 / last_index) * sample)
+        .collect()
+}
+
+/// Applies a force window to an array of samples. The window is `1.0` for
+/// the first `pulse_fraction` of the samples and `0.0` afterwards. It is
+/// used for the excitation (force) signal of an impact hammer to remove
+/// noise after the actual impulse. It is usually combined with
+/// [`exponential_window`] for the response signal. In that case, the force
+/// signal should be weighted by the same exponential window as well, so that
+/// both signals have the same artificial damping.
+///
+/// ## Parameters
+/// * `samples` Samples to apply the window to.
+/// * `pulse_fraction` Fraction of the window that contains the impulse. Must
+///                    be in interval `[0.0; 1.0]`.
+///
+/// ## Return value
+/// New vector with the force window applied to the values.
+///
+/// ## Panics
+/// If `pulse_fraction` is not in interval `[0.0; 1.0]`.
+#[must_use]
+pub fn force_window(samples: &[f32], pulse_fraction: f32) -> Vec<f32> {
+    assert!(
+        (0.0..=1.0).contains(&pulse_fraction),
+        "pulse_fraction must be in interval [0.0; 1.0]"
+    );
+    let pulse_len = (samples.len() as f32 * pulse_fraction) as usize;
+    samples
+        .iter()
+        .enumerate()
+        .map(|(i, sample)| if i < pulse_len { *sample } else { 0.0 })
+        .collect()
+}
+
+/// Multiplies two windows element-wise, e.g., Hann × Tukey, for a custom
+/// trade-off between the width of the main lobe and the height of the side
+/// lobes. The coefficients of a window function of this module are obtained
+/// by applying it to ones, e.g., `hann_window(&[1.0; 1024])`. Apply the
+/// combined window with [`apply_custom_window`].
+///
+/// ## Parameters
+/// * `a` Coefficients of the first window.
+/// * `b` Coefficients of the second window. Must have the same length as
+///       `a`.
+///
+/// ## Return value
+/// Coefficients of the combined window.
+///
+/// ## Errors
+/// [`SpectrumAnalyzerError::WindowLengthMismatch`] if the lengths differ.
+pub fn combine_windows(a: &[f32], b: &[f32]) -> Result<Vec<f32>, SpectrumAnalyzerError> {
+    // the product is commutative; this order reports the length of `a` first
+    apply_custom_window(b, a)
+}
+
+/// Applies a window, given by its coefficients, to an array of samples.
+/// This is useful for windows that this module doesn't provide, such as a
+/// window from [`combine_windows`].
+///
+/// ## Parameters
+/// * `samples` Samples to apply the window to.
+/// * `coefficients` Coefficients of the window. Must have the same length as
+///                  `samples`.
+///
+/// ## Return value
+/// New vector with the window applied to the values.
+///
+/// ## Errors
+/// [`SpectrumAnalyzerError::WindowLengthMismatch`] if the lengths differ.
+pub fn apply_custom_window(
+    samples: &[f32],
+    coefficients: &[f32],
+) -> Result<Vec<f32>, SpectrumAnalyzerError> {
+    if samples.len() != coefficients.len() {
+        return Err(SpectrumAnalyzerError::WindowLengthMismatch(
+            coefficients.len(),
+            samples.len(),
+        ));
+    }
+    Ok(samples
+        .iter()
+        .zip(coefficients)
+        .map(|(sample, coefficient)| sample * coefficient)
+        .collect())
+}
+
+/// Returns the coherent gain of a window, i.e., the average of its
+/// coefficients. This is the counterpart of [`WindowType::coherent_gain`] for
+/// custom coefficients, e.g., for [`crate::SpectrumConfig::coherent_gain`].
+///
+/// ## Return value
+/// `sum(coefficients) / coefficients.len()`, or `1.0` for no coefficients.
+#[must_use]
+pub fn coherent_gain(coefficients: &[f32]) -> f32 {
+    if coefficients.is_empty() {
+        return 1.0;
+    }
+    coefficients.iter().sum::<f32>() / coefficients.len() as f32
+}
+
+/// Like [`apply_custom_window`] but returns the windowed samples directly as
+/// complex numbers with an imaginary part of `0.0`, e.g., as input for a
+/// complex FFT. In contrast to applying the window and converting the result
+/// afterwards, this needs only one pass over the samples and one allocation.
+///
+/// ## Parameters
+/// * `samples` Samples to apply the window to.
+/// * `window` Coefficients of the window. Must have the same length as
+///            `samples`.
+///
+/// ## Return value
+/// New vector with the windowed samples as complex numbers.
+///
+/// ## Errors
+/// [`SpectrumAnalyzerError::WindowLengthMismatch`] if the lengths differ.
+pub fn windowed_samples_to_complex(
+    samples: &[f32],
+    window: &[f32],
+) -> Result<Vec<Complex32>, SpectrumAnalyzerError> {
+    if samples.len() != window.len() {
+        return Err(SpectrumAnalyzerError::WindowLengthMismatch(
+            window.len(),
+            samples.len(),
+        ));
+    }
+    Ok(samples
+        .iter()
+        .zip(window)
+        .map(|(sample, coefficient)| Complex32::new(sample * coefficient, 0.0))
+        .collect())
+}
+
+/// Normalized sinc function `sin(PI * x) / (PI * x)`, i.e., the Fourier
+/// transform of the rectangular window at `x` bins.
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        let x = core::f64::consts::PI * x;
+        libm::sin(x) / x
+    }
+}
 
-    blackman_harris_xterm(samples, &ALPHA)
+/// Fourier transform of the Welch window, i.e., of the parabola `1 - t * t`
+/// for `t` in `[-1; 1]`, at `x` bins, relative to the center.
+fn welch_kernel(x: f64) -> f64 {
+    let x = core::f64::consts::PI * x;
+    if x.abs() < 1e-3 {
+        // limit for `x -> 0`, avoids the cancellation
+        1.0
+    } else {
+        (3.0 * (libm::sin(x) - x * libm::cos(x)) / (x * x * x)).abs()
+    }
 }
 
 /// Applies a Blackman-Harris x-term window
@@ -117,24 +503,260 @@ fn blackman_harris_xterm(samples: &[f32], alphas: &[f32]) -> Vec<f32> {
 
     let samples_len_f32 = samples.len() as f32;
 
-    for sample in samples.iter() {
+    for (i, sample) in samples.iter().enumerate() {
         // Will result in something like that:
         /* ALPHA0
-            + ALPHA1 * ((2.0 * PI * *samples[i])/samples_len_f32).cos()
-            + ALPHA2 * ((4.0 * PI * *samples[i])/samples_len_f32).cos()
-            + ALPHA3 * ((6.0 * PI * *samples[i])/samples_len_f32).cos()
+            + ALPHA1 * ((2.0 * PI * i)/samples_len_f32).cos()
+            + ALPHA2 * ((4.0 * PI * i)/samples_len_f32).cos()
+            + ALPHA3 * ((6.0 * PI * i)/samples_len_f32).cos()
         */
 
-        let mut acc = 0.0;
+        let mut multiplier = 0.0;
         for (alpha_i, alpha) in alphas.iter().enumerate() {
             // in 1. iter. 0PI, then 2PI, then 4 PI, then 6 PI
             let two_pi_iteration = 2.0 * alpha_i as f32 * PI;
-            let cos = cosf((two_pi_iteration * sample) / samples_len_f32);
-            acc += alpha * cos;
+            let cos = cosf((two_pi_iteration * i as f32) / samples_len_f32);
+            multiplier += alpha * cos;
         }
 
-        windowed_samples.push(acc)
+        windowed_samples.push(multiplier * sample)
     }
 
     windowed_samples
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rectangular_window() {
+        let samples = [1.0, -2.0, 3.5, 0.0];
+        assert_eq!(rectangular_window(&samples), samples);
+        assert_eq!(WindowType::Rectangular.apply(&samples), samples);
+        assert_eq!(WindowType::Rectangular.coherent_gain(), 1.0);
+        assert_eq!(WindowType::Rectangular.enbw(), 1.0);
+    }
+
+    #[test]
+    fn test_exponential_window() {
+        let samples = [1.0; 64];
+        let windowed = exponential_window(&samples, 0.01);
+        assert_eq!(windowed[0], 1.0);
+        float_cmp::assert_approx_eq!(f32, windowed[63], 0.01, epsilon = 1e-6);
+        assert!(windowed.windows(2).all(|pair| pair[0] > pair[1]));
+
+        let windowed = exponential_window(&[2.0; 16], 0.5);
+        float_cmp::assert_approx_eq!(f32, windowed[15], 1.0, epsilon = 1e-6);
+
+        assert_eq!(exponential_window(&samples, 1.0), samples);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_exponential_window_invalid_fraction() {
+        let _ = exponential_window(&[1.0; 8], 0.0);
+    }
+
+    #[test]
+    fn test_force_window() {
+        let samples = [3.0; 8];
+        assert_eq!(
+            force_window(&samples, 0.25),
+            [3.0, 3.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]
+        );
+        assert_eq!(force_window(&samples, 1.0), samples);
+        assert_eq!(force_window(&samples, 0.0), [0.0; 8]);
+    }
+
+    #[test]
+    fn test_welch_window() {
+        const N: usize = 16;
+        let windowed = welch_window(&[1.0; N]);
+        for (n, value) in windowed.iter().enumerate() {
+            let half_width = (N - 1) as f32 / 2.0;
+            let expected = 1.0 - ((n as f32 - half_width) / half_width).powi(2);
+            float_cmp::assert_approx_eq!(f32, *value, expected, epsilon = 1e-6);
+        }
+        assert_eq!(windowed[0], 0.0);
+        assert_eq!(windowed[N - 1], 0.0);
+    }
+
+    #[test]
+    fn test_planck_taper_window() {
+        let windowed = planck_taper_window(&[1.0; 101], 0.1);
+        assert_eq!(windowed[0], 0.0);
+        assert_eq!(windowed[100], 0.0);
+        // flat center region
+        assert!(windowed[10..=90].iter().all(|&x| x == 1.0));
+        // smooth and monotonic taper on both sides
+        for i in 0..10 {
+            assert!(windowed[i] < windowed[i + 1]);
+            assert_eq!(windowed[i], windowed[100 - i]);
+        }
+        assert!(windowed[1] < 1e-3);
+        assert!(windowed[9] > 0.99);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_planck_taper_window_invalid_epsilon() {
+        let _ = planck_taper_window(&[1.0; 8], 0.6);
+    }
+
+    #[test]
+    fn test_window_type_apply() {
+        let samples = [1.0, -2.0, 3.5, 0.0, 7.0, 1.0, 1.0, 2.0];
+        assert_eq!(WindowType::Hann.apply(&samples), hann_window(&samples));
+        assert_eq!(
+            WindowType::Hamming.apply(&samples),
+            hamming_window(&samples)
+        );
+        assert_eq!(
+            WindowType::BlackmanHarris4Term.apply(&samples),
+            blackman_harris_4term(&samples)
+        );
+        assert_eq!(
+            WindowType::BlackmanHarris7Term.apply(&samples),
+            blackman_harris_7term(&samples)
+        );
+    }
+
+    #[test]
+    fn test_combine_windows() {
+        let ones = [1.0; 64];
+        let hann = hann_window(&ones);
+        let planck = planck_taper_window(&ones, 0.2);
+        let combined = combine_windows(&hann, &planck).unwrap();
+        for ((combined, hann), planck) in combined.iter().zip(&hann).zip(&planck) {
+            assert_eq!(*combined, hann * planck);
+        }
+
+        let samples = (0..64).map(|i| i as f32).collect::<Vec<_>>();
+        let windowed = apply_custom_window(&samples, &hann).unwrap();
+        assert_eq!(windowed, hann_window(&samples));
+
+        assert!(matches!(
+            combine_windows(&hann, &[1.0; 63]),
+            Err(SpectrumAnalyzerError::WindowLengthMismatch(64, 63))
+        ));
+        assert!(matches!(
+            apply_custom_window(&samples, &[1.0; 32]),
+            Err(SpectrumAnalyzerError::WindowLengthMismatch(32, 64))
+        ));
+    }
+
+    #[test]
+    fn test_windowed_samples_to_complex() {
+        let samples = (0..64).map(|i| i as f32).collect::<Vec<_>>();
+        let hann = hann_window(&[1.0; 64]);
+        let complex = windowed_samples_to_complex(&samples, &hann).unwrap();
+        let windowed = apply_custom_window(&samples, &hann).unwrap();
+        assert_eq!(complex.len(), 64);
+        for (complex, windowed) in complex.iter().zip(&windowed) {
+            assert_eq!(*complex, Complex32::new(*windowed, 0.0));
+        }
+
+        assert!(matches!(
+            windowed_samples_to_complex(&samples, &[1.0; 32]),
+            Err(SpectrumAnalyzerError::WindowLengthMismatch(32, 64))
+        ));
+    }
+
+    #[test]
+    fn test_hamming_window() {
+        let coefficients = hamming_window(&[1.0; 5]);
+        for (coefficient, expected) in coefficients.iter().zip([0.08, 0.54, 1.0, 0.54, 0.08]) {
+            float_cmp::assert_approx_eq!(f32, *coefficient, expected, epsilon = 1e-6);
+        }
+        // the window is multiplied with the samples
+        let windowed = hamming_window(&[2.0, -3.0, 2.0]);
+        for (val, expected) in windowed.iter().zip([0.16, -3.0, 0.16]) {
+            float_cmp::assert_approx_eq!(f32, *val, expected, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_blackman_harris_window() {
+        // periodic window: zero (almost) at the beginning, one in the center
+        let coefficients = blackman_harris_4term(&[1.0; 4]);
+        let expected = [0.00006, 0.21747, 1.0, 0.21747];
+        for (coefficient, expected) in coefficients.iter().zip(expected) {
+            float_cmp::assert_approx_eq!(f32, *coefficient, expected, epsilon = 1e-6);
+        }
+        let coefficients = blackman_harris_7term(&[1.0; 4]);
+        let expected = [0.000_000_06, 0.063_726_25, 1.0, 0.063_726_25];
+        for (coefficient, expected) in coefficients.iter().zip(expected) {
+            float_cmp::assert_approx_eq!(f32, *coefficient, expected, epsilon = 1e-6);
+        }
+        // the window is multiplied with the samples
+        let samples = [3.0, -2.0, 0.5, 1.0];
+        let windowed = blackman_harris_4term(&samples);
+        for (i, val) in windowed.iter().enumerate() {
+            let expected = blackman_harris_4term(&[1.0; 4])[i] * samples[i];
+            float_cmp::assert_approx_eq!(f32, *val, expected, ulps = 2);
+        }
+
+        // the average equals the coherent gain
+        for window in [
+            WindowType::BlackmanHarris4Term,
+            WindowType::BlackmanHarris7Term,
+        ] {
+            float_cmp::assert_approx_eq!(
+                f32,
+                coherent_gain(&window.apply(&[1.0; 1024])),
+                window.coherent_gain(),
+                epsilon = 1e-5
+            );
+        }
+    }
+
+    #[test]
+    fn test_window_type_kernel() {
+        // magnitude of the DFT of the coefficients at `offset_bins`
+        let transform = |coefficients: &[f32], offset_bins: f32| {
+            let len = coefficients.len() as f64;
+            let (re, im) =
+                coefficients
+                    .iter()
+                    .enumerate()
+                    .fold((0.0, 0.0), |(re, im), (n, coefficient)| {
+                        let phase =
+                            2.0 * core::f64::consts::PI * f64::from(offset_bins) * n as f64 / len;
+                        let coefficient = f64::from(*coefficient);
+                        (
+                            re + coefficient * libm::cos(phase),
+                            im - coefficient * libm::sin(phase),
+                        )
+                    });
+            libm::sqrt(re * re + im * im) as f32
+        };
+
+        for window in [
+            WindowType::Rectangular,
+            WindowType::Hann,
+            WindowType::Hamming,
+            WindowType::BlackmanHarris4Term,
+            WindowType::BlackmanHarris7Term,
+            WindowType::Welch,
+        ] {
+            assert_eq!(window.kernel(0.0), 1.0);
+            let coefficients = window.apply(&[1.0; 4096]);
+            let center = transform(&coefficients, 0.0);
+            for offset_bins in [0.25, 0.5, 1.0, 1.5, 2.5] {
+                let expected = transform(&coefficients, offset_bins) / center;
+                assert!(
+                    (window.kernel(offset_bins) - expected).abs() < 1e-3,
+                    "{:?}: {} vs {}",
+                    window,
+                    window.kernel(offset_bins),
+                    expected
+                );
+            }
+        }
+        // zero of the main lobe of the Hann window, the main lobe of a
+        // Blackman-Harris window is much wider
+        assert!(WindowType::Hann.kernel(2.0) < 1e-6);
+        assert!(WindowType::BlackmanHarris4Term.kernel(2.0) > 0.1);
+    }
+}